@@ -6,6 +6,7 @@ impl Command for Build {
         clap::SubCommand::with_name("build")
             .version(*crate::VERSION)
             .about("Build the Project")
+            .args(&super::verify_args())
         // .args(&super::building_args())
     }
 
@@ -19,9 +20,12 @@ impl Command for Build {
                     Box::new(crate::tasks::ValidName {}),
                     Box::new(crate::tasks::ModTime {}),
                     Box::new(crate::tasks::Populate {}),
+                    Box::new(crate::tasks::Mount {}),
                     Box::new(crate::tasks::Prefix::new()),
+                    Box::new(crate::tasks::Lint {}),
+                    Box::new(crate::tasks::Patches {}),
                     Box::new(crate::tasks::Preprocess {}),
-                    Box::new(crate::tasks::Rapify {}),
+                    Box::new(super::rapify_task(args)),
                     Box::new(crate::tasks::Pack {}),
                 ];
                 if args.is_present("force") {
@@ -0,0 +1,44 @@
+use std::io::{Read, Seek};
+
+use hemtt::{HEMTTError, Project};
+use hemtt_arma_config::{simplify::Config, LinterOptions};
+
+/// The `lint` options of the project when there is one, for the style of derapified configs
+///
+/// The working directory is changed to the root of the project, paths from the command line
+/// have to be resolved before.
+pub fn options() -> Result<LinterOptions, HEMTTError> {
+    if Project::find_root().is_err() {
+        return Ok(LinterOptions::default());
+    }
+    Ok(Project::read()?.lint)
+}
+
+/// Reads a rapified config and writes it as source
+pub fn derapify<I: Read + Seek>(
+    input: &mut I,
+    options: &LinterOptions,
+) -> Result<String, HEMTTError> {
+    let config = Config::read_rapified(input).map_err(|e| HEMTTError::Generic(e.to_string()))?;
+    Ok(hemtt_arma_config::derapify::derapify(&config, options))
+}
+
+pub fn run(a: &clap::ArgMatches) -> Result<(), HEMTTError> {
+    let file = a.value_of("file").unwrap();
+    let path = super::super::user_path(file)?;
+    if !path.exists() {
+        return Err(HEMTTError::User(format!("`{}` does not exist", file)));
+    }
+    let output = a
+        .value_of("output")
+        .map(super::super::user_path)
+        .transpose()?;
+    let source = derapify(&mut std::fs::File::open(path)?, &options()?)?;
+    if let Some(output) = output {
+        std::fs::write(&output, source)?;
+        info!("Derapified `{}` to `{}`", file, output.display());
+    } else {
+        print!("{}", source);
+    }
+    Ok(())
+}
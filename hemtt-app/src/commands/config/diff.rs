@@ -0,0 +1,141 @@
+use std::fs::File;
+use std::path::Path;
+
+use hemtt::HEMTTError;
+use hemtt_arma_config::{
+    diff::{diff, Change},
+    simplify::{Class, Config},
+};
+use serde_json::Value;
+
+use crate::tasks::{read_bin, read_pbo};
+
+pub fn run(a: &clap::ArgMatches) -> Result<(), HEMTTError> {
+    let (old_path, new_path) = (
+        Path::new(a.value_of("old").unwrap()),
+        Path::new(a.value_of("new").unwrap()),
+    );
+    let mut old = read(old_path)?;
+    let mut new = read(new_path)?;
+    // Two files with a config each are compared directly, the configs of folders and of PBOs
+    // with several configs are paired by their path
+    if old_path.is_file()
+        && new_path.is_file()
+        && old.len() == 1
+        && new.len() == 1
+        && old[0].0 != new[0].0
+    {
+        let name = format!("{} -> {}", old[0].0, new[0].0);
+        old[0].0 = name.clone();
+        new[0].0 = name;
+    }
+    let empty = Config {
+        root: Class {
+            parent: String::new(),
+            external: false,
+            deletion: false,
+            entries: Vec::new(),
+        },
+        enums: Vec::new(),
+    };
+    // The configs that are in either side, in the order of the old side
+    let mut files: Vec<&str> = old.iter().map(|(name, _)| name.as_str()).collect();
+    for (name, _) in &new {
+        if !files.contains(&name.as_str()) {
+            files.push(name);
+        }
+    }
+    let json = a.value_of("format") == Some("json");
+    let mut content = String::new();
+    let mut found = Vec::new();
+    for file in files {
+        let (old, new) = (find(&old, file), find(&new, file));
+        let changes = diff(old.unwrap_or(&empty), new.unwrap_or(&empty));
+        if json {
+            found.extend(changes.iter().map(|change| {
+                let mut value = change.to_json();
+                value["file"] = Value::String(file.to_string());
+                value
+            }));
+        } else if !changes.is_empty() || old.is_none() || new.is_none() {
+            content.push_str(&text(file, old.is_some(), new.is_some(), &changes));
+        }
+    }
+    if json {
+        content =
+            serde_json::to_string_pretty(&found).map_err(|e| HEMTTError::Generic(e.to_string()))?;
+        content.push('\n');
+    }
+    if let Some(output) = a.value_of("output") {
+        std::fs::write(output, content)?;
+        info!("Wrote the changes to `{}`", output);
+    } else {
+        print!("{}", content);
+    }
+    Ok(())
+}
+
+fn find<'a>(configs: &'a [(String, Config)], file: &str) -> Option<&'a Config> {
+    configs
+        .iter()
+        .find(|(name, _)| name == file)
+        .map(|(_, config)| config)
+}
+
+fn text(file: &str, old: bool, new: bool, changes: &[Change]) -> String {
+    let mut content = format!(
+        "# {}{}\n",
+        file,
+        match (old, new) {
+            (false, _) => " (added)",
+            (_, false) => " (removed)",
+            _ => "",
+        }
+    );
+    for change in changes {
+        content.push_str(&format!("{}\n", change));
+    }
+    content.push('\n');
+    content
+}
+
+/// The configs of a PBO, a `config.bin` or a folder of PBOs, named by their path in it
+fn read(path: &Path) -> Result<Vec<(String, Config)>, HEMTTError> {
+    if !path.exists() {
+        return Err(HEMTTError::User(format!(
+            "`{}` does not exist",
+            path.display()
+        )));
+    }
+    if path.is_file() {
+        if is_pbo(path) {
+            return read_pbo(path);
+        }
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        return Ok(vec![(name, read_bin(&mut File::open(path)?)?)]);
+    }
+    let mut configs = Vec::new();
+    for entry in walkdir::WalkDir::new(path).sort_by(|a, b| a.file_name().cmp(b.file_name())) {
+        let entry = entry.map_err(|e| HEMTTError::Generic(e.to_string()))?;
+        if !entry.file_type().is_file() || !is_pbo(entry.path()) {
+            continue;
+        }
+        let pbo = entry
+            .path()
+            .strip_prefix(path)
+            .unwrap()
+            .display()
+            .to_string()
+            .replace('\\', "/");
+        configs.extend(
+            read_pbo(entry.path())?
+                .into_iter()
+                .map(|(name, config)| (format!("{}/{}", pbo, name), config)),
+        );
+    }
+    Ok(configs)
+}
+
+fn is_pbo(path: &Path) -> bool {
+    matches!(path.extension(), Some(ext) if ext.eq_ignore_ascii_case("pbo"))
+}
@@ -0,0 +1,66 @@
+use hemtt::{HEMTTError, Project};
+use hemtt_arma_config::query::Query;
+
+use crate::{
+    flow::Stage,
+    tasks::{is_built, read_built, write_matches, ConfigQuery, QueryFormat},
+    Flow,
+};
+
+/// Runs the query directly when every file is built, otherwise in the project
+pub fn run(a: &clap::ArgMatches) -> Result<(), HEMTTError> {
+    let query: Query = a
+        .value_of("query")
+        .unwrap()
+        .parse()
+        .map_err(|e: hemtt_arma_config::ArmaConfigError| HEMTTError::User(e.to_string()))?;
+    let files: Vec<&str> = a
+        .values_of("files")
+        .map(Iterator::collect)
+        .unwrap_or_default();
+    let format = QueryFormat::from_name(a.value_of("format").unwrap()).unwrap();
+    let output = a
+        .value_of("output")
+        .map(super::super::user_path)
+        .transpose()?;
+    if !files.is_empty() && files.iter().all(|file| is_built(file)) {
+        let mut configs = Vec::new();
+        for file in files {
+            configs.extend(read_built(file, &super::super::user_path(file)?)?);
+        }
+        return write_matches(&query, &configs, format, output.as_deref());
+    }
+    // Source configs are read from the vfs, relative to the root of the project
+    let root = Project::find_root()?;
+    let files = files
+        .into_iter()
+        .map(|file| {
+            let path = super::super::user_path(file)?;
+            if is_built(file) {
+                return Ok((file.to_string(), path.to_string_lossy().to_string()));
+            }
+            let relative = path
+                .strip_prefix(&root)
+                .map_err(|_| HEMTTError::User(format!("`{}` is not in the project", file)))?;
+            Ok((
+                file.to_string(),
+                relative.to_string_lossy().replace('\\', "/"),
+            ))
+        })
+        .collect::<Result<Vec<_>, HEMTTError>>()?;
+    let p = Project::read()?;
+    let flow = Flow {
+        tasks: vec![
+            Box::new(crate::tasks::Populate {}),
+            Box::new(crate::tasks::Mount {}),
+            Box::new(crate::tasks::Prefix::new()),
+            Box::new(ConfigQuery::new(query, files, format, output)),
+        ],
+    };
+    flow.execute(
+        hemtt::get_all_addons()?,
+        vec![Stage::Check, Stage::PreBuild],
+        &p,
+    )?;
+    Ok(())
+}
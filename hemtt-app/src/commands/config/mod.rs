@@ -0,0 +1,109 @@
+use hemtt::HEMTTError;
+
+use crate::Command;
+
+pub mod derapify;
+mod diff;
+mod get;
+
+pub struct Config;
+impl Command for Config {
+    fn register(&self) -> clap::App {
+        clap::SubCommand::with_name("config")
+            .version(*crate::VERSION)
+            .about("Inspect configs")
+            .subcommand(
+                clap::SubCommand::with_name("get")
+                    .about("Find classes and properties with a query, like `CfgVehicles >> *[scope == 2] >> displayName`")
+                    .arg(
+                        clap::Arg::with_name("query")
+                            .help("The query, names are separated by `>>` and can use `*` and `**`")
+                            .required(true),
+                    )
+                    .arg(
+                        clap::Arg::with_name("files")
+                            .help("Source configs, `config.bin` files or PBOs, the `config.cpp` of every addon by default")
+                            .multiple(true),
+                    )
+                    .arg(
+                        clap::Arg::with_name("format")
+                            .help("Show the matches as text or JSON")
+                            .long("format")
+                            .short("f")
+                            .possible_values(&["text", "json"])
+                            .default_value("text"),
+                    )
+                    .arg(
+                        clap::Arg::with_name("output")
+                            .help("Write to a file instead of the terminal")
+                            .long("output")
+                            .short("o")
+                            .takes_value(true),
+                    ),
+            )
+            .subcommand(
+                clap::SubCommand::with_name("diff")
+                    .about("Show what changed between two builds")
+                    .arg(
+                        clap::Arg::with_name("old")
+                            .help("The old PBO, `config.bin` or folder of PBOs, like a release")
+                            .required(true),
+                    )
+                    .arg(
+                        clap::Arg::with_name("new")
+                            .help("The new PBO, `config.bin` or folder of PBOs")
+                            .required(true),
+                    )
+                    .arg(
+                        clap::Arg::with_name("format")
+                            .help("Show the changes as text or JSON")
+                            .long("format")
+                            .short("f")
+                            .possible_values(&["text", "json"])
+                            .default_value("text"),
+                    )
+                    .arg(
+                        clap::Arg::with_name("output")
+                            .help("Write to a file instead of the terminal")
+                            .long("output")
+                            .short("o")
+                            .takes_value(true),
+                    ),
+            )
+            .subcommand(
+                clap::SubCommand::with_name("derapify")
+                    .about("Write a rapified `config.bin` as a readable config")
+                    .arg(
+                        clap::Arg::with_name("file")
+                            .help("The `config.bin` to derapify")
+                            .required(true),
+                    )
+                    .arg(
+                        clap::Arg::with_name("output")
+                            .help("Write to a file instead of the terminal")
+                            .long("output")
+                            .short("o")
+                            .takes_value(true),
+                    ),
+            )
+    }
+
+    fn can_announce(&self) -> bool {
+        false
+    }
+
+    fn require_project(&self) -> bool {
+        false
+    }
+
+    fn run_no_project(&self, a: &clap::ArgMatches) -> Result<(), HEMTTError> {
+        match a.subcommand() {
+            ("get", Some(b)) => get::run(b),
+            ("derapify", Some(b)) => derapify::run(b),
+            ("diff", Some(b)) => diff::run(b),
+            _ => Err(HEMTTError::User(String::from(
+                "options are `get`, `derapify` and `diff`",
+            ))),
+        }
+    }
+}
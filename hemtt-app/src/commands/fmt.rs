@@ -0,0 +1,25 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use crate::{flow::Stage, tasks::Format, Command, Flow, HEMTTError, Project};
+
+pub struct Fmt {}
+impl Command for Fmt {
+    fn register(&self) -> App {
+        SubCommand::with_name("fmt")
+            .version(*crate::VERSION)
+            .about("Format the configs of the project")
+            .arg(
+                Arg::with_name("check")
+                    .help("Report the files that are not formatted instead of changing them")
+                    .long("check"),
+            )
+    }
+
+    fn run(&self, args: &ArgMatches, p: Project) -> Result<(), HEMTTError> {
+        let flow = Flow {
+            tasks: vec![Box::new(Format::new(args.is_present("check")))],
+        };
+        flow.execute(hemtt::get_all_addons()?, Stage::check(), &p)?;
+        Ok(())
+    }
+}
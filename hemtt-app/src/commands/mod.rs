@@ -1,6 +1,10 @@
 mod bug;
 mod build;
 mod clean;
+mod config;
+mod fmt;
+mod pbo;
+mod preprocess;
 mod project;
 mod release;
 mod template;
@@ -8,6 +12,51 @@ mod template;
 pub use bug::Bug;
 pub use build::Build;
 pub use clean::Clean;
+pub use config::Config;
+pub use fmt::Fmt;
+pub use pbo::Pbo;
+pub use preprocess::Preprocess;
 pub use project::Project;
 pub use release::Release;
 pub use template::Template;
+
+/// A path from the command line, relative to the directory the command was run in
+///
+/// Reading a project changes the working directory to its root, so paths are resolved before.
+/// `..` is resolved without following links, so the path can be compared to the project root.
+fn user_path(path: &str) -> Result<std::path::PathBuf, hemtt::HEMTTError> {
+    let mut resolved = std::path::PathBuf::new();
+    for component in std::env::current_dir()?.join(path).components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                resolved.pop();
+            }
+            _ => resolved.push(component),
+        }
+    }
+    Ok(resolved)
+}
+
+/// The arguments of `build` and `release` to verify rapified configs
+fn verify_args<'a, 'b>() -> Vec<clap::Arg<'a, 'b>> {
+    vec![
+        clap::Arg::with_name("verify-rapify")
+            .help("Read every rapified config back and compare it to its source")
+            .long("verify-rapify"),
+        clap::Arg::with_name("reference")
+            .help("Compare rapified configs byte for byte to the files in a folder with a folder for each addon, like `pbo unpack` creates")
+            .long("reference")
+            .takes_value(true)
+            .requires("verify-rapify"),
+    ]
+}
+
+/// The `Rapify` task for the arguments of `verify_args`
+fn rapify_task(args: &clap::ArgMatches) -> crate::tasks::Rapify {
+    if args.is_present("verify-rapify") {
+        crate::tasks::Rapify::verifying(args.value_of("reference").map(Into::into))
+    } else {
+        crate::tasks::Rapify::default()
+    }
+}
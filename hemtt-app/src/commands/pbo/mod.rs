@@ -0,0 +1,44 @@
+use hemtt::HEMTTError;
+
+use crate::Command;
+
+mod unpack;
+
+pub struct Pbo;
+impl Command for Pbo {
+    fn register(&self) -> clap::App {
+        clap::SubCommand::with_name("pbo")
+            .version(*crate::VERSION)
+            .about("Work with PBOs")
+            .subcommand(
+                clap::SubCommand::with_name("unpack")
+                    .about("Extract the files of a PBO")
+                    .arg(
+                        clap::Arg::with_name("pbo")
+                            .help("The PBO to unpack")
+                            .required(true),
+                    )
+                    .arg(
+                        clap::Arg::with_name("output")
+                            .help("The folder to extract to, named after the PBO by default"),
+                    )
+                    .arg(
+                        clap::Arg::with_name("derapify")
+                            .help("Write rapified configs as readable configs, `config.bin` becomes `config.cpp`")
+                            .long("derapify")
+                            .short("d"),
+                    ),
+            )
+    }
+
+    fn require_project(&self) -> bool {
+        false
+    }
+
+    fn run_no_project(&self, a: &clap::ArgMatches) -> Result<(), HEMTTError> {
+        match a.subcommand() {
+            ("unpack", Some(b)) => unpack::run(b),
+            _ => Err(HEMTTError::User(String::from("options are `unpack`"))),
+        }
+    }
+}
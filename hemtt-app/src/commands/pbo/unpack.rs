@@ -0,0 +1,77 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use hemtt::HEMTTError;
+use hemtt_pbo::ReadablePbo;
+
+use super::super::config::derapify;
+
+pub fn run(a: &clap::ArgMatches) -> Result<(), HEMTTError> {
+    let file = a.value_of("pbo").unwrap();
+    let path = super::super::user_path(file)?;
+    if !path.exists() {
+        return Err(HEMTTError::User(format!("`{}` does not exist", file)));
+    }
+    let output = match a.value_of("output") {
+        Some(output) => super::super::user_path(output)?,
+        None => super::super::user_path(&path.file_stem().unwrap().to_string_lossy())?,
+    };
+    let options = if a.is_present("derapify") {
+        Some(derapify::options()?)
+    } else {
+        None
+    };
+    let mut pbo = ReadablePbo::from(File::open(&path)?)?;
+    let headers = pbo.files();
+    for header in &headers {
+        let path = extract_path(&output, header.filename())?;
+        let mut data = pbo.retrieve(header.filename()).ok_or_else(|| {
+            HEMTTError::Generic(format!("Unable to read `{}`", header.filename()))
+        })?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        match &options {
+            Some(options) if data.get_ref().starts_with(b"\0raP") => {
+                let path = if header.filename().to_lowercase().ends_with("config.bin") {
+                    path.with_file_name("config.cpp")
+                } else {
+                    path
+                };
+                std::fs::write(&path, derapify::derapify(&mut data, options)?)?;
+                debug!("Derapified `{}`", path.display());
+            }
+            _ => std::fs::write(&path, data.get_ref())?,
+        }
+    }
+    if let Some(prefix) = pbo.extension("prefix") {
+        if !headers
+            .iter()
+            .any(|h| h.filename().eq_ignore_ascii_case("$PBOPREFIX$"))
+        {
+            std::fs::write(output.join("$PBOPREFIX$"), prefix)?;
+        }
+    }
+    info!(
+        "Unpacked {} files from `{}` to `{}`",
+        headers.len(),
+        file,
+        output.display()
+    );
+    Ok(())
+}
+
+/// Where a file of the PBO is extracted to, names that would leave the output are refused
+fn extract_path(output: &Path, filename: &str) -> Result<PathBuf, HEMTTError> {
+    let mut path = output.to_path_buf();
+    for part in filename.split(&['\\', '/'][..]) {
+        if part.is_empty() || part == "." || part == ".." || part.contains(':') {
+            return Err(HEMTTError::User(format!(
+                "The PBO contains a file with an unsafe name: `{}`",
+                filename
+            )));
+        }
+        path.push(part);
+    }
+    Ok(path)
+}
@@ -0,0 +1,63 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use crate::{
+    flow::Stage,
+    tasks::{Inspect, InspectFormat},
+    Command, Flow, HEMTTError, Project,
+};
+
+pub struct Preprocess {}
+impl Command for Preprocess {
+    fn register(&self) -> App {
+        SubCommand::with_name("preprocess")
+            .version(*crate::VERSION)
+            .about("Preprocess a single file, to inspect the output of its macros")
+            .visible_alias("inspect")
+            .arg(
+                Arg::with_name("file")
+                    .help("The file to preprocess, relative to the project root")
+                    .required(true),
+            )
+            .arg(
+                Arg::with_name("format")
+                    .help("Show the text, the source map, an HTML page linking to the sources, or the macro expansions")
+                    .long("format")
+                    .short("f")
+                    .possible_values(&["text", "map", "html", "trace"])
+                    .default_value("text"),
+            )
+            .arg(
+                Arg::with_name("output")
+                    .help("Write to a file instead of the terminal")
+                    .long("output")
+                    .short("o")
+                    .takes_value(true),
+            )
+    }
+
+    fn can_announce(&self) -> bool {
+        false
+    }
+
+    fn run(&self, args: &ArgMatches, p: Project) -> Result<(), HEMTTError> {
+        let format = InspectFormat::from_name(args.value_of("format").unwrap()).unwrap();
+        let flow = Flow {
+            tasks: vec![
+                Box::new(crate::tasks::Populate {}),
+                Box::new(crate::tasks::Mount {}),
+                Box::new(crate::tasks::Prefix::new()),
+                Box::new(Inspect::new(
+                    args.value_of("file").unwrap().to_string(),
+                    format,
+                    args.value_of("output").map(Into::into),
+                )),
+            ],
+        };
+        flow.execute(
+            hemtt::get_all_addons()?,
+            vec![Stage::Check, Stage::PreBuild],
+            &p,
+        )?;
+        Ok(())
+    }
+}
@@ -6,6 +6,7 @@ impl Command for Release {
         clap::SubCommand::with_name("release")
             .version(*crate::VERSION)
             .about("Release the Project")
+            .args(&super::verify_args())
     }
 
     fn run(&self, args: &clap::ArgMatches, p: Project) -> Result<(), HEMTTError> {
@@ -19,9 +20,12 @@ impl Command for Release {
                     Box::new(crate::tasks::NotEmpty {}),
                     Box::new(crate::tasks::ValidName {}),
                     Box::new(crate::tasks::Populate {}),
+                    Box::new(crate::tasks::Mount {}),
                     Box::new(crate::tasks::Prefix::new()),
+                    Box::new(crate::tasks::Lint {}),
+                    Box::new(crate::tasks::Patches {}),
                     Box::new(crate::tasks::Preprocess {}),
-                    Box::new(crate::tasks::Rapify {}),
+                    Box::new(super::rapify_task(args)),
                     Box::new(crate::tasks::Pack {}),
                     Box::new(crate::tasks::Sign::default()),
                 ]
@@ -84,6 +84,10 @@ pub fn execute(input: &[String], root: bool) -> Result<(), HEMTTError> {
     commands.push(Box::new(commands::Bug {}));
     commands.push(Box::new(commands::Build {}));
     commands.push(Box::new(commands::Clean {}));
+    commands.push(Box::new(commands::Config {}));
+    commands.push(Box::new(commands::Fmt {}));
+    commands.push(Box::new(commands::Pbo {}));
+    commands.push(Box::new(commands::Preprocess {}));
     commands.push(Box::new(commands::Project {}));
     commands.push(Box::new(commands::Release {}));
     commands.push(Box::new(commands::Template {}));
@@ -27,7 +27,7 @@ impl Task for Pack {
             let entry = entry?;
             if entry.filename().contains(".ht.")
                 || entry.filename().starts_with('$')
-                || entry.filename().ends_with(".hemtt_map")
+                || entry.filename().contains(".hemtt_")
             {
                 continue;
             }
@@ -1,8 +1,14 @@
 use std::path::PathBuf;
 
-use vfs::VfsFileType;
+use hemtt::ConfigError;
+use hemtt_arma_config::{
+    simplify::Config,
+    verify::{self, Mismatch},
+    ArmaConfigError, Flag, Pragmas,
+};
+use vfs::{VfsFileType, VfsPath};
 
-use crate::{context::AddonContext, HEMTTError, Stage, Task};
+use crate::{context::AddonContext, tasks::read_source, HEMTTError, Stage, Task};
 
 pub fn can_rapify(path: &str) -> bool {
     let path = PathBuf::from(path);
@@ -17,7 +23,26 @@ pub fn can_rapify(path: &str) -> bool {
     ["cpp", "rvmat", "ext"].contains(&name)
 }
 
-pub struct Rapify {}
+/// Rapifies the configs of the addons
+///
+/// When verifying, every rapified config is read back and compared to its source, and to the
+/// file at the same path in the addon's folder of `reference`. The folder is named after the
+/// addon's PBO like `pbo unpack` names it, or after the addon.
+#[derive(Default)]
+pub struct Rapify {
+    verify: bool,
+    reference: Option<PathBuf>,
+}
+
+impl Rapify {
+    pub fn verifying(reference: Option<PathBuf>) -> Self {
+        Self {
+            verify: true,
+            reference,
+        }
+    }
+}
+
 impl Task for Rapify {
     fn name(&self) -> String {
         String::from("rapify")
@@ -31,30 +56,147 @@ impl Task for Rapify {
         for entry in ctx.global().vfs().join(ctx.addon().source())?.walk_dir()? {
             let entry = entry?;
             if entry.metadata()?.file_type == VfsFileType::File && can_rapify(entry.as_str()) {
-                ctx.debug(&format!("rapify: {:?}", entry.as_str()));
-                let mut buf = String::new();
-                entry.open_file()?.read_to_string(&mut buf)?;
-                let mut map = entry
-                    .parent()
-                    .unwrap()
-                    .join(entry.filename() + ".hemtt_map")?
-                    .open_file()?;
-                let simplified =
-                    hemtt_arma_config::simplify::Config::from_ast(hemtt_arma_config::parse(
-                        &buf,
-                        entry.as_str(),
-                        Some(serde_json::from_reader(&mut map).unwrap()),
-                    )?)
-                    .unwrap();
-                let mut out = if entry.filename() == "config.cpp" {
-                    entry.parent().unwrap().join("config.bin")?
-                } else {
-                    entry
+                if let Err(e) = self.rapify(entry, ctx) {
+                    ctx.error(&format!("{}", e));
+                    ctx.set_failed(e);
                 }
-                .create_file()?;
-                simplified.write_rapified(&mut out).unwrap();
             }
         }
         Ok(())
     }
 }
+
+impl Rapify {
+    fn rapify(&self, entry: VfsPath, ctx: &mut AddonContext) -> Result<(), HEMTTError> {
+        let pragmas = entry
+            .parent()
+            .unwrap()
+            .join(entry.filename() + ".hemtt_pragma")?;
+        if pragmas.exists()? {
+            let pragmas: Pragmas = serde_json::from_reader(pragmas.open_file()?).map_err(|e| {
+                HEMTTError::Generic(format!("Unable to read `{}`: {}", pragmas.as_str(), e))
+            })?;
+            if pragmas.flag(Flag::PboNoBinarize) {
+                ctx.debug(&format!("not rapifying {:?}", entry.as_str()));
+                return Ok(());
+            }
+        }
+        ctx.debug(&format!("rapify: {:?}", entry.as_str()));
+        let mut buf = String::new();
+        entry.open_file()?.read_to_string(&mut buf)?;
+        let map_path = entry
+            .parent()
+            .unwrap()
+            .join(entry.filename() + ".hemtt_map")?;
+        let map = serde_json::from_reader(map_path.open_file()?).map_err(|e| {
+            HEMTTError::Generic(format!("Unable to read `{}`: {}", map_path.as_str(), e))
+        })?;
+        let vfs = ctx.global().vfs();
+        let report = |e: ArmaConfigError| {
+            HEMTTError::Config(ConfigError {
+                path: Some(entry.as_str().to_string()),
+                message: e.report(|file| read_original(vfs, file)),
+                source: Box::new(HEMTTError::Generic(e.to_string())),
+            })
+        };
+        let ast = hemtt_arma_config::parse(&buf, entry.as_str(), Some(map)).map_err(&report)?;
+        let simplified = Config::from_ast(ast).map_err(&report)?;
+        let out = if entry.filename() == "config.cpp" {
+            entry.parent().unwrap().join("config.bin")?
+        } else {
+            entry.clone()
+        };
+        let mut data = Vec::new();
+        simplified
+            .write_rapified(&mut data)
+            .map_err(|e| HEMTTError::Generic(e.to_string()))?;
+        out.create_file()?.write_all(&data)?;
+        if self.verify {
+            self.verify(&out, &simplified, &data, ctx)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a rapified config back and compares it to its source and the reference
+    fn verify(
+        &self,
+        out: &VfsPath,
+        source: &Config,
+        data: &[u8],
+        ctx: &AddonContext,
+    ) -> Result<(), HEMTTError> {
+        let fail = |mismatch: Mismatch| {
+            HEMTTError::Generic(format!(
+                "Rapified `{}` does not match: {}",
+                out.as_str(),
+                mismatch
+            ))
+        };
+        let read = Config::read_rapified(&mut std::io::Cursor::new(data)).map_err(|e| {
+            HEMTTError::Generic(format!(
+                "Rapified `{}` can not be read back: {}",
+                out.as_str(),
+                e
+            ))
+        })?;
+        if let Some(mismatch) = verify::compare(source, &read) {
+            return Err(fail(mismatch));
+        }
+        if let Some(reference) = &self.reference {
+            let path = out
+                .as_str()
+                .trim_start_matches('/')
+                .strip_prefix(ctx.addon().source())
+                .and_then(|path| path.strip_prefix('/'))
+                .ok_or_else(|| {
+                    HEMTTError::Generic(format!(
+                        "Rapified `{}` is not in the addon `{}`",
+                        out.as_str(),
+                        ctx.addon().source()
+                    ))
+                })?;
+            let tried = reference_folders(ctx)
+                .into_iter()
+                .map(|folder| reference.join(folder).join(path))
+                .collect::<Vec<_>>();
+            if let Some(file) = tried.iter().find(|file| file.exists()) {
+                if let Some(mismatch) = verify::compare_bytes(&std::fs::read(file)?, data) {
+                    return Err(fail(mismatch));
+                }
+                ctx.debug(&format!("`{}` matches `{}`", out.as_str(), file.display()));
+            } else {
+                ctx.warn(&format!(
+                    "No reference for `{}`, tried {}",
+                    out.as_str(),
+                    tried
+                        .iter()
+                        .map(|file| format!("`{}`", file.display()))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The folders of `reference` the addon's files can be in, `pbo unpack` names the folder after
+/// the PBO, which has the project prefix
+fn reference_folders(ctx: &AddonContext) -> Vec<String> {
+    let pbo = ctx.addon().pbo(Some(ctx.global().project().prefix()));
+    let mut folders = vec![pbo.trim_end_matches(".pbo").to_string()];
+    if folders[0] != ctx.addon().name() {
+        folders.push(ctx.addon().name().to_string());
+    }
+    folders
+}
+
+/// Reads a source file as it was before it was preprocessed
+///
+/// The addons' files in the vfs have been replaced by their preprocessed output,
+/// so they are read from the project folder, anything else is read from the vfs
+fn read_original(vfs: &VfsPath, file: &str) -> Option<String> {
+    std::fs::read_to_string(file.trim_start_matches('/'))
+        .ok()
+        .or_else(|| read_source(vfs, file))
+}
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use hemtt_arma_config::{Rendered, AST};
+use vfs::{impls::overlay::OverlayFS, MemoryFS, VfsFileType, VfsPath};
+
+use crate::{
+    context::{AddonContext, AddonListContext, Context},
+    tasks::{can_populate, destination, populate, preprocess_file, MountMap, PrefixMap},
+    HEMTTError,
+};
+
+/// The files of the addons as checks see them
+///
+/// The templates of the addons are rendered to a layer over the vfs that is only used by
+/// checks. Includes are resolved from the project's addons and include paths, and from the
+/// mounts when the `Mount` task ran before.
+pub struct CheckFs {
+    vfs: VfsPath,
+    prefixes: PrefixMap,
+}
+
+impl CheckFs {
+    /// Sets up the files for the checks, once for all tasks that use them
+    pub fn init(ctx: &mut AddonListContext) -> Result<(), HEMTTError> {
+        if ctx.global().container.try_get::<Self>().is_some() {
+            return Ok(());
+        }
+        let vfs: VfsPath =
+            OverlayFS::new(&[MemoryFS::new().into(), ctx.global().vfs().clone()]).into();
+        let mut prefixes = HashMap::new();
+        for addon in ctx.mut_addons() {
+            prefixes.insert(
+                addon.prefix().to_string(),
+                addon.addon().source().to_string(),
+            );
+            let templates = vfs
+                .join(addon.addon().source())?
+                .walk_dir()?
+                .collect::<Result<Vec<_>, _>>()?;
+            for entry in templates {
+                if entry.metadata()?.file_type == VfsFileType::File && can_populate(entry.as_str())
+                {
+                    populate(entry.clone(), destination(entry)?, addon)?;
+                }
+            }
+        }
+        if let Some(mounts) = ctx.global().container.try_get::<MountMap>() {
+            for (prefix, path) in mounts.inner() {
+                prefixes
+                    .entry(prefix.to_owned())
+                    .or_insert_with(|| path.to_owned());
+            }
+        }
+        ctx.global().container.set(Self {
+            vfs,
+            prefixes: PrefixMap::new(prefixes),
+        });
+        Ok(())
+    }
+
+    pub fn get<'a>(ctx: &'a Context) -> &'a Self {
+        ctx.container.get::<Self>()
+    }
+
+    pub fn vfs(&self) -> &VfsPath {
+        &self.vfs
+    }
+
+    /// Preprocesses and parses a file of an addon
+    ///
+    /// A file that can not be preprocessed or parsed is left for the build to report, `None` is
+    /// returned after warning that it is not checked
+    pub fn parse(&self, entry: &VfsPath, ctx: &AddonContext) -> Option<(Rendered, AST)> {
+        let render = match preprocess_file(
+            entry,
+            ctx.global(),
+            &self.vfs,
+            &self.prefixes,
+            ctx.addon().source(),
+            false,
+        ) {
+            Ok((render, _)) => render,
+            Err(e) => {
+                ctx.warn(&format!("not checking `{}`: {}", entry.as_str(), e));
+                return None;
+            }
+        };
+        match hemtt_arma_config::parse(&render.export(), entry.as_str(), None) {
+            Ok(ast) => Some((render, ast)),
+            Err(e) => {
+                ctx.warn(&format!("not checking `{}`: {}", entry.as_str(), e));
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::path::Path;
+
+use hemtt::ConfigError;
+use hemtt_arma_config::cst::Cst;
+
+use crate::{context::AddonContext, HEMTTError, Stage, Task};
+
+fn can_format(path: &Path) -> bool {
+    if path.display().to_string().contains(".ht.") {
+        return false;
+    }
+    let ext = path
+        .extension()
+        .unwrap_or_else(|| std::ffi::OsStr::new(""))
+        .to_str()
+        .unwrap();
+    ["cpp", "hpp", "ext"].contains(&ext)
+}
+
+/// Formats the configs of an addon in place, or reports the ones that are not formatted
+#[derive(Clone)]
+pub struct Format {
+    check: bool,
+}
+impl Format {
+    pub const fn new(check: bool) -> Self {
+        Self { check }
+    }
+}
+impl Task for Format {
+    fn name(&self) -> String {
+        String::from("format")
+    }
+
+    fn hooks(&self) -> &[Stage] {
+        &[Stage::Check]
+    }
+
+    fn check(&self, ctx: &mut AddonContext) -> Result<(), HEMTTError> {
+        let options = ctx.global().project().lint.clone();
+        for entry in walkdir::WalkDir::new(ctx.addon().source()) {
+            let entry = entry.map_err(|e| HEMTTError::Generic(e.to_string()))?;
+            if !entry.file_type().is_file() || !can_format(entry.path()) {
+                continue;
+            }
+            let path = entry.path().display().to_string();
+            let source = std::fs::read_to_string(entry.path())?;
+            let cst = match Cst::parse(source.as_str(), &path) {
+                Ok(cst) => cst,
+                Err(e) => {
+                    let e = HEMTTError::Config(ConfigError {
+                        path: Some(path.clone()),
+                        message: e.report(|file| std::fs::read_to_string(file).ok()),
+                        source: Box::new(HEMTTError::Generic(e.to_string())),
+                    });
+                    ctx.error(&format!("{}", e));
+                    ctx.set_failed(e);
+                    continue;
+                }
+            };
+            let formatted = hemtt_arma_config::format::format(&cst, &options);
+            if formatted == source {
+                continue;
+            }
+            if self.check {
+                ctx.error(&format!("`{}` is not formatted", path));
+                ctx.set_failed(HEMTTError::User(format!("`{}` is not formatted", path)));
+            } else {
+                ctx.info(&format!("formatted `{}`", path));
+                std::fs::write(entry.path(), formatted)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+use hemtt_arma_config::{Level, LinterOptions};
+use vfs::{VfsFileType, VfsPath};
+
+use super::CheckFs;
+use crate::{
+    context::{AddonContext, AddonListContext},
+    tasks::{can_preprocess, read_source},
+    HEMTTError, Stage, Task,
+};
+
+/// Runs the config lints on the addons' files
+///
+/// Files are preprocessed on their own from the files of `CheckFs`.
+pub struct Lint;
+impl Task for Lint {
+    fn name(&self) -> String {
+        String::from("lint")
+    }
+
+    fn hooks(&self) -> &[Stage] {
+        &[Stage::Check]
+    }
+
+    fn check_single(&self, ctx: &mut AddonListContext) -> Result<(), HEMTTError> {
+        CheckFs::init(ctx)
+    }
+
+    fn check(&self, ctx: &mut AddonContext) -> Result<(), HEMTTError> {
+        let options = ctx.global().project().lint.clone();
+        let fs = CheckFs::get(ctx.global());
+        let mut errors = 0;
+        for entry in fs.vfs().join(ctx.addon().source())?.walk_dir()? {
+            let entry = entry?;
+            if entry.metadata()?.file_type == VfsFileType::File && can_preprocess(entry.as_str()) {
+                errors += lint(&entry, ctx, fs, &options);
+            }
+        }
+        if errors > 0 {
+            ctx.set_failed(HEMTTError::User(format!(
+                "{} lint error{} in `{}`",
+                errors,
+                if errors == 1 { "" } else { "s" },
+                ctx.addon().source()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Lints a single file, returns how many errors were found
+fn lint(entry: &VfsPath, ctx: &AddonContext, fs: &CheckFs, options: &LinterOptions) -> usize {
+    let (render, ast) = match fs.parse(entry, ctx) {
+        Some(parsed) => parsed,
+        None => return 0,
+    };
+    let mut errors = 0;
+    for lint in hemtt_arma_config::lint(&ast, options) {
+        let (line, col) = lint.position();
+        let token = render.origin(line, col);
+        if let Some(token) = &token {
+            if lint.suppressed(render.pragmas(), token.path(), token.start().1 .0) {
+                continue;
+            }
+        }
+        let report = lint.report(token.as_ref(), |file| read_source(fs.vfs(), file));
+        if lint.level() == Level::Error {
+            errors += 1;
+            ctx.error(&report);
+        } else {
+            ctx.warn(&report);
+        }
+    }
+    errors
+}
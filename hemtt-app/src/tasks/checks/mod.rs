@@ -1,9 +1,17 @@
+mod checkfs;
 mod clean;
 mod clear;
+mod format;
+mod lint;
 mod modtime;
 mod names;
+mod patches;
 
+pub use checkfs::CheckFs;
 pub use clean::Clean;
 pub use clear::Clear;
+pub use format::Format;
+pub use lint::Lint;
 pub use modtime::ModTime;
 pub use names::*;
+pub use patches::Patches;
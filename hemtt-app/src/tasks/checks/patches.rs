@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use hemtt::project::addon_matches;
+use hemtt_arma_config::{
+    patches::{check, Patch, Problem},
+    simplify::Config,
+};
+
+use super::CheckFs;
+use crate::{context::AddonListContext, HEMTTError, Stage, Task};
+
+/// Checks that every addon declares `CfgPatches` and that the patches of the project require
+/// addons that exist, without cycles
+///
+/// A required addon exists if it is a patch of the project, of the game (`A3_*`) or matches
+/// one of the project's `dependencies`. Runs after `Mount` so configs can include the mounts.
+/// When a config can not be parsed its patches are unknown, so no required addon is reported as
+/// missing and no patch as unused.
+pub struct Patches;
+impl Task for Patches {
+    fn name(&self) -> String {
+        String::from("patches")
+    }
+
+    fn hooks(&self) -> &[Stage] {
+        &[Stage::Check]
+    }
+
+    fn check_single(&self, ctx: &mut AddonListContext) -> Result<(), HEMTTError> {
+        CheckFs::init(ctx)?;
+        let fs = CheckFs::get(ctx.global());
+        let mut patches: Vec<Patch> = Vec::new();
+        let mut owners = HashMap::new();
+        let mut undeclared = Vec::new();
+        let mut unparsed = Vec::new();
+        for (index, addon) in ctx.addons().iter().enumerate() {
+            let entry = fs.vfs().join(addon.addon().source())?.join("config.cpp")?;
+            if !entry.exists()? {
+                continue;
+            }
+            let config = match fs.parse(&entry, addon) {
+                Some((_, ast)) => match Config::from_ast(ast) {
+                    Ok(config) => config,
+                    Err(e) => {
+                        addon.warn(&format!("not checking `{}`: {}", entry.as_str(), e));
+                        unparsed.push(entry.as_str().to_string());
+                        continue;
+                    }
+                },
+                None => {
+                    unparsed.push(entry.as_str().to_string());
+                    continue;
+                }
+            };
+            match config.patches() {
+                Some(found) if !found.is_empty() => {
+                    for patch in found {
+                        owners.insert(patch.name().to_lowercase(), index);
+                        patches.push(patch);
+                    }
+                }
+                _ => undeclared.push(index),
+            }
+        }
+
+        let dependencies = ctx.global().project().dependencies.clone();
+        let mut problems = check(&patches, |name| {
+            addon_matches(name, "a3_*") || dependencies.iter().any(|d| addon_matches(name, d))
+        });
+        if !unparsed.is_empty() {
+            warn!(
+                "Missing and unused patches are not reported, {} can not be checked",
+                unparsed
+                    .iter()
+                    .map(|path| format!("`{}`", path))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            problems.retain(|problem| matches!(problem, Problem::Cycle(_)));
+        }
+
+        let addons = ctx.mut_addons();
+        for index in undeclared {
+            let addon = &mut addons[index];
+            let message = format!(
+                "`{}/config.cpp` does not declare a class in `CfgPatches`",
+                addon.addon().source()
+            );
+            addon.error(&message);
+            addon.set_failed(HEMTTError::User(message));
+        }
+        for problem in problems {
+            match problem {
+                Problem::Missing {
+                    patch,
+                    required,
+                    suggestion,
+                } => {
+                    let addon = &mut addons[owners[&patch.to_lowercase()]];
+                    let mut message = format!(
+                        "`{}` requires `{}`, which is not in the project or its `dependencies`",
+                        patch, required
+                    );
+                    if let Some(suggestion) = suggestion {
+                        message.push_str(&format!(", did you mean `{}`?", suggestion));
+                    }
+                    addon.error(&message);
+                    addon.set_failed(HEMTTError::User(message));
+                }
+                Problem::Cycle(cycle) => {
+                    let addon = &mut addons[owners[&cycle[0].to_lowercase()]];
+                    let message = format!(
+                        "`requiredAddons` form a cycle: {}",
+                        cycle
+                            .iter()
+                            .map(|patch| format!("`{}`", patch))
+                            .collect::<Vec<_>>()
+                            .join(" -> ")
+                    );
+                    addon.error(&message);
+                    addon.set_failed(HEMTTError::User(message));
+                }
+                Problem::Unused(patch) => {
+                    addons[owners[&patch.to_lowercase()]].warn(&format!(
+                        "`{}` is not required by any other patch of the project",
+                        patch
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
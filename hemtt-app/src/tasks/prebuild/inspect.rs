@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+use crate::{context::AddonListContext, HEMTTError, Stage, Task};
+
+use super::{
+    prefix::PrefixMap,
+    preprocess::{preprocess_file, read_source},
+};
+
+/// How `Inspect` shows the preprocessed file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InspectFormat {
+    /// The preprocessed text
+    Text,
+    /// The `.hemtt_map` JSON, mapping each output line back to the sources
+    Map,
+    /// A page where each token links to where it came from
+    Html,
+    /// Every macro expansion, nested by depth
+    Trace,
+}
+
+impl InspectFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(Self::Text),
+            "map" => Some(Self::Map),
+            "html" => Some(Self::Html),
+            "trace" => Some(Self::Trace),
+            _ => None,
+        }
+    }
+}
+
+/// Preprocesses a single file without building anything
+pub struct Inspect {
+    file: String,
+    format: InspectFormat,
+    output: Option<PathBuf>,
+}
+
+impl Inspect {
+    pub fn new(file: String, format: InspectFormat, output: Option<PathBuf>) -> Self {
+        Self {
+            file,
+            format,
+            output,
+        }
+    }
+}
+
+impl Task for Inspect {
+    fn name(&self) -> String {
+        String::from("inspect")
+    }
+
+    fn hooks(&self) -> &[Stage] {
+        &[Stage::PreBuild]
+    }
+
+    fn prebuild_single(&self, ctx: &mut AddonListContext) -> Result<(), HEMTTError> {
+        let vfs = ctx.global().vfs();
+        let path = vfs.join(
+            self.file
+                .replace('\\', "/")
+                .trim_start_matches("./")
+                .trim_start_matches('/'),
+        )?;
+        if !path.exists()? {
+            return Err(HEMTTError::User(format!("`{}` does not exist", self.file)));
+        }
+        let (render, trace) = preprocess_file(
+            &path,
+            ctx.global(),
+            vfs,
+            ctx.global().container.get::<PrefixMap>(),
+            "",
+            self.format == InspectFormat::Trace,
+        )?;
+        let content = match self.format {
+            InspectFormat::Text => render.export(),
+            InspectFormat::Map => render
+                .export_map_json()
+                .map_err(|e| HEMTTError::Generic(e.to_string()))?,
+            InspectFormat::Html => render.export_html(|file| read_source(vfs, file)),
+            InspectFormat::Trace => trace.unwrap_or_default().export(),
+        };
+        if let Some(output) = &self.output {
+            std::fs::write(output, content)?;
+            info!("Wrote `{}` to `{}`", self.file, output.display());
+        } else {
+            print!("{}", content);
+        }
+        Ok(())
+    }
+}
@@ -1,8 +1,19 @@
+mod inspect;
+pub use inspect::{Inspect, InspectFormat};
+
+mod mount;
+pub use mount::{Mount, MountMap};
+
 mod prefix;
-pub use prefix::Prefix;
+pub use prefix::{Prefix, PrefixMap};
 
 mod preprocess;
-pub use preprocess::Preprocess;
+pub use preprocess::{can_preprocess, preprocess_file, read_source, Preprocess};
+
+mod query;
+pub use query::{
+    is_built, read_bin, read_built, read_pbo, write_matches, ConfigQuery, QueryFormat,
+};
 
 mod populate;
-pub use populate::Populate;
+pub use populate::{can_populate, destination, populate, Populate};
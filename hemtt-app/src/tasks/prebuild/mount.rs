@@ -0,0 +1,221 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Seek, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use hemtt_pbo::ReadablePbo;
+use vfs::VfsPath;
+
+use super::preprocess::strip_prefix_ignore_case;
+use crate::{context::AddonListContext, HEMTTError, Stage, Task};
+
+/// The prefixes of the mounted dependencies and where their files are
+///
+/// A folder maps to its absolute path on disk, its files are read in place and are never added to
+/// the vfs of the context. The files of a PBO are at their location in the vfs once they are
+/// loaded by [`MountedPbos::load`].
+pub struct MountMap(HashMap<String, String>);
+impl MountMap {
+    pub fn inner(&self) -> &HashMap<String, String> {
+        &self.0
+    }
+}
+
+/// The mounted PBOs, only their headers are read until a file is included
+pub struct MountedPbos(Vec<MountedPbo>);
+impl MountedPbos {
+    /// Copies a file of a mounted PBO into `vfs` if `path` is in one, returns if it was
+    ///
+    /// # Errors
+    /// If the file is in a PBO but can not be read from it
+    pub fn load(&self, vfs: &VfsPath, path: &str) -> Result<bool, HEMTTError> {
+        for mounted in &self.0 {
+            let file = match strip_prefix_ignore_case(path, &mounted.path)
+                .and_then(|file| file.strip_prefix('/'))
+            {
+                Some(file) => file,
+                None => continue,
+            };
+            let mut pbo = mounted.pbo.lock().unwrap();
+            if pbo.header(file).is_none() {
+                continue;
+            }
+            debug!("Loading `{}` from `{}`", file, mounted.source.display());
+            let data = pbo.retrieve(file).ok_or_else(|| {
+                HEMTTError::Generic(format!(
+                    "Unable to read `{}` from `{}`",
+                    file,
+                    mounted.source.display()
+                ))
+            })?;
+            let dest = vfs.join(path)?;
+            dest.parent().unwrap().create_dir_all()?;
+            dest.create_file()?.write_all(data.get_ref())?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+struct MountedPbo {
+    /// Where the files of the PBO are in the vfs
+    path: String,
+    source: PathBuf,
+    pbo: Mutex<ReadablePbo<File>>,
+}
+
+/// Makes the dependencies in `Project::mount` available to includes, like the project's addons
+///
+/// A folder is mounted at its prefix. PBOs are mounted at their `prefix` extension, falling back to
+/// the mount's prefix, or the mount's prefix and the name of the PBO for a folder of PBOs.
+/// Mounts are not an overlay of the context's vfs. A folder is read in place from its absolute
+/// path, only the files of a PBO that are included are copied into the vfs. Mounts are resolved in
+/// the `Check` stage so the checks can include them.
+pub struct Mount {}
+impl Task for Mount {
+    fn name(&self) -> String {
+        String::from("mount")
+    }
+
+    fn hooks(&self) -> &[Stage] {
+        &[Stage::Check]
+    }
+
+    fn check_single(&self, ctx: &mut AddonListContext) -> Result<(), HEMTTError> {
+        let mut mounted = HashMap::new();
+        let mut pbos = Vec::new();
+        for (prefix, source) in &ctx.global().project().mount {
+            if is_pbo(source) {
+                pbos.push(mount_pbo(prefix, source, &mut mounted)?);
+            } else if source.is_dir() {
+                let mut found = Vec::new();
+                for entry in std::fs::read_dir(source)? {
+                    let path = entry?.path();
+                    if is_pbo(&path) {
+                        found.push(path);
+                    }
+                }
+                if found.is_empty() {
+                    debug!("Mounting `{}` at `{}`", source.display(), prefix);
+                    let source = std::env::current_dir()?.join(source);
+                    mounted.insert(prefix.to_owned(), source.to_string_lossy().to_string());
+                } else {
+                    for pbo in found {
+                        let fallback =
+                            format!("{}\\{}", prefix, pbo.file_stem().unwrap().to_string_lossy());
+                        pbos.push(mount_pbo(&fallback, &pbo, &mut mounted)?);
+                    }
+                }
+            } else {
+                return Err(HEMTTError::Generic(format!(
+                    "Unable to mount `{}`, `{}` is not a folder or a PBO",
+                    prefix,
+                    source.display()
+                )));
+            }
+        }
+        ctx.global().container.set(MountMap(mounted));
+        ctx.global().container.set(MountedPbos(pbos));
+        Ok(())
+    }
+}
+
+fn is_pbo(path: &Path) -> bool {
+    path.is_file() && path.extension().map(|e| e == "pbo").unwrap_or(false)
+}
+
+/// The location in the vfs for a prefix, mirroring the layout of a P drive
+fn vfs_prefix(prefix: &str) -> String {
+    prefix.replace('\\', "/").trim_matches('/').to_string()
+}
+
+/// The prefix of a PBO, its `prefix` extension or `fallback` when it has none
+fn pbo_prefix<I: Read + Seek>(pbo: &ReadablePbo<I>, fallback: &str) -> String {
+    pbo.extension("prefix")
+        .cloned()
+        .unwrap_or_else(|| fallback.to_string())
+}
+
+fn mount_pbo(
+    fallback: &str,
+    source: &Path,
+    mounted: &mut HashMap<String, String>,
+) -> Result<MountedPbo, HEMTTError> {
+    let pbo = ReadablePbo::from(File::open(source)?)?;
+    let prefix = pbo_prefix(&pbo, fallback);
+    debug!("Mounting `{}` at `{}`", source.display(), prefix);
+    let path = vfs_prefix(&prefix);
+    mounted.insert(prefix, path.clone());
+    Ok(MountedPbo {
+        path,
+        source: source.to_path_buf(),
+        pbo: Mutex::new(pbo),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::io::{Cursor, Read};
+
+    use hemtt_pbo::{ReadablePbo, WritablePbo};
+    use vfs::{MemoryFS, VfsPath};
+
+    use super::{mount_pbo, pbo_prefix, vfs_prefix, MountedPbos};
+
+    fn pbo(prefix: Option<&str>) -> Vec<u8> {
+        let mut pbo = WritablePbo::<Cursor<Vec<u8>>>::new();
+        if let Some(prefix) = prefix {
+            pbo.add_extension("prefix", prefix);
+        }
+        pbo.add_file("script_macros.hpp", Cursor::new(b"#define ABE 1".to_vec()))
+            .unwrap();
+        let mut buffer = Vec::new();
+        pbo.write(&mut buffer, true).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn prefix() {
+        assert_eq!(vfs_prefix("z\\abe\\addons\\main"), "z/abe/addons/main");
+        assert_eq!(vfs_prefix("\\z\\abe\\addons\\main\\"), "z/abe/addons/main");
+        assert_eq!(vfs_prefix("z/abe"), "z/abe");
+    }
+
+    #[test]
+    fn prefix_fallback() {
+        let with = ReadablePbo::from(Cursor::new(pbo(Some("z\\abe\\addons\\main")))).unwrap();
+        assert_eq!(pbo_prefix(&with, "deps\\main"), "z\\abe\\addons\\main");
+        let without = ReadablePbo::from(Cursor::new(pbo(None))).unwrap();
+        assert_eq!(pbo_prefix(&without, "deps\\main"), "deps\\main");
+    }
+
+    #[test]
+    fn load() {
+        let source =
+            std::env::temp_dir().join(format!("hemtt_mount_load_{}.pbo", std::process::id()));
+        std::fs::write(&source, pbo(None)).unwrap();
+        let mut mounted = HashMap::new();
+        let pbos = MountedPbos(vec![mount_pbo("deps\\main", &source, &mut mounted).unwrap()]);
+        assert_eq!(mounted["deps\\main"], "deps/main");
+
+        let vfs = VfsPath::new(MemoryFS::new());
+        assert!(!pbos.load(&vfs, "deps/main/missing.hpp").unwrap());
+        assert!(!pbos.load(&vfs, "other/script_macros.hpp").unwrap());
+        assert!(!vfs.join("deps").unwrap().exists().unwrap());
+        // Only the file that is loaded is copied
+        assert!(pbos.load(&vfs, "Deps/Main/Script_Macros.hpp").unwrap());
+        let mut data = String::new();
+        vfs.join("Deps/Main/Script_Macros.hpp")
+            .unwrap()
+            .open_file()
+            .unwrap()
+            .read_to_string(&mut data)
+            .unwrap();
+        assert_eq!(data, "#define ABE 1");
+        std::fs::remove_file(source).unwrap();
+    }
+}
@@ -5,6 +5,8 @@ use crate::{
     HEMTTError, Stage, Task,
 };
 
+use super::mount::MountMap;
+
 pub struct Prefix {
     seen: RwLock<HashMap<String, String>>,
 }
@@ -42,15 +44,33 @@ impl Task for Prefix {
     }
 
     fn prebuild_single(&self, ctx: &mut AddonListContext) -> Result<(), HEMTTError> {
-        ctx.global()
-            .container
-            .set(PrefixMap(self.seen.read().unwrap().clone()));
+        let mut prefixes = self.seen.read().unwrap().clone();
+        if let Some(mounts) = ctx.global().container.try_get::<MountMap>() {
+            for (prefix, path) in mounts.inner() {
+                if let Some(source) = prefixes.get(prefix) {
+                    warn!(
+                        "Mounted prefix `{}` is already in use by `{}`!",
+                        prefix, source
+                    );
+                } else {
+                    prefixes.insert(prefix.to_owned(), path.to_owned());
+                }
+            }
+        }
+        ctx.global().container.set(PrefixMap::new(prefixes));
         Ok(())
     }
 }
 
 pub struct PrefixMap(HashMap<String, String>);
 impl PrefixMap {
+    /// A map of prefixes to the folders they are in
+    ///
+    /// Addons and PBOs are in the vfs of the context, a mounted folder is an absolute path on disk.
+    pub fn new(prefixes: HashMap<String, String>) -> Self {
+        Self(prefixes)
+    }
+
     pub fn inner(&self) -> &HashMap<String, String> {
         &self.0
     }
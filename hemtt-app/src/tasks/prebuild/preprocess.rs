@@ -1,14 +1,21 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
+use hemtt::PreprocessError;
 use hemtt_arma_config::{
     resolver::{ResolvedFile, Resolver},
-    ArmaConfigError,
+    ArmaConfigError, Rendered, Trace,
 };
-use vfs::{VfsFileType, VfsPath};
+use vfs::{SeekAndRead, VfsFileType, VfsPath};
 
-use crate::{context::AddonContext, HEMTTError, Stage, Task};
+use crate::{
+    context::{AddonContext, Context},
+    HEMTTError, Stage, Task,
+};
 
-use super::prefix::PrefixMap;
+use super::{mount::MountedPbos, prefix::PrefixMap};
 
 pub fn can_preprocess(path: &str) -> bool {
     let path = PathBuf::from(path);
@@ -23,28 +30,88 @@ pub fn can_preprocess(path: &str) -> bool {
     ["cpp", "rvmat", "ext"].contains(&name)
 }
 
-pub fn preprocess(path: VfsPath, ctx: &mut AddonContext) -> Result<(), HEMTTError> {
-    ctx.debug(&format!("Preprocessing: {}", path.as_str()));
+/// Preprocesses a file from `vfs`, resolving includes from the project's include paths and
+/// the addons and mounts in `prefixes`
+///
+/// The files of mounted PBOs that are included are copied into `vfs`
+///
+/// Every macro expansion is recorded when `trace` is set
+pub fn preprocess_file(
+    path: &VfsPath,
+    ctx: &Context,
+    vfs: &VfsPath,
+    prefixes: &PrefixMap,
+    root: &str,
+    trace: bool,
+) -> Result<(Rendered, Option<Trace>), HEMTTError> {
     let mut buf = String::new();
     path.open_file()?.read_to_string(&mut buf)?;
-    let processed = hemtt_arma_config::preprocess(
-        hemtt_arma_config::tokenize(&buf, path.as_str()).unwrap(),
+    let report = |e: ArmaConfigError| {
+        HEMTTError::Preprocess(PreprocessError {
+            path: Some(path.as_str().to_string()),
+            message: e.report(|file| read_source(vfs, file)),
+            source: Box::new(HEMTTError::Generic(e.to_string())),
+        })
+    };
+    let tokens = hemtt_arma_config::tokenize(&buf, path.as_str()).map_err(|e| report(e.into()))?;
+    let resolver = VfsResolver::new(vfs.clone(), prefixes, &ctx.project().include)
+        .with_pbos(ctx.container.try_get::<MountedPbos>());
+    let mut processed =
+        hemtt_arma_config::preprocess_with(tokens, root, resolver, trace).map_err(&report)?;
+    let trace = processed.take_trace();
+    Ok((processed.render(), trace))
+}
+
+/// Reads a file by the path the preprocessor gave its tokens
+pub fn read_source(vfs: &VfsPath, file: &str) -> Option<String> {
+    if Path::new(file).is_absolute() {
+        // Include directories and mounted folders outside of the project
+        if let Ok(source) = std::fs::read_to_string(file) {
+            return Some(source);
+        }
+    }
+    let mut source = String::new();
+    vfs.join(file.trim_start_matches('/'))
+        .ok()?
+        .open_file()
+        .ok()?
+        .read_to_string(&mut source)
+        .ok()?;
+    Some(source)
+}
+
+pub fn preprocess(path: VfsPath, ctx: &mut AddonContext) -> Result<(), HEMTTError> {
+    ctx.debug(&format!("Preprocessing: {}", path.as_str()));
+    let (render, trace) = preprocess_file(
+        &path,
+        ctx.global(),
+        ctx.global().vfs(),
+        ctx.global().container.get::<PrefixMap>(),
         ctx.addon().source(),
-        VfsResolver::new(
-            ctx.global().vfs().clone(),
-            ctx.global().container.get::<PrefixMap>(),
-        ),
-    );
+        *crate::TRACE,
+    )?;
+    if let Some(trace) = trace {
+        path.parent()
+            .unwrap()
+            .join(path.filename() + ".hemtt_trace")?
+            .create_file()?
+            .write_all(trace.export().as_bytes())?;
+    }
     let mut f = path.create_file()?;
     let mut fmap = path
         .parent()
         .unwrap()
         .join(path.filename() + ".hemtt_map")?
         .create_file()?;
-    // TODO remove processed.unwrap()
-    let render = hemtt_arma_config::render(processed.unwrap());
     f.write_all(render.export().as_bytes())?;
     fmap.write_all(render.export_map_json().unwrap().as_bytes())?;
+    if !render.pragmas().is_empty() {
+        path.parent()
+            .unwrap()
+            .join(path.filename() + ".hemtt_pragma")?
+            .create_file()?
+            .write_all(render.export_pragmas_json().unwrap().as_bytes())?;
+    }
     Ok(())
 }
 
@@ -75,67 +142,234 @@ impl Task for Preprocess {
 }
 
 #[derive(Clone)]
-struct VfsResolver<'a>(Arc<VfsPath>, &'a PrefixMap);
+struct VfsResolver<'a> {
+    vfs: Arc<VfsPath>,
+    prefixes: &'a PrefixMap,
+    includes: &'a [PathBuf],
+    pbos: Option<&'a MountedPbos>,
+}
 impl<'a> VfsResolver<'a> {
-    pub fn new(path: VfsPath, prefixes: &'a PrefixMap) -> Self {
-        Self(Arc::new(path), prefixes)
+    pub fn new(path: VfsPath, prefixes: &'a PrefixMap, includes: &'a [PathBuf]) -> Self {
+        Self {
+            vfs: Arc::new(path),
+            prefixes,
+            includes,
+            pbos: None,
+        }
+    }
+
+    /// Loads the files that are not in the vfs from the mounted PBOs
+    pub fn with_pbos(mut self, pbos: Option<&'a MountedPbos>) -> Self {
+        self.pbos = pbos;
+        self
+    }
+
+    /// The paths `to` could be found at, in the order they are searched
+    ///
+    /// Relative paths are looked up next to the including file first. Paths starting with a
+    /// backslash are P-drive paths, they are matched against the addon prefixes and then
+    /// mapped onto each include directory, as are relative paths that were not found
+    fn candidates(&self, from: &str, to: &str) -> Vec<String> {
+        let to = to.replace('\\', "/");
+        let absolute = to.starts_with('/');
+        let to = to.trim_start_matches('/');
+        let mut candidates = Vec::new();
+        if !absolute {
+            let from = from.replace('\\', "/");
+            let dir = from
+                .trim_start_matches('/')
+                .rsplit_once('/')
+                .map(|(dir, _)| dir)
+                .unwrap_or("");
+            candidates.push(join(dir, to));
+        }
+        // The longest prefix is the most specific match
+        let mut prefixes = self.prefixes.inner().iter().collect::<Vec<_>>();
+        prefixes.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        for (prefix, source) in prefixes {
+            let prefix = prefix.replace('\\', "/");
+            if let Some(path) = strip_prefix_ignore_case(to, prefix.trim_matches('/'))
+                .and_then(|path| path.strip_prefix('/'))
+            {
+                candidates.push(join(source, path));
+            }
+        }
+        for include in self.includes {
+            candidates.push(join(&include.to_string_lossy(), to));
+        }
+        candidates
     }
 }
 impl<'a> Resolver for VfsResolver<'a> {
     fn resolve(&self, _root: &str, from: &str, to: &str) -> Result<ResolvedFile, ArmaConfigError> {
         trace!("Resolving from {} to {}", from, to);
-        let to_f = to.replace('\\', "/");
-        let to = to_f.trim_start_matches('/');
-        let from_f = from.replace('\\', "/");
-        let from = from_f.trim_start_matches('/');
-        let mut buf = String::new();
-        let new_path = self
-            .0
-            .join(from)
-            .unwrap()
-            .parent()
-            .unwrap()
-            .join(to)
-            .unwrap();
-        match new_path.open_file() {
-            Ok(mut f) => {
-                f.read_to_string(&mut buf).unwrap();
-                Ok(ResolvedFile::new(new_path.as_str(), buf))
-            }
-            Err(e) => {
-                // Check for prefix
-                if let Some((prefix, path)) = self.1.inner().iter().find(|(prefix, _)| {
-                    trace!("Checking prefix {}", prefix.replace('\\', "/"));
-                    to.starts_with(&prefix.replace('\\', "/"))
-                }) {
-                    let new_path = self
-                        .0
-                        .join(path.trim_start_matches('/'))
-                        .unwrap()
-                        .join(to.trim_start_matches(&format!("{}/", prefix.replace('\\', "/"))))
-                        .unwrap();
-                    new_path
-                        .open_file()
-                        .unwrap()
-                        .read_to_string(&mut buf)
-                        .unwrap();
-                    Ok(ResolvedFile::new(new_path.as_str(), buf))
-                } else {
-                    // TODO use the project's includes vec
-                    if PathBuf::from("include").exists() {
-                        let new_path = self.0.join(&format!("include/{}", to)).unwrap();
-                        new_path
+        let candidates = self.candidates(from, to);
+        for candidate in &candidates {
+            trace!("Checking {}", candidate);
+            if Path::new(candidate).is_absolute() {
+                // Include directories outside of the project, such as a P drive
+                if let Ok(data) = std::fs::read_to_string(candidate) {
+                    return Ok(ResolvedFile::new(candidate, data));
+                }
+            } else if let Ok(path) = self.vfs.join(candidate) {
+                if let Ok(f) = path.open_file() {
+                    return Ok(read_file(path.as_str(), f)?);
+                }
+                if let Some(pbos) = self.pbos {
+                    let loaded = pbos
+                        .load(&self.vfs, candidate)
+                        .map_err(|e| ArmaConfigError::GENERIC(e.to_string()))?;
+                    if loaded {
+                        let f = path
                             .open_file()
-                            .unwrap()
-                            .read_to_string(&mut buf)
-                            .unwrap();
-                        Ok(ResolvedFile::new(new_path.as_str(), buf))
-                    } else {
-                        panic!("{}", e);
-                        // TODO Err(e.into())
+                            .map_err(|e| ArmaConfigError::GENERIC(e.to_string()))?;
+                        return Ok(read_file(path.as_str(), f)?);
                     }
                 }
             }
         }
+        Err(ArmaConfigError::FileNotFound { tried: candidates })
+    }
+}
+
+/// Strips a prefix from a path without case, like the game matches paths
+pub(super) fn strip_prefix_ignore_case<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    let len = prefix.len();
+    if path.is_char_boundary(len) && path[..len].eq_ignore_ascii_case(prefix) {
+        Some(&path[len..])
+    } else {
+        None
+    }
+}
+
+/// Joins a directory from the project or the prefix map with a path inside of it
+fn join(dir: &str, path: &str) -> String {
+    let dir = dir.replace('\\', "/");
+    let dir = dir.trim_start_matches("./").trim_end_matches('/');
+    if dir.is_empty() || dir == "." {
+        path.to_string()
+    } else {
+        format!("{}/{}", dir, path)
+    }
+}
+
+fn read_file(path: &str, mut f: Box<dyn SeekAndRead>) -> std::io::Result<ResolvedFile> {
+    let mut buf = String::new();
+    f.read_to_string(&mut buf)?;
+    Ok(ResolvedFile::new(path, buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    use vfs::{MemoryFS, VfsPath};
+
+    use hemtt_arma_config::{resolver::Resolver, ArmaConfigError};
+
+    use super::VfsResolver;
+    use crate::tasks::PrefixMap;
+
+    fn prefix_map() -> PrefixMap {
+        let mut prefixes = HashMap::new();
+        prefixes.insert(String::from("x\\cba\\addons"), String::from("cba"));
+        prefixes.insert(
+            String::from("x\\cba\\addons\\main"),
+            String::from("addons/main"),
+        );
+        PrefixMap::new(prefixes)
+    }
+
+    fn candidates(from: &str, to: &str) -> Vec<String> {
+        let prefixes = prefix_map();
+        let includes = vec![PathBuf::from("include"), PathBuf::from("/p")];
+        VfsResolver::new(VfsPath::new(MemoryFS::new()), &prefixes, &includes).candidates(from, to)
+    }
+
+    #[test]
+    fn relative() {
+        assert_eq!(
+            candidates("/addons/main/config.cpp", "script_component.hpp"),
+            vec![
+                "addons/main/script_component.hpp",
+                "include/script_component.hpp",
+                "/p/script_component.hpp",
+            ]
+        );
+        assert_eq!(
+            candidates("config.cpp", "..\\common.hpp"),
+            vec!["../common.hpp", "include/../common.hpp", "/p/../common.hpp"]
+        );
+    }
+
+    #[test]
+    fn prefixes() {
+        assert_eq!(
+            candidates(
+                "/addons/other/config.cpp",
+                "\\x\\cba\\addons\\main\\script_macros.hpp"
+            ),
+            vec![
+                "addons/main/script_macros.hpp",
+                "cba/main/script_macros.hpp",
+                "include/x/cba/addons/main/script_macros.hpp",
+                "/p/x/cba/addons/main/script_macros.hpp",
+            ]
+        );
+    }
+
+    #[test]
+    fn prefixes_ignore_case() {
+        assert_eq!(
+            candidates(
+                "/addons/other/config.cpp",
+                "\\X\\CBA\\Addons\\Main\\macros.hpp"
+            )[0],
+            "addons/main/macros.hpp"
+        );
+        // Only whole folders are matched
+        assert_eq!(
+            candidates(
+                "/addons/other/config.cpp",
+                "\\x\\cba\\addons_extra\\macros.hpp"
+            ),
+            vec![
+                "include/x/cba/addons_extra/macros.hpp",
+                "/p/x/cba/addons_extra/macros.hpp",
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve() {
+        let vfs = VfsPath::new(MemoryFS::new());
+        vfs.join("cba/main").unwrap().create_dir_all().unwrap();
+        vfs.join("cba/main/macros.hpp")
+            .unwrap()
+            .create_file()
+            .unwrap()
+            .write_all(b"#define CBA 1")
+            .unwrap();
+        let prefixes = prefix_map();
+        let includes = vec![PathBuf::from("include")];
+        let resolver = VfsResolver::new(vfs, &prefixes, &includes);
+        // The longer prefix is tried first, the file is found under the shorter one
+        let file = resolver
+            .resolve(
+                "",
+                "addons/other/config.cpp",
+                "\\x\\cba\\addons\\main\\macros.hpp",
+            )
+            .unwrap();
+        assert_eq!(file.data(), "#define CBA 1");
+        match resolver.resolve("", "addons/other/config.cpp", "missing.hpp") {
+            Err(ArmaConfigError::FileNotFound { tried }) => assert_eq!(
+                tried,
+                vec!["addons/other/missing.hpp", "include/missing.hpp"]
+            ),
+            _ => panic!("expected the file to not be found"),
+        }
     }
 }
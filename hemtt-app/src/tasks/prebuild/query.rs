@@ -0,0 +1,209 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use hemtt::ConfigError;
+use hemtt_arma_config::{query::Query, simplify::Config, ArmaConfigError};
+use hemtt_pbo::ReadablePbo;
+use serde_json::Value;
+
+use crate::{context::AddonListContext, HEMTTError, Stage, Task};
+
+use super::{
+    prefix::PrefixMap,
+    preprocess::{preprocess_file, read_source},
+};
+
+/// How `ConfigQuery` shows what it found
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueryFormat {
+    /// One line for each match, with its value
+    Text,
+    /// An array of the matches with the file they are in
+    Json,
+}
+
+impl QueryFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(Self::Text),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Runs a query on configs and prints what it found
+///
+/// Source configs are preprocessed like they are for a build, `config.bin` files and the
+/// `config.bin` files in PBOs are read as they are. Without files, the `config.cpp` of every
+/// addon is queried.
+pub struct ConfigQuery {
+    query: Query,
+    /// The name each file is shown with and where it is read from, relative to the project
+    files: Vec<(String, String)>,
+    format: QueryFormat,
+    output: Option<PathBuf>,
+}
+
+impl ConfigQuery {
+    pub fn new(
+        query: Query,
+        files: Vec<(String, String)>,
+        format: QueryFormat,
+        output: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            query,
+            files,
+            format,
+            output,
+        }
+    }
+}
+
+impl Task for ConfigQuery {
+    fn name(&self) -> String {
+        String::from("query")
+    }
+
+    fn hooks(&self) -> &[Stage] {
+        &[Stage::PreBuild]
+    }
+
+    fn prebuild_single(&self, ctx: &mut AddonListContext) -> Result<(), HEMTTError> {
+        let files = if self.files.is_empty() {
+            ctx.addons()
+                .iter()
+                .map(|addon| format!("{}/config.cpp", addon.addon().source()))
+                .filter(|file| Path::new(file).exists())
+                .map(|file| (file.clone(), file))
+                .collect()
+        } else {
+            self.files.clone()
+        };
+        let mut configs = Vec::new();
+        for (name, file) in &files {
+            if is_built(file) {
+                configs.extend(read_built(name, Path::new(file))?);
+            } else {
+                configs.push((name.clone(), read_config(file, ctx)?));
+            }
+        }
+        write_matches(&self.query, &configs, self.format, self.output.as_deref())
+    }
+}
+
+/// If a file is a `config.bin` or a PBO, which are queried without a project
+pub fn is_built(file: &str) -> bool {
+    let lower = file.to_lowercase();
+    lower.ends_with(".pbo") || lower.ends_with(".bin")
+}
+
+/// Reads a `config.bin` or the `config.bin` files in a PBO, named after `name`
+pub fn read_built(name: &str, path: &Path) -> Result<Vec<(String, Config)>, HEMTTError> {
+    if !path.exists() {
+        return Err(HEMTTError::User(format!("`{}` does not exist", name)));
+    }
+    if name.to_lowercase().ends_with(".pbo") {
+        Ok(read_pbo(path)?
+            .into_iter()
+            .map(|(file, config)| (format!("{}/{}", name, file), config))
+            .collect())
+    } else {
+        Ok(vec![(name.to_string(), read_bin(&mut File::open(path)?)?)])
+    }
+}
+
+/// Prints the matches of a query in each config, or writes them to `output`
+pub fn write_matches(
+    query: &Query,
+    configs: &[(String, Config)],
+    format: QueryFormat,
+    output: Option<&Path>,
+) -> Result<(), HEMTTError> {
+    let mut content = String::new();
+    let mut json = Vec::new();
+    for (file, config) in configs {
+        let found = query.run(config);
+        match format {
+            QueryFormat::Text => {
+                if configs.len() > 1 && !found.is_empty() {
+                    content.push_str(&format!("# {}\n", file));
+                }
+                for found in found {
+                    content.push_str(&format!("{}\n", found));
+                }
+            }
+            QueryFormat::Json => {
+                for found in found {
+                    let mut value = found.to_json();
+                    value["file"] = Value::String(file.clone());
+                    json.push(value);
+                }
+            }
+        }
+    }
+    if format == QueryFormat::Json {
+        content =
+            serde_json::to_string_pretty(&json).map_err(|e| HEMTTError::Generic(e.to_string()))?;
+        content.push('\n');
+    }
+    if let Some(output) = output {
+        std::fs::write(output, content)?;
+        info!("Wrote the matches to `{}`", output.display());
+    } else {
+        print!("{}", content);
+    }
+    Ok(())
+}
+
+pub fn read_bin<I: std::io::Read + std::io::Seek>(input: &mut I) -> Result<Config, HEMTTError> {
+    Config::read_rapified(input).map_err(|e| HEMTTError::Generic(e.to_string()))
+}
+
+/// Reads every `config.bin` in a PBO, named by their path in it
+pub fn read_pbo(file: &Path) -> Result<Vec<(String, Config)>, HEMTTError> {
+    let mut pbo = ReadablePbo::open_mmap(file)?;
+    let mut configs = Vec::new();
+    for header in pbo.files() {
+        let name = header.filename().replace('\\', "/");
+        if name != "config.bin" && !name.to_lowercase().ends_with("/config.bin") {
+            continue;
+        }
+        if let Some(mut data) = pbo.retrieve(header.filename()) {
+            configs.push((name, read_bin(&mut data)?));
+        }
+    }
+    Ok(configs)
+}
+
+/// Preprocesses and parses a config of the project
+fn read_config(file: &str, ctx: &AddonListContext) -> Result<Config, HEMTTError> {
+    let vfs = ctx.global().vfs();
+    let path = vfs.join(
+        file.replace('\\', "/")
+            .trim_start_matches("./")
+            .trim_start_matches('/'),
+    )?;
+    if !path.exists()? {
+        return Err(HEMTTError::User(format!("`{}` does not exist", file)));
+    }
+    let (render, _) = preprocess_file(
+        &path,
+        ctx.global(),
+        vfs,
+        ctx.global().container.get::<PrefixMap>(),
+        "",
+        false,
+    )?;
+    let report = |e: ArmaConfigError| {
+        HEMTTError::Config(ConfigError {
+            path: Some(path.as_str().to_string()),
+            message: e.report(|file| read_source(vfs, file)),
+            source: Box::new(HEMTTError::Generic(e.to_string())),
+        })
+    };
+    let ast = hemtt_arma_config::parse(&render.export(), path.as_str(), Some(render.map().clone()))
+        .map_err(&report)?;
+    Config::from_ast(ast).map_err(&report)
+}
@@ -0,0 +1,90 @@
+use std::io::Cursor;
+use std::path::Path;
+
+use hemtt_arma_config::simplify::Config;
+use hemtt_pbo::WritablePbo;
+
+fn config_bin() -> Vec<u8> {
+    let ast = hemtt_arma_config::parse(
+        "class CfgPatches {\n    class test_sub {\n        units[] = {};\n    };\n};\n",
+        "config.cpp",
+        None,
+    )
+    .unwrap();
+    let mut data = Vec::new();
+    Config::from_ast(ast)
+        .unwrap()
+        .write_rapified(&mut data)
+        .unwrap();
+    data
+}
+
+fn execute(dir: &Path, args: &[&str]) {
+    // Reading the project moves to its root, every command starts from the subdirectory
+    std::env::set_current_dir(dir).unwrap();
+    let mut input = vec![String::from("hemtt-app")];
+    input.extend(args.iter().map(ToString::to_string));
+    hemtt_app::execute(&input, false).unwrap();
+}
+
+#[test]
+fn paths_from_subdirectory() {
+    let dir = std::env::current_dir()
+        .unwrap()
+        .join("tests/mod_alpha/subdirectory");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("config.bin"), config_bin()).unwrap();
+    let mut pbo = WritablePbo::<Cursor<Vec<u8>>>::new();
+    pbo.add_file("config.bin", Cursor::new(config_bin()))
+        .unwrap();
+    pbo.write(
+        &mut std::fs::File::create(dir.join("sub.pbo")).unwrap(),
+        true,
+    )
+    .unwrap();
+
+    execute(&dir, &["pbo", "unpack", "-d", "sub.pbo", "out"]);
+    assert!(dir.join("out/config.cpp").exists());
+    execute(
+        &dir,
+        &["config", "derapify", "config.bin", "-o", "config.cpp"],
+    );
+    assert!(dir.join("config.cpp").exists());
+    assert!(!dir.join("../config.cpp").exists());
+    execute(
+        &dir,
+        &[
+            "config",
+            "get",
+            "CfgPatches >> *",
+            "sub.pbo",
+            "-o",
+            "get.txt",
+        ],
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.join("get.txt")).unwrap(),
+        "CfgPatches >> test_sub\n"
+    );
+
+    // Built files are queried without a project
+    let outside = std::env::temp_dir().join(format!("hemtt_outside_{}", std::process::id()));
+    std::fs::create_dir_all(&outside).unwrap();
+    std::fs::write(outside.join("config.bin"), config_bin()).unwrap();
+    execute(
+        &outside,
+        &[
+            "config",
+            "get",
+            "CfgPatches >> *",
+            "config.bin",
+            "-o",
+            "get.txt",
+        ],
+    );
+    assert!(outside.join("get.txt").exists());
+
+    std::env::set_current_dir(dir.join("../..")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    std::fs::remove_dir_all(&outside).unwrap();
+}
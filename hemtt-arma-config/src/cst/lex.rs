@@ -0,0 +1,103 @@
+use super::{Kind, Node};
+
+/// Characters that are tokens on their own and end a word
+const SYMBOLS: &[char] = &['{', '}', '(', ')', '[', ']', ';', ',', ':', '='];
+
+/// Splits a config into tokens that cover every byte of it
+///
+/// Fails with the offset of an unterminated string or comment
+pub fn lex(source: &str) -> Result<Vec<Node>, usize> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    // Only whitespace has been seen on the current line, so a `#` starts a directive
+    let mut line_start = true;
+    while pos < source.len() {
+        let rest = &source[pos..];
+        let c = rest.chars().next().unwrap();
+        let (kind, len) = if rest.starts_with("\r\n") || c == '\n' {
+            (Kind::Newline, if c == '\n' { 1 } else { 2 })
+        } else if c.is_whitespace() {
+            (
+                Kind::Whitespace,
+                rest.find(|c: char| !c.is_whitespace() || c == '\n')
+                    .unwrap_or(rest.len()),
+            )
+        } else if rest.starts_with("//") {
+            (Kind::Comment, rest.find('\n').unwrap_or(rest.len()))
+        } else if rest.starts_with("/*") {
+            (Kind::Comment, rest.find("*/").ok_or(pos)? + 2)
+        } else if c == '#' && line_start {
+            (Kind::Directive, directive(rest))
+        } else if c == '"' || c == '\'' {
+            (Kind::Str, string(rest, c).ok_or(pos)?)
+        } else if rest.starts_with("[]") || rest.starts_with("+=") || rest.starts_with("-=") {
+            (Kind::Symbol, 2)
+        } else if SYMBOLS.contains(&c) {
+            (Kind::Symbol, 1)
+        } else {
+            (Kind::Word, word(rest))
+        };
+        line_start = match kind {
+            Kind::Newline | Kind::Directive => true,
+            Kind::Whitespace | Kind::Comment => line_start,
+            _ => false,
+        };
+        tokens.push(Node::token(kind, pos..pos + len));
+        pos += len;
+    }
+    Ok(tokens)
+}
+
+/// The length of a directive, lines ending in a backslash continue it
+fn directive(rest: &str) -> usize {
+    let mut end = 0;
+    loop {
+        match rest[end..].find('\n') {
+            Some(i) => {
+                let line = rest[end..end + i].trim_end_matches('\r');
+                if line.ends_with('\\') {
+                    end += i + 1;
+                } else {
+                    return end + line.len();
+                }
+            }
+            None => return rest.len(),
+        }
+    }
+}
+
+/// The length of a string including its quotes, a doubled quote is part of the string
+fn string(rest: &str, quote: char) -> Option<usize> {
+    let mut chars = rest.char_indices().skip(1).peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '\n' {
+            return None;
+        }
+        if c == quote {
+            if chars.peek().map(|(_, c)| *c) == Some(quote) {
+                chars.next();
+            } else {
+                return Some(i + 1);
+            }
+        }
+    }
+    None
+}
+
+fn word(rest: &str) -> usize {
+    let mut end = 0;
+    for (i, c) in rest.char_indices() {
+        let next = &rest[i..];
+        if c.is_whitespace()
+            || SYMBOLS.contains(&c)
+            || c == '"'
+            || next.starts_with("//")
+            || next.starts_with("/*")
+            || (i > 0 && (next.starts_with("+=") || next.starts_with("-=")))
+        {
+            break;
+        }
+        end = i + c.len_utf8();
+    }
+    end
+}
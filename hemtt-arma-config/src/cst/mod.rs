@@ -0,0 +1,392 @@
+//! A lossless concrete syntax tree of a config
+//!
+//! Unlike the `AST`, every byte of the source is kept, including whitespace, comments and
+//! preprocessor directives, and each node knows its span. Writing the tree out again gives
+//! back the source it was read from.
+
+use std::ops::Range;
+
+use crate::parser::{self, Node as AstNode, Statement, AST};
+use crate::preprocess::{Token, TokenPos};
+use crate::ArmaConfigError;
+
+mod lex;
+mod parse;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    // Nodes
+    /// The whole file
+    Config,
+    /// `class Name: Parent { ... };`, `class Name;`
+    Class,
+    /// `delete Name;`
+    Delete,
+    /// `enum { ... };`
+    Enum,
+    /// A constant of an `enum`, `Name` or `Name = 1`
+    EnumValue,
+    /// `name = value;`, `name[] = {...};`, `name[] += {...};`, `name[] -= {...};`
+    Property,
+    /// `{ ... }`
+    Array,
+    /// `__EXEC(...)`
+    Exec,
+    /// A macro used on its own, such as `MACRO(arg)` or `MACRO;`
+    Macro,
+    /// A name or value made of several tokens, such as `GVAR(name)` or `__EVAL(1 + 2)`
+    Text,
+
+    // Tokens
+    Whitespace,
+    Newline,
+    /// `// ...` or `/* ... */`
+    Comment,
+    /// A preprocessor line such as `#include "file.hpp"`, including its continuations
+    Directive,
+    /// `class`, `delete` or `enum`
+    Keyword,
+    Name,
+    Number,
+    /// A quoted string, including its quotes
+    Str,
+    /// An unquoted value
+    Word,
+    /// Punctuation such as `{`, `;` or `+=`
+    Symbol,
+}
+
+impl Kind {
+    /// Nodes that can be in the body of a config or class
+    pub const fn is_item(self) -> bool {
+        matches!(
+            self,
+            Self::Class | Self::Delete | Self::Enum | Self::Property | Self::Exec | Self::Macro
+        )
+    }
+
+    /// Tokens that do not change the meaning of a config
+    pub const fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            Self::Whitespace | Self::Newline | Self::Comment | Self::Directive
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    kind: Kind,
+    span: Range<usize>,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn token(kind: Kind, span: Range<usize>) -> Self {
+        Self {
+            kind,
+            span,
+            children: Vec::new(),
+        }
+    }
+
+    /// A node covering all of its children, which must not be empty
+    fn branch(kind: Kind, children: Vec<Node>) -> Self {
+        Self {
+            kind,
+            span: children.first().unwrap().span.start..children.last().unwrap().span.end,
+            children,
+        }
+    }
+
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    /// The byte range of the node in the source
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Every child, including trivia
+    pub fn children(&self) -> &[Node] {
+        &self.children
+    }
+
+    /// The children that are not trivia
+    pub fn significant(&self) -> impl Iterator<Item = &Node> {
+        self.children.iter().filter(|n| !n.kind.is_trivia())
+    }
+
+    pub fn is_token(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    pub fn text<'a>(&self, source: &'a str) -> &'a str {
+        &source[self.span.clone()]
+    }
+
+    fn write_tokens(&self, source: &str, out: &mut String) {
+        if self.is_token() {
+            out.push_str(self.text(source));
+        } else {
+            for child in &self.children {
+                child.write_tokens(source, out);
+            }
+        }
+    }
+
+    fn find(&self, kind: Kind) -> Option<&Node> {
+        if self.kind == kind {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(kind))
+    }
+}
+
+/// A config file as it was written
+#[derive(Clone, Debug)]
+pub struct Cst {
+    source: String,
+    path: String,
+    root: Node,
+}
+
+impl Cst {
+    /// Reads a config, preprocessor directives and macros are kept as they are
+    pub fn parse<S: Into<String>>(source: S, path: &str) -> Result<Self, ArmaConfigError> {
+        let source = source.into();
+        let root = parse::parse(&source, path)?;
+        Ok(Self {
+            source,
+            path: path.to_string(),
+            root,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    pub fn text(&self, node: &Node) -> &str {
+        node.text(&self.source)
+    }
+
+    /// Converts the tree into the `AST` the rest of the crate works with, like [`crate::parse`]
+    ///
+    /// The config must have been preprocessed, directives and macros can not be converted
+    pub fn to_ast(&self) -> Result<AST, ArmaConfigError> {
+        if let Some(node) = self
+            .root
+            .find(Kind::Directive)
+            .or_else(|| self.root.find(Kind::Macro))
+        {
+            return Err(self.error(node, "the config must be preprocessed first"));
+        }
+        let mut config = AstNode {
+            line: self.source.clone(),
+            start: (1, 1),
+            statement: Statement::Config(self.items(&self.root)?),
+        };
+        crate::parser::resolve(&mut config).map_err(ArmaConfigError::GENERIC)?;
+        Ok(AST { config })
+    }
+
+    fn items(&self, node: &Node) -> Result<Vec<AstNode>, ArmaConfigError> {
+        node.children
+            .iter()
+            .filter(|n| n.kind.is_item())
+            .map(|n| self.item(n))
+            .collect()
+    }
+
+    fn item(&self, node: &Node) -> Result<AstNode, ArmaConfigError> {
+        let parts: Vec<&Node> = node.significant().collect();
+        let statement = match node.kind {
+            Kind::Class => {
+                let ident = Box::new(self.ident(parts[1], false));
+                let extends = parts
+                    .iter()
+                    .position(|n| self.is_symbol(n, ":"))
+                    .map(|i| Box::new(self.ident(parts[i + 1], false)));
+                if parts.iter().any(|n| self.is_symbol(n, "{")) {
+                    Statement::Class {
+                        ident,
+                        extends,
+                        props: self.items(node)?,
+                    }
+                } else if extends.is_some() {
+                    return Err(self.error(node, "a class with a parent needs a body"));
+                } else {
+                    Statement::ClassDef(ident)
+                }
+            }
+            Kind::Delete => Statement::ClassDelete(Box::new(self.ident(parts[1], false))),
+            Kind::Enum => Statement::Enum(
+                parts
+                    .iter()
+                    .filter(|n| n.kind == Kind::EnumValue)
+                    .map(|n| {
+                        let parts: Vec<&Node> = n.significant().collect();
+                        let value = match parts.get(2).map(|v| self.value(v)) {
+                            Some(Statement::Integer(value)) => Some(value),
+                            Some(_) => return Err(self.error(n, "expected an integer")),
+                            None => None,
+                        };
+                        Ok((self.text(parts[0]).to_string(), value))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            Kind::Property => {
+                let array = self.is_symbol(parts[1], "[]");
+                let operator = self.text(parts[if array { 2 } else { 1 }]);
+                let value = parts[if array { 3 } else { 2 }];
+                Statement::Property {
+                    ident: Box::new(self.ident(parts[0], array)),
+                    value: Box::new(AstNode {
+                        line: self.text(value).to_string(),
+                        start: line_col(&self.source, value.span.start),
+                        statement: if value.kind == Kind::Array {
+                            Statement::Array(self.array(value))
+                        } else {
+                            self.value(value)
+                        },
+                    }),
+                    expand: operator == "+=",
+                    shrink: operator == "-=",
+                }
+            }
+            Kind::Exec => Statement::Exec(self.inner(node)),
+            _ => return Err(self.error(node, "unexpected item")),
+        };
+        Ok(AstNode {
+            line: self.text(node).to_string(),
+            start: line_col(&self.source, node.span.start),
+            statement,
+        })
+    }
+
+    fn ident(&self, node: &Node, array: bool) -> AstNode {
+        let name = self.text(node).to_string();
+        AstNode {
+            line: name.clone(),
+            start: line_col(&self.source, node.span.start),
+            statement: if array {
+                Statement::IdentArray(name)
+            } else {
+                Statement::Ident(name)
+            },
+        }
+    }
+
+    fn array(&self, node: &Node) -> Vec<AstNode> {
+        node.significant()
+            .filter(|n| n.kind != Kind::Symbol)
+            .map(|n| AstNode {
+                line: self.text(n).to_string(),
+                start: line_col(&self.source, n.span.start),
+                statement: if n.kind == Kind::Array {
+                    Statement::Array(self.array(n))
+                } else {
+                    self.value(n)
+                },
+            })
+            .collect()
+    }
+
+    fn value(&self, node: &Node) -> Statement {
+        let text = self.text(node);
+        match node.kind {
+            Kind::Str => {
+                let quote = &text[..1];
+                Statement::Str(
+                    text[1..text.len() - 1].replace(&format!("{}{}", quote, quote), quote),
+                )
+            }
+            Kind::Text if text.starts_with("__EVAL") => Statement::Eval(self.inner(node)),
+            Kind::Text => Statement::Word(text.to_string()),
+            _ if text == "true" || text == "false" => Statement::Bool(text == "true"),
+            _ => number(text).unwrap_or_else(|| Statement::Word(text.to_string())),
+        }
+    }
+
+    /// The text between the outer parentheses of `__EXEC(...)` or `__EVAL(...)`
+    fn inner(&self, node: &Node) -> String {
+        let text = self.text(node);
+        let start = text.find('(').map(|i| i + 1).unwrap_or(0);
+        let end = text.rfind(')').unwrap_or(text.len()).max(start);
+        text[start..end].to_string()
+    }
+
+    fn is_symbol(&self, node: &Node, symbol: &str) -> bool {
+        node.kind == Kind::Symbol && self.text(node) == symbol
+    }
+
+    fn error(&self, node: &Node, reason: &str) -> ArmaConfigError {
+        let (line, col) = line_col(&self.source, node.span.start);
+        ArmaConfigError::InvalidInput(format!("{}, at {}:{}:{}", reason, self.path, line, col))
+    }
+}
+
+impl std::fmt::Display for Cst {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut out = String::new();
+        self.root.write_tokens(&self.source, &mut out);
+        write!(f, "{}", out)
+    }
+}
+
+/// Reads a number the way the config grammar does: decimal and hex integers and floats,
+/// optionally negative and with an exponent
+///
+/// Integers are read like the parser reads them, one too large for an integer is a float.
+pub fn number(text: &str) -> Option<Statement> {
+    let unsigned = text.strip_prefix('-').unwrap_or(text);
+    if unsigned.starts_with("0x") || unsigned.starts_with("0X") {
+        return parser::number(text, 16).ok();
+    }
+    if unsigned.is_empty() || !unsigned.chars().any(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    if unsigned.chars().all(|c| c.is_ascii_digit()) {
+        return parser::number(text, 10).ok();
+    }
+    let mantissa = unsigned.split(['e', 'E']).next().unwrap_or_default();
+    if unsigned
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-'))
+        && mantissa.chars().any(|c| c.is_ascii_digit())
+        && !unsigned.starts_with(['e', 'E', '+'])
+    {
+        return text.parse().ok().map(Statement::Float);
+    }
+    None
+}
+
+/// The line and column of a byte in `source`, both starting at 1
+fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let col = before[before.rfind('\n').map(|i| i + 1).unwrap_or(0)..]
+        .chars()
+        .count()
+        + 1;
+    (line, col)
+}
+
+fn token_at(source: &str, path: &str, span: Range<usize>) -> TokenPos {
+    TokenPos::mapped(
+        Token::Word(source[span.clone()].to_string()),
+        path,
+        line_col(source, span.start),
+        line_col(source, span.end),
+    )
+}
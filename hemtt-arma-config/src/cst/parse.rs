@@ -0,0 +1,316 @@
+use std::ops::Range;
+
+use super::{lex::lex, line_col, number, token_at, Kind, Node};
+use crate::ArmaConfigError;
+
+/// Reads the tokens of a config into a tree, trivia is kept in the node it is found in
+pub fn parse(source: &str, path: &str) -> Result<Node, ArmaConfigError> {
+    let tokens = lex(source).map_err(|offset| {
+        error(
+            source,
+            path,
+            offset..offset + 1,
+            vec![String::from("end of string or comment")],
+        )
+    })?;
+    let mut parser = Parser {
+        source,
+        path,
+        tokens,
+        pos: 0,
+    };
+    let mut children = Vec::new();
+    parser.items(&mut children, false)?;
+    Ok(if children.is_empty() {
+        Node::token(Kind::Config, 0..0)
+    } else {
+        Node {
+            kind: Kind::Config,
+            span: 0..source.len(),
+            children,
+        }
+    })
+}
+
+/// A parsing error at `span`, `expected` describes what should have been there
+pub fn error(
+    source: &str,
+    path: &str,
+    span: Range<usize>,
+    expected: Vec<String>,
+) -> ArmaConfigError {
+    let span = span.start.min(source.len())..span.end.min(source.len());
+    ArmaConfigError::ParsingError {
+        positives: expected,
+        negatives: Vec::new(),
+        position: pest::error::LineColLocation::Pos(line_col(source, span.start)),
+        token: Some(Box::new(token_at(source, path, span))),
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    path: &'a str,
+    tokens: Vec<Node>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn text(&self, node: &Node) -> &'a str {
+        node.text(self.source)
+    }
+
+    /// Moves the trivia at the current position into `children`
+    fn trivia(&mut self, children: &mut Vec<Node>) {
+        while let Some(token) = self.tokens.get(self.pos) {
+            if !token.kind.is_trivia() {
+                break;
+            }
+            children.push(token.clone());
+            self.pos += 1;
+        }
+    }
+
+    /// The next token after any trivia, without moving past it
+    fn peek(&self) -> Option<&Node> {
+        self.tokens[self.pos..].iter().find(|t| !t.kind.is_trivia())
+    }
+
+    fn peek_symbol(&self, symbol: &str) -> bool {
+        self.peek()
+            .map(|t| t.kind == Kind::Symbol && self.text(t) == symbol)
+            .unwrap_or(false)
+    }
+
+    fn bump(&mut self, children: &mut Vec<Node>, kind: Kind) {
+        let mut token = self.tokens[self.pos].clone();
+        token.kind = kind;
+        children.push(token);
+        self.pos += 1;
+    }
+
+    fn expect(&mut self, children: &mut Vec<Node>, symbol: &str) -> Result<(), ArmaConfigError> {
+        self.trivia(children);
+        if self.peek_symbol(symbol) {
+            self.bump(children, Kind::Symbol);
+            Ok(())
+        } else {
+            Err(self.unexpected(&[symbol]))
+        }
+    }
+
+    fn unexpected(&self, expected: &[&str]) -> ArmaConfigError {
+        let span = self
+            .peek()
+            .map(Node::span)
+            .unwrap_or(self.source.len()..self.source.len());
+        let expected = expected.iter().map(|e| e.to_string()).collect();
+        error(self.source, self.path, span, expected)
+    }
+
+    /// Reads items until the end of the file, or the `}` of a class
+    fn items(&mut self, children: &mut Vec<Node>, class: bool) -> Result<(), ArmaConfigError> {
+        loop {
+            self.trivia(children);
+            let token = match self.peek() {
+                Some(token) => token.clone(),
+                None if class => return Err(self.unexpected(&["}"])),
+                None => return Ok(()),
+            };
+            match (token.kind, self.text(&token)) {
+                (Kind::Symbol, "}") if class => return Ok(()),
+                // An empty statement
+                (Kind::Symbol, ";") => self.bump(children, Kind::Symbol),
+                (Kind::Symbol, _) => return Err(self.unexpected(&["name"])),
+                (_, "class") => children.push(self.class()?),
+                (_, "delete") => children.push(self.delete()?),
+                (_, "enum") if self.next_is_brace() => children.push(self.enumdef()?),
+                (_, "__EXEC") => children.push(self.exec()?),
+                _ => children.push(self.property()?),
+            }
+        }
+    }
+
+    fn next_is_brace(&self) -> bool {
+        self.tokens[self.pos + 1..]
+            .iter()
+            .find(|t| !t.kind.is_trivia())
+            .map(|t| self.text(t) == "{")
+            .unwrap_or(false)
+    }
+
+    fn class(&mut self) -> Result<Node, ArmaConfigError> {
+        let mut children = Vec::new();
+        self.bump(&mut children, Kind::Keyword);
+        self.trivia(&mut children);
+        children.push(self.text_until(&["{", ":", ";"], Kind::Name)?);
+        if self.peek_symbol(":") {
+            self.expect(&mut children, ":")?;
+            self.trivia(&mut children);
+            children.push(self.text_until(&["{", ";"], Kind::Name)?);
+        }
+        if self.peek_symbol("{") {
+            self.expect(&mut children, "{")?;
+            self.items(&mut children, true)?;
+            self.expect(&mut children, "}")?;
+        }
+        self.expect(&mut children, ";")?;
+        Ok(Node::branch(Kind::Class, children))
+    }
+
+    fn delete(&mut self) -> Result<Node, ArmaConfigError> {
+        let mut children = Vec::new();
+        self.bump(&mut children, Kind::Keyword);
+        self.trivia(&mut children);
+        children.push(self.text_until(&[";"], Kind::Name)?);
+        self.expect(&mut children, ";")?;
+        Ok(Node::branch(Kind::Delete, children))
+    }
+
+    fn enumdef(&mut self) -> Result<Node, ArmaConfigError> {
+        let mut children = Vec::new();
+        self.bump(&mut children, Kind::Keyword);
+        self.expect(&mut children, "{")?;
+        loop {
+            self.trivia(&mut children);
+            if self.peek_symbol("}") {
+                break;
+            }
+            let mut value = Vec::new();
+            value.push(self.text_until(&["=", ",", "}"], Kind::Name)?);
+            if self.peek_symbol("=") {
+                self.expect(&mut value, "=")?;
+                self.trivia(&mut value);
+                value.push(self.text_until(&[",", "}"], Kind::Number)?);
+            }
+            children.push(Node::branch(Kind::EnumValue, value));
+            if self.peek_symbol(",") {
+                self.expect(&mut children, ",")?;
+            } else {
+                break;
+            }
+        }
+        self.expect(&mut children, "}")?;
+        self.expect(&mut children, ";")?;
+        Ok(Node::branch(Kind::Enum, children))
+    }
+
+    fn exec(&mut self) -> Result<Node, ArmaConfigError> {
+        let mut children = vec![self.text_until(&[";"], Kind::Text)?];
+        if self.peek_symbol(";") {
+            self.expect(&mut children, ";")?;
+        }
+        Ok(Node::branch(Kind::Exec, children))
+    }
+
+    /// A property, or a macro used on its own when there is no assignment
+    fn property(&mut self) -> Result<Node, ArmaConfigError> {
+        let mut children = vec![self.text_until(&["[]", "=", "+=", "-=", ";", "}"], Kind::Name)?];
+        let array = self.peek_symbol("[]");
+        if array {
+            self.expect(&mut children, "[]")?;
+        }
+        let operator = ["=", "+=", "-="]
+            .iter()
+            .find(|op| self.peek_symbol(op) && (array || **op == "="));
+        let operator = match operator {
+            Some(operator) => operator,
+            None if array => return Err(self.unexpected(&["=", "+=", "-="])),
+            None => {
+                // Macros such as `ADDON;` or `MACRO(arg)` without a semicolon
+                if self.peek_symbol(";") {
+                    self.expect(&mut children, ";")?;
+                }
+                return Ok(Node::branch(Kind::Macro, children));
+            }
+        };
+        self.expect(&mut children, operator)?;
+        self.trivia(&mut children);
+        if self.peek_symbol("{") {
+            children.push(self.array()?);
+        } else {
+            children.push(self.value(&[";", "}"])?);
+        }
+        self.expect(&mut children, ";")?;
+        Ok(Node::branch(Kind::Property, children))
+    }
+
+    fn array(&mut self) -> Result<Node, ArmaConfigError> {
+        let mut children = Vec::new();
+        self.expect(&mut children, "{")?;
+        loop {
+            self.trivia(&mut children);
+            if self.peek_symbol("}") {
+                break;
+            }
+            if self.peek_symbol("{") {
+                children.push(self.array()?);
+            } else {
+                children.push(self.value(&[",", "}"])?);
+            }
+            self.trivia(&mut children);
+            if self.peek_symbol(",") {
+                self.expect(&mut children, ",")?;
+            } else {
+                break;
+            }
+        }
+        self.expect(&mut children, "}")?;
+        Ok(Node::branch(Kind::Array, children))
+    }
+
+    fn value(&mut self, stops: &[&str]) -> Result<Node, ArmaConfigError> {
+        let mut value = self.text_until(stops, Kind::Word)?;
+        if value.is_token() {
+            let text = self.text(&value);
+            if text.starts_with('"') || text.starts_with('\'') {
+                value.kind = Kind::Str;
+            } else if number(text).is_some() {
+                value.kind = Kind::Number;
+            }
+        }
+        Ok(value)
+    }
+
+    /// Reads tokens until one of `stops` outside of parentheses, or the end of the line
+    ///
+    /// A single token is given `kind`, several are grouped into a `Text` node
+    fn text_until(&mut self, stops: &[&str], kind: Kind) -> Result<Node, ArmaConfigError> {
+        let start = self.pos;
+        let mut depth = 0;
+        while let Some(token) = self.tokens.get(self.pos) {
+            let text = self.text(token);
+            if depth == 0
+                && (token.kind == Kind::Newline
+                    || (token.kind == Kind::Symbol && stops.contains(&text)))
+            {
+                break;
+            }
+            match text {
+                "(" => depth += 1,
+                ")" if depth > 0 => depth -= 1,
+                "{" | "}" | ";" if depth == 0 => break,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+        // Trailing trivia belongs to the parent
+        while self.pos > start && self.tokens[self.pos - 1].kind.is_trivia() {
+            self.pos -= 1;
+        }
+        let mut tokens = self.tokens[start..self.pos].to_vec();
+        match tokens.len() {
+            0 => Err(self.unexpected(&[match kind {
+                Kind::Name => "name",
+                Kind::Number => "number",
+                _ => "value",
+            }])),
+            1 => {
+                let mut token = tokens.remove(0);
+                token.kind = kind;
+                Ok(token)
+            }
+            _ => Ok(Node::branch(Kind::Text, tokens)),
+        }
+    }
+}
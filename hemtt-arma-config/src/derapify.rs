@@ -0,0 +1,199 @@
+//! Writes a `Config` back to source, to read what was rapified
+//!
+//! The source follows the `LinterOptions` of a project the way `format` does. Floats are always
+//! written with a decimal point or an exponent, so they are read back as floats.
+
+use crate::simplify::{Array, ArrayElement, Class, Config, Entry};
+use crate::{BraceStyle, InheritanceStyle, LinterOptions};
+
+/// Writes a config as source
+pub fn derapify(config: &Config, options: &LinterOptions) -> String {
+    let mut writer = Writer {
+        options,
+        out: String::new(),
+    };
+    if !config.enums.is_empty() {
+        writer.enums(&config.enums);
+        if !config.root.entries.is_empty() {
+            writer.out.push('\n');
+        }
+    }
+    writer.entries(&config.root.entries, 0);
+    writer.out
+}
+
+struct Writer<'a> {
+    options: &'a LinterOptions,
+    out: String,
+}
+
+impl Writer<'_> {
+    fn indent(&self, depth: usize) -> String {
+        " ".repeat(depth * self.options.indent())
+    }
+
+    fn enums(&mut self, enums: &[(String, i32)]) {
+        self.out.push_str("enum {\n");
+        for (i, (name, value)) in enums.iter().enumerate() {
+            self.out.push_str(&self.indent(1));
+            self.out.push_str(&format!("{} = {}", name, value));
+            if i + 1 < enums.len() {
+                self.out.push(',');
+            }
+            self.out.push('\n');
+        }
+        self.out.push_str("};\n");
+    }
+
+    fn entries(&mut self, entries: &[(String, Entry)], depth: usize) {
+        for (name, entry) in entries {
+            match entry {
+                Entry::Invisible(entries) => {
+                    self.entries(entries, depth);
+                    continue;
+                }
+                Entry::Class(class) => self.class(name, class, depth),
+                _ => {
+                    self.out.push_str(&self.indent(depth));
+                    self.property(name, entry, depth);
+                }
+            }
+            self.out.push('\n');
+        }
+    }
+
+    fn class(&mut self, name: &str, class: &Class, depth: usize) {
+        self.out.push_str(&self.indent(depth));
+        if class.deletion {
+            self.out.push_str(&format!("delete {};", name));
+            return;
+        }
+        self.out.push_str("class ");
+        self.out.push_str(name);
+        if class.external {
+            self.out.push(';');
+            return;
+        }
+        if !class.parent.is_empty() {
+            self.out
+                .push_str(match self.options.class_inheritance_style() {
+                    InheritanceStyle::Space => " : ",
+                    InheritanceStyle::NoSpace => ": ",
+                });
+            self.out.push_str(&class.parent);
+        }
+        if class.entries.is_empty() {
+            self.out.push_str(" {};");
+            return;
+        }
+        match self.options.brace_style() {
+            BraceStyle::SameLine => self.out.push_str(" {\n"),
+            BraceStyle::NextLine => {
+                self.out.push('\n');
+                self.out.push_str(&self.indent(depth));
+                self.out.push_str("{\n");
+            }
+        }
+        self.entries(&class.entries, depth + 1);
+        self.out.push_str(&self.indent(depth));
+        self.out.push_str("};");
+    }
+
+    fn property(&mut self, name: &str, entry: &Entry, depth: usize) {
+        let value = match entry {
+            Entry::Str(value) => string(value),
+            Entry::Float(value) => float(*value),
+            Entry::Int32(value) => value.to_string(),
+            Entry::Int64(value) => value.to_string(),
+            Entry::Array(array) => {
+                let operator = if array.expand {
+                    "+="
+                } else if array.shrink {
+                    "-="
+                } else {
+                    "="
+                };
+                let start = format!("{}[] {} ", name, operator);
+                let column = self.indent(depth).len() + start.len();
+                self.out.push_str(&start);
+                let array = self.array(array, depth, column);
+                self.out.push_str(&array);
+                self.out.push(';');
+                return;
+            }
+            Entry::Class(_) | Entry::Invisible(_) => unreachable!(),
+        };
+        self.out.push_str(&format!("{} = {};", name, value));
+    }
+
+    /// An array on a single line if it fits in `array_width`, otherwise one element per line
+    fn array(&self, array: &Array, depth: usize, column: usize) -> String {
+        let inline = inline(array);
+        if array.elements.is_empty() || column + inline.chars().count() < self.options.array_width()
+        {
+            return inline;
+        }
+        let indent = self.indent(depth + 1);
+        let mut out = String::from("{");
+        for (i, element) in array.elements.iter().enumerate() {
+            out.push('\n');
+            out.push_str(&indent);
+            out.push_str(&match element {
+                ArrayElement::Array(array) => self.array(array, depth + 1, indent.len()),
+                element => self::element(element),
+            });
+            if i + 1 < array.elements.len() {
+                out.push(',');
+            }
+        }
+        out.push('\n');
+        out.push_str(&self.indent(depth));
+        out.push('}');
+        out
+    }
+}
+
+fn inline(array: &Array) -> String {
+    format!(
+        "{{{}}}",
+        array
+            .elements
+            .iter()
+            .map(element)
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+fn element(element: &ArrayElement) -> String {
+    match element {
+        ArrayElement::Str(value) => string(value),
+        ArrayElement::Float(value) => float(*value),
+        ArrayElement::Int32(value) => value.to_string(),
+        ArrayElement::Int64(value) => value.to_string(),
+        ArrayElement::Array(array) => inline(array),
+    }
+}
+
+fn string(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+/// The shortest text that reads back as the same `f32`, always with a `.` or an exponent
+fn float(value: f32) -> String {
+    format!("{:?}", value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::float;
+
+    #[test]
+    fn floats() {
+        assert_eq!(float(2.0), "2.0");
+        assert_eq!(float(1.88), "1.88");
+        assert_eq!(float(-0.5), "-0.5");
+        assert_eq!(float(1e20), "1e20");
+        assert_eq!(float(1.5e-7), "1.5e-7");
+    }
+}
@@ -0,0 +1,281 @@
+//! Compares two configs by what they define
+//!
+//! Classes and properties are matched by name without case, like the game does. External
+//! classes and `delete` statements define nothing and are not compared. The entries of a class
+//! that was added or removed are not listed on their own.
+
+use serde_json::{json, Value};
+
+use crate::query::{array_text, element_json, element_text, entry_json, string_text};
+use crate::simplify::{Array, ArrayElement, Class, Config, Entry};
+
+/// A difference between two configs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    path: Vec<String>,
+    kind: Kind,
+}
+
+/// What changed about an entry
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    /// A class that is only in the new config, with its parent
+    ClassAdded(String),
+    /// A class that is only in the old config
+    ClassRemoved,
+    /// A class that inherits from a different class, an empty parent is no parent
+    ParentChanged { old: String, new: String },
+    /// A property that is only in the new config
+    Added(Entry),
+    /// A property that is only in the old config
+    Removed(Entry),
+    /// A property with a different value, or that changed between a class and a property
+    Changed { old: Entry, new: Entry },
+    /// An array with the elements that are only in the old and only in the new array, in the
+    /// order of the arrays
+    ArrayChanged {
+        removed: Vec<ArrayElement>,
+        added: Vec<ArrayElement>,
+    },
+}
+
+impl Change {
+    /// The names of the classes to the entry, and its own name
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    pub const fn kind(&self) -> &Kind {
+        &self.kind
+    }
+
+    pub fn to_json(&self) -> Value {
+        let (change, mut value) = match &self.kind {
+            Kind::ClassAdded(parent) => ("class_added", json!({ "parent": parent_json(parent) })),
+            Kind::ClassRemoved => ("class_removed", json!({})),
+            Kind::ParentChanged { old, new } => (
+                "parent_changed",
+                json!({ "old": parent_json(old), "new": parent_json(new) }),
+            ),
+            Kind::Added(new) => ("added", json!({ "new": value_json(new) })),
+            Kind::Removed(old) => ("removed", json!({ "old": value_json(old) })),
+            Kind::Changed { old, new } => (
+                "changed",
+                json!({ "old": value_json(old), "new": value_json(new) }),
+            ),
+            Kind::ArrayChanged { removed, added } => (
+                "array_changed",
+                json!({
+                    "removed": removed.iter().map(element_json).collect::<Vec<_>>(),
+                    "added": added.iter().map(element_json).collect::<Vec<_>>(),
+                }),
+            ),
+        };
+        value["change"] = json!(change);
+        value["path"] = json!(self.path);
+        value
+    }
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let path = self.path.join(" >> ");
+        match &self.kind {
+            Kind::ClassAdded(parent) if parent.is_empty() => write!(f, "+ class {}", path),
+            Kind::ClassAdded(parent) => write!(f, "+ class {}: {}", path, parent),
+            Kind::ClassRemoved => write!(f, "- class {}", path),
+            Kind::ParentChanged { old, new } => write!(
+                f,
+                "~ class {}: {} -> {}",
+                path,
+                parent_text(old),
+                parent_text(new)
+            ),
+            Kind::Added(new) => write!(f, "+ {}{}", path, assignment(new)),
+            Kind::Removed(old) => write!(f, "- {}{}", path, assignment(old)),
+            Kind::Changed { old, new } => {
+                write!(f, "~ {}: {} -> {}", path, value_text(old), value_text(new))
+            }
+            Kind::ArrayChanged { removed, added } => write!(
+                f,
+                "~ {}[]: {}",
+                path,
+                removed
+                    .iter()
+                    .map(|element| format!("-{}", element_text(element)))
+                    .chain(
+                        added
+                            .iter()
+                            .map(|element| format!("+{}", element_text(element)))
+                    )
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+/// The changes from `old` to `new`
+pub fn diff(old: &Config, new: &Config) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_class(&old.root, &new.root, &mut Vec::new(), &mut changes);
+    changes
+}
+
+fn diff_class(old: &Class, new: &Class, path: &mut Vec<String>, changes: &mut Vec<Change>) {
+    let old_entries = defined(old);
+    let new_entries = defined(new);
+    let change = |path: &[String], name: &str, kind: Kind| {
+        let mut path = path.to_vec();
+        path.push(name.to_string());
+        Change { path, kind }
+    };
+    for (key, name, entry) in &old_entries {
+        match new_entries.iter().find(|(other, _, _)| other == key) {
+            None => changes.push(change(
+                path,
+                name,
+                match entry {
+                    Entry::Class(_) => Kind::ClassRemoved,
+                    entry => Kind::Removed((*entry).clone()),
+                },
+            )),
+            Some((_, name, new)) => match (entry, new) {
+                (Entry::Class(old), Entry::Class(new)) => {
+                    if !old.parent.eq_ignore_ascii_case(&new.parent) {
+                        changes.push(change(
+                            path,
+                            name,
+                            Kind::ParentChanged {
+                                old: old.parent.clone(),
+                                new: new.parent.clone(),
+                            },
+                        ));
+                    }
+                    path.push(name.to_string());
+                    diff_class(old, new, path, changes);
+                    path.pop();
+                }
+                (Entry::Array(old), Entry::Array(new)) if old == new => {}
+                (Entry::Array(old), Entry::Array(new))
+                    if old.expand == new.expand && old.shrink == new.shrink =>
+                {
+                    let (removed, added) = diff_array(old, new);
+                    changes.push(change(path, name, Kind::ArrayChanged { removed, added }));
+                }
+                (old, new) if old != new => changes.push(change(
+                    path,
+                    name,
+                    Kind::Changed {
+                        old: (*old).clone(),
+                        new: (*new).clone(),
+                    },
+                )),
+                _ => {}
+            },
+        }
+    }
+    for (key, name, entry) in &new_entries {
+        if old_entries.iter().any(|(other, _, _)| other == key) {
+            continue;
+        }
+        changes.push(change(
+            path,
+            name,
+            match entry {
+                Entry::Class(class) => Kind::ClassAdded(class.parent.clone()),
+                entry => Kind::Added((*entry).clone()),
+            },
+        ));
+    }
+}
+
+/// The entries of a class that define something, with their names in lowercase
+fn defined(class: &Class) -> Vec<(String, &str, &Entry)> {
+    class
+        .entries
+        .iter()
+        .filter(|(_, entry)| !matches!(entry, Entry::Class(c) if c.external || c.deletion))
+        .map(|(name, entry)| (name.to_lowercase(), name.as_str(), entry))
+        .collect()
+}
+
+/// The elements that are not in the longest common subsequence of the arrays
+fn diff_array(old: &Array, new: &Array) -> (Vec<ArrayElement>, Vec<ArrayElement>) {
+    let (old, new) = (&old.elements, &new.elements);
+    // `common[i][j]` is the length of the longest common subsequence of `old[i..]` and `new[j..]`
+    let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            removed.push(old[i].clone());
+            i += 1;
+        } else {
+            added.push(new[j].clone());
+            j += 1;
+        }
+    }
+    removed.extend_from_slice(&old[i..]);
+    added.extend_from_slice(&new[j..]);
+    (removed, added)
+}
+
+fn parent_text(parent: &str) -> &str {
+    if parent.is_empty() {
+        "(none)"
+    } else {
+        parent
+    }
+}
+
+fn parent_json(parent: &str) -> Value {
+    if parent.is_empty() {
+        Value::Null
+    } else {
+        json!(parent)
+    }
+}
+
+/// The value of an entry, classes are shown as `class` and arrays with their operator when they
+/// extend or shrink an array
+fn value_text(entry: &Entry) -> String {
+    match entry {
+        Entry::Str(value) => string_text(value),
+        Entry::Float(value) => value.to_string(),
+        Entry::Int32(value) => value.to_string(),
+        Entry::Int64(value) => value.to_string(),
+        Entry::Array(array) if array.expand => format!("+= {}", array_text(array)),
+        Entry::Array(array) if array.shrink => format!("-= {}", array_text(array)),
+        Entry::Array(array) => array_text(array),
+        Entry::Class(_) | Entry::Invisible(_) => String::from("class"),
+    }
+}
+
+fn assignment(entry: &Entry) -> String {
+    match entry {
+        Entry::Array(array) if array.expand => format!("[] += {}", array_text(array)),
+        Entry::Array(array) if array.shrink => format!("[] -= {}", array_text(array)),
+        Entry::Array(array) => format!("[] = {}", array_text(array)),
+        entry => format!(" = {}", value_text(entry)),
+    }
+}
+
+fn value_json(entry: &Entry) -> Value {
+    match entry {
+        Entry::Class(_) | Entry::Invisible(_) => json!("class"),
+        entry => entry_json(entry),
+    }
+}
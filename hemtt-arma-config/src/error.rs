@@ -1,3 +1,8 @@
+use std::collections::HashMap;
+
+use codespan_reporting::diagnostic::{Diagnostic, Label};
+use codespan_reporting::files::SimpleFiles;
+use codespan_reporting::term::{self, termcolor::NoColor};
 use thiserror::Error;
 
 use crate::preprocess::TokenPos;
@@ -17,21 +22,27 @@ impl<T, E: std::fmt::Debug + std::fmt::Display> PrintableError<T, E> for Result<
 
 #[derive(Error, Debug)]
 pub enum ArmaConfigError {
+    /// The source could not be parsed, `token` is where the parser stopped in the original files
     ParsingError {
         positives: Vec<String>,
         negatives: Vec<String>,
         position: pest::error::LineColLocation,
+        token: Option<Box<TokenPos>>,
     },
     InvalidInput(String),
     InvalidProperty(String),
     NotProcessed,
     NotRoot,
+    /// No file existed at any of the paths a resolver searched
+    FileNotFound {
+        tried: Vec<String>,
+    },
 
     // Syntax
     ArgCoundMismatch {
+        token: TokenPos,
         expected: usize,
         actual: usize,
-        args: Vec<String>,
     },
     DefineWithoutName {
         token: TokenPos,
@@ -39,6 +50,35 @@ pub enum ArmaConfigError {
     UndefineWithoutName {
         token: TokenPos,
     },
+    InvalidCondition {
+        token: TokenPos,
+        reason: String,
+    },
+    InvalidBuiltin {
+        token: TokenPos,
+        reason: String,
+    },
+    IncludeNotFound {
+        token: TokenPos,
+        reason: String,
+    },
+    UnterminatedIf {
+        token: TokenPos,
+    },
+    InvalidPragma {
+        token: TokenPos,
+        reason: String,
+    },
+    /// A macro used inside its own expansion, `cycle` holds the macros from the first use back to it
+    RecursiveMacro {
+        token: TokenPos,
+        cycle: Vec<String>,
+    },
+    /// An error from inside an include or macro, `stack` holds where each was used, innermost first
+    Traced {
+        error: Box<ArmaConfigError>,
+        stack: Vec<TokenPos>,
+    },
 
     // Wrappers
     IO(std::io::Error),
@@ -53,6 +93,181 @@ impl ArmaConfigError {
     pub fn error(&self) {
         error!("{}", self);
     }
+
+    /// The token that caused the error
+    pub fn token(&self) -> Option<&TokenPos> {
+        match *self {
+            Self::ArgCoundMismatch { ref token, .. }
+            | Self::DefineWithoutName { ref token }
+            | Self::UndefineWithoutName { ref token }
+            | Self::InvalidCondition { ref token, .. }
+            | Self::InvalidBuiltin { ref token, .. }
+            | Self::IncludeNotFound { ref token, .. }
+            | Self::UnterminatedIf { ref token }
+            | Self::InvalidPragma { ref token, .. }
+            | Self::RecursiveMacro { ref token, .. } => Some(token),
+            Self::ParsingError {
+                token: Some(ref token),
+                ..
+            } => Some(token),
+            Self::Traced { ref error, .. } => error.token(),
+            _ => None,
+        }
+    }
+
+    /// The description of the error, without its location
+    pub fn message(&self) -> String {
+        match *self {
+            Self::ArgCoundMismatch {
+                ref token,
+                expected,
+                actual,
+            } => format!(
+                "Expected {} arguments for {}, got {}",
+                expected,
+                token.to_string(),
+                actual
+            ),
+            Self::DefineWithoutName { .. } => String::from("Expected a name after define"),
+            Self::UndefineWithoutName { .. } => String::from("Expected a name after undef"),
+            Self::InvalidCondition { ref reason, .. } => {
+                format!("Invalid condition: {}", reason)
+            }
+            Self::InvalidBuiltin {
+                ref token,
+                ref reason,
+            } => format!("Invalid use of {}: {}", token.to_string(), reason),
+            Self::IncludeNotFound { ref reason, .. } => format!("Unable to include {}", reason),
+            Self::UnterminatedIf { .. } => String::from("Conditional is never closed with #endif"),
+            Self::InvalidPragma { ref reason, .. } => format!("Invalid pragma: {}", reason),
+            Self::RecursiveMacro { ref cycle, .. } => {
+                format!("Recursive macro expansion: {}", cycle.join(" -> "))
+            }
+            Self::ParsingError {
+                ref positives,
+                ref token,
+                ..
+            } => {
+                let mut message = if positives.is_empty() {
+                    String::from("Unexpected input")
+                } else {
+                    format!("Expected {}", expected(positives))
+                };
+                if let Some(token) = token {
+                    message.push_str(&format!(", found `{}`", token.to_string()));
+                }
+                message
+            }
+            Self::Traced { ref error, .. } => error.message(),
+            _ => self.to_string(),
+        }
+    }
+
+    /// Records that the error happened inside the include or macro used at `token`
+    pub(crate) fn within(self, token: &TokenPos) -> Self {
+        match self {
+            Self::Traced { error, mut stack } => {
+                stack.push(token.to_owned());
+                Self::Traced { error, stack }
+            }
+            error => Self::Traced {
+                error: Box::new(error),
+                stack: vec![token.to_owned()],
+            },
+        }
+    }
+
+    /// Renders the error as an annotated snippet of the source, `read` loads a file by the path of its tokens
+    pub fn report<F>(&self, read: F) -> String
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let stack: &[TokenPos] = if let Self::Traced { ref stack, .. } = *self {
+            stack
+        } else {
+            &[]
+        };
+        let mut files = SimpleFiles::new();
+        let mut ids = HashMap::new();
+        let mut labels = Vec::new();
+        for (i, token) in self.token().into_iter().chain(stack).enumerate() {
+            let id = if let Some(id) = ids.get(token.path()) {
+                *id
+            } else if let Some(source) = read(token.path()) {
+                let id = files.add(token.path().to_string(), source);
+                ids.insert(token.path().to_string(), id);
+                id
+            } else {
+                continue;
+            };
+            let range = range(files.get(id).unwrap().source(), token);
+            labels.push(if i == 0 {
+                Label::primary(id, range)
+            } else {
+                Label::secondary(id, range).with_message(format!("in {}", token.to_string()))
+            });
+        }
+        let diagnostic = Diagnostic::error()
+            .with_message(self.message())
+            .with_labels(labels);
+        let mut writer = NoColor::new(Vec::new());
+        if term::emit(&mut writer, &term::Config::default(), &files, &diagnostic).is_err() {
+            return self.to_string();
+        }
+        String::from_utf8_lossy(&writer.into_inner()).into_owned()
+    }
+}
+
+/// Lists the rules a parser expected in words
+fn expected(rules: &[String]) -> String {
+    let rules = rules
+        .iter()
+        .map(|rule| match rule.as_str() {
+            "EOI" => String::from("end of file"),
+            "name" | "value" | "number" => format!("a {}", rule),
+            "identarray" => String::from("`name[]`"),
+            "classdef" | "classextends" => String::from("`class`"),
+            "classdelete" => String::from("`delete`"),
+            "enumdef" => String::from("`enum`"),
+            "exec" => String::from("`__EXEC`"),
+            "eval" => String::from("`__EVAL`"),
+            "propexpand" => String::from("`+=`"),
+            "propshrink" => String::from("`-=`"),
+            rule if rule.contains(' ') => rule.to_string(),
+            rule => format!("`{}`", rule),
+        })
+        .fold(Vec::new(), |mut rules, rule| {
+            if !rules.contains(&rule) {
+                rules.push(rule);
+            }
+            rules
+        });
+    match rules.split_last() {
+        Some((last, [])) => last.to_owned(),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
+
+/// The byte range of a token in its source, found by its lines and columns as
+/// tokens from a line map do not know their offsets
+pub(crate) fn range(source: &str, token: &TokenPos) -> std::ops::Range<usize> {
+    let offset = |(line, col): (usize, usize)| {
+        let start = source
+            .split_inclusive('\n')
+            .take(line.saturating_sub(1))
+            .map(str::len)
+            .sum::<usize>();
+        let line = &source[start..];
+        start
+            + line
+                .char_indices()
+                .nth(col.saturating_sub(1))
+                .map(|(i, _)| i)
+                .unwrap_or_else(|| line.len())
+    };
+    let start = offset(token.start().1);
+    start..offset(token.end().1).max(start)
 }
 
 impl std::fmt::Display for ArmaConfigError {
@@ -63,34 +278,91 @@ impl std::fmt::Display for ArmaConfigError {
             Self::PATH(ref err) => write!(f, "IO error {}: {}", err.path.display(), err.source),
             Self::NotProcessed => write!(f, "Attempt to perform action on non-processed AST"),
             Self::NotRoot => write!(f, "The root of the AST is required"),
+            Self::FileNotFound { ref tried } => write!(
+                f,
+                "File not found, tried {}",
+                tried
+                    .iter()
+                    .map(|path| format!("`{}`", path))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
             Self::InvalidInput(ref err) => write!(f, "Invalid Input: {}", err),
             Self::InvalidProperty(ref err) => write!(f, "Invalid Property: {}", err),
             Self::ParsingError {
-                ref positives,
-                ref position,
+                token: Some(ref token),
                 ..
-            } => write!(f, "Expected {:?} at {:?}", positives, position),
-
-            //Syntax
-            Self::ArgCoundMismatch {
-                expected,
-                actual,
-                ref args,
             } => write!(
                 f,
-                "Expected {} arguments, got {}: {:?}",
-                expected, actual, args
+                "{}, at {}:{}:{}",
+                self.message(),
+                token.path(),
+                token.start().1 .0,
+                token.start().1 .1
             ),
-            Self::DefineWithoutName { ref token } => {
-                write!(f, "Expected a name after define, at {:?}", token)
+            Self::ParsingError { ref position, .. } => {
+                let (line, col) = match *position {
+                    pest::error::LineColLocation::Pos(s)
+                    | pest::error::LineColLocation::Span(s, _) => s,
+                };
+                write!(f, "{}, at {}:{}", self.message(), line, col)
             }
-            Self::UndefineWithoutName { ref token } => {
-                write!(f, "Expected a name after undef, at {:?}", token)
+
+            //Syntax
+            Self::ArgCoundMismatch { ref token, .. }
+            | Self::DefineWithoutName { ref token }
+            | Self::UndefineWithoutName { ref token }
+            | Self::InvalidCondition { ref token, .. }
+            | Self::InvalidBuiltin { ref token, .. }
+            | Self::IncludeNotFound { ref token, .. }
+            | Self::UnterminatedIf { ref token }
+            | Self::InvalidPragma { ref token, .. }
+            | Self::RecursiveMacro { ref token, .. } => write!(
+                f,
+                "{}, at {}:{}:{}",
+                self.message(),
+                token.path(),
+                token.start().1 .0,
+                token.start().1 .1
+            ),
+            Self::Traced {
+                ref error,
+                ref stack,
+            } => {
+                write!(f, "{}", error)?;
+                for token in stack {
+                    write!(
+                        f,
+                        "\n    from {} at {}:{}:{}",
+                        token.to_string(),
+                        token.path(),
+                        token.start().1 .0,
+                        token.start().1 .1
+                    )?;
+                }
+                Ok(())
             }
         }
     }
 }
 
+impl From<pest::error::Error<crate::preprocess::Rule>> for ArmaConfigError {
+    fn from(err: pest::error::Error<crate::preprocess::Rule>) -> Self {
+        match err.variant {
+            pest::error::ErrorVariant::ParsingError {
+                positives,
+                negatives,
+            } => Self::ParsingError {
+                positives: positives.iter().map(|r| format!("{:?}", r)).collect(),
+                negatives: negatives.iter().map(|r| format!("{:?}", r)).collect(),
+                position: err.line_col,
+                token: None,
+            },
+            pest::error::ErrorVariant::CustomError { message } => Self::GENERIC(message),
+        }
+    }
+}
+
 impl From<std::io::Error> for ArmaConfigError {
     fn from(err: std::io::Error) -> Self {
         Self::IO(err)
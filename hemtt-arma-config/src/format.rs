@@ -0,0 +1,321 @@
+//! Formats configs according to the `LinterOptions` of a project
+//!
+//! Formatting works on the concrete syntax tree, so comments, preprocessor directives and
+//! macros are kept. An item with a comment or directive between its own tokens is written as
+//! it was, only its indentation changes.
+
+use crate::cst::{self, Cst, Kind, Node};
+use crate::{BraceStyle, InheritanceStyle, LinterOptions};
+
+/// Formats a config, line endings are kept as they were in the source
+pub fn format(cst: &Cst, options: &LinterOptions) -> String {
+    let mut formatter = Formatter {
+        source: cst.source(),
+        options,
+        out: String::new(),
+    };
+    formatter.items(cst.root().children(), 0, false);
+    let newline = if cst.source().contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut out = String::new();
+    for line in formatter.out.lines() {
+        out.push_str(line.trim_end());
+        out.push_str(newline);
+    }
+    out
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    options: &'a LinterOptions,
+    out: String,
+}
+
+impl<'a> Formatter<'a> {
+    fn text(&self, node: &Node) -> &'a str {
+        node.text(self.source)
+    }
+
+    fn is_symbol(&self, node: &Node, symbol: &str) -> bool {
+        node.kind() == Kind::Symbol && self.text(node) == symbol
+    }
+
+    fn indent(&self, depth: usize) -> String {
+        " ".repeat(depth * self.options.indent())
+    }
+
+    /// Starts a new line at `depth`
+    fn line(&mut self, depth: usize) {
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        self.out.push_str(&self.indent(depth));
+    }
+
+    /// The column the next character is written at
+    fn column(&self) -> usize {
+        self.out[self.out.rfind('\n').map(|i| i + 1).unwrap_or(0)..]
+            .chars()
+            .count()
+    }
+
+    /// Writes the items and comments of a config or class body
+    ///
+    /// A single blank line is kept where there was at least one, a comment on the same line as
+    /// the previous item stays there. `open` is whether the current line already has something
+    /// a comment can follow.
+    fn items(&mut self, children: &[Node], depth: usize, mut open: bool) {
+        let mut newlines = 0;
+        let mut first = true;
+        for child in children {
+            match child.kind() {
+                Kind::Whitespace => continue,
+                Kind::Newline => {
+                    newlines += 1;
+                    continue;
+                }
+                // An empty statement
+                Kind::Symbol => continue,
+                Kind::Comment if open && newlines == 0 => {
+                    self.out.push(' ');
+                    self.out.push_str(self.text(child));
+                }
+                kind => {
+                    if newlines > 1 && !first {
+                        self.out.push('\n');
+                    }
+                    if kind == Kind::Directive {
+                        self.line(0);
+                        self.out.push_str(self.text(child));
+                    } else {
+                        self.line(depth);
+                        self.item(child, depth);
+                    }
+                    open = kind != Kind::Directive;
+                }
+            }
+            newlines = 0;
+            first = false;
+        }
+    }
+
+    fn item(&mut self, node: &Node, depth: usize) {
+        match node.kind() {
+            Kind::Class => self.class(node, depth),
+            Kind::Property if !commented(node.children()) => self.property(node, depth),
+            Kind::Enum if !commented(node.children()) => self.enumdef(node, depth),
+            Kind::Delete if !commented(node.children()) => {
+                let name = node.significant().nth(1).unwrap();
+                self.out.push_str("delete ");
+                self.out.push_str(self.text(name));
+                self.out.push(';');
+            }
+            _ => self.out.push_str(self.text(node)),
+        }
+    }
+
+    fn class(&mut self, node: &Node, depth: usize) {
+        let children = node.children();
+        let open = children.iter().position(|n| self.is_symbol(n, "{"));
+        let close = children.iter().rposition(|n| self.is_symbol(n, "}"));
+        let (header, body, footer) = match (open, close) {
+            (Some(open), Some(close)) => (
+                &children[..open],
+                Some(&children[open + 1..close]),
+                &children[close + 1..],
+            ),
+            _ => (children, None, &children[children.len()..]),
+        };
+        if commented(header) || commented(footer) {
+            self.out.push_str(self.text(node));
+            return;
+        }
+        let parts: Vec<&Node> = header.iter().filter(|n| !n.kind().is_trivia()).collect();
+        self.out.push_str("class ");
+        self.out.push_str(self.text(parts[1]));
+        if let Some(i) = parts.iter().position(|n| self.is_symbol(n, ":")) {
+            self.out
+                .push_str(match self.options.class_inheritance_style() {
+                    InheritanceStyle::Space => " : ",
+                    InheritanceStyle::NoSpace => ": ",
+                });
+            self.out.push_str(self.text(parts[i + 1]));
+        }
+        let body = match body {
+            Some(body) => body,
+            None => {
+                self.out.push(';');
+                return;
+            }
+        };
+        if !body
+            .iter()
+            .any(|n| n.kind().is_item() || commented(std::slice::from_ref(n)))
+        {
+            self.out.push_str(" {};");
+            return;
+        }
+        match self.options.brace_style() {
+            BraceStyle::SameLine => self.out.push_str(" {"),
+            BraceStyle::NextLine => {
+                self.line(depth);
+                self.out.push('{');
+            }
+        }
+        self.items(body, depth + 1, true);
+        self.line(depth);
+        self.out.push_str("};");
+    }
+
+    fn property(&mut self, node: &Node, depth: usize) {
+        let parts: Vec<&Node> = node.significant().collect();
+        let array = self.is_symbol(parts[1], "[]");
+        let (operator, value) = if array {
+            (parts[2], parts[3])
+        } else {
+            (parts[1], parts[2])
+        };
+        self.out.push_str(self.text(parts[0]));
+        if array {
+            self.out.push_str("[]");
+        }
+        self.out.push(' ');
+        self.out.push_str(self.text(operator));
+        self.out.push(' ');
+        let value = self.value(value, depth, self.column());
+        self.out.push_str(&value);
+        self.out.push(';');
+    }
+
+    fn enumdef(&mut self, node: &Node, depth: usize) {
+        let values: Vec<&Node> = node
+            .significant()
+            .filter(|n| n.kind() == Kind::EnumValue)
+            .collect();
+        if values.is_empty() {
+            self.out.push_str("enum {};");
+            return;
+        }
+        self.out.push_str("enum {");
+        for (i, value) in values.iter().enumerate() {
+            self.line(depth + 1);
+            let parts: Vec<&Node> = value.significant().collect();
+            self.out.push_str(self.text(parts[0]));
+            if let Some(number) = parts.get(2) {
+                self.out.push_str(" = ");
+                self.out.push_str(&normalize_number(self.text(number)));
+            }
+            if i + 1 < values.len() {
+                self.out.push(',');
+            }
+        }
+        self.line(depth);
+        self.out.push_str("};");
+    }
+
+    /// A value or array that starts at `column`
+    fn value(&self, node: &Node, depth: usize, column: usize) -> String {
+        match node.kind() {
+            Kind::Array => self.array(node, depth, column),
+            Kind::Number => normalize_number(self.text(node)),
+            _ => self.text(node).to_string(),
+        }
+    }
+
+    /// An array on a single line if it fits in `array_width`, otherwise one element per line
+    fn array(&self, node: &Node, depth: usize, column: usize) -> String {
+        if commented(node.children()) {
+            return self.text(node).to_string();
+        }
+        let inline = self.inline(node);
+        let elements: Vec<&Node> = node
+            .significant()
+            .filter(|n| n.kind() != Kind::Symbol)
+            .collect();
+        // The `;` or `,` after the array counts towards the width
+        if elements.is_empty() || column + inline.chars().count() < self.options.array_width() {
+            return inline;
+        }
+        let indent = self.indent(depth + 1);
+        let mut out = String::from("{");
+        for (i, element) in elements.iter().enumerate() {
+            out.push('\n');
+            out.push_str(&indent);
+            out.push_str(&self.value(element, depth + 1, indent.len()));
+            if i + 1 < elements.len() {
+                out.push(',');
+            }
+        }
+        out.push('\n');
+        out.push_str(&self.indent(depth));
+        out.push('}');
+        out
+    }
+
+    fn inline(&self, node: &Node) -> String {
+        if node.kind() != Kind::Array {
+            return self.value(node, 0, 0);
+        }
+        let elements: Vec<String> = node
+            .significant()
+            .filter(|n| n.kind() != Kind::Symbol)
+            .map(|n| self.inline(n))
+            .collect();
+        format!("{{{}}}", elements.join(", "))
+    }
+}
+
+/// Whether there is a comment or directive in any of `nodes`
+fn commented(nodes: &[Node]) -> bool {
+    nodes
+        .iter()
+        .any(|n| matches!(n.kind(), Kind::Comment | Kind::Directive) || commented(n.children()))
+}
+
+/// Writes a number the same way every time: hex with a lowercase `0x` and uppercase digits,
+/// a digit on both sides of a decimal point and a lowercase exponent
+fn normalize_number(text: &str) -> String {
+    if cst::number(text).is_none() {
+        return text.to_string();
+    }
+    let (sign, unsigned) = match text.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", text),
+    };
+    if let Some(hex) = unsigned
+        .strip_prefix("0x")
+        .or_else(|| unsigned.strip_prefix("0X"))
+    {
+        return format!("{}0x{}", sign, hex.to_uppercase());
+    }
+    let unsigned = unsigned.replace('E', "e");
+    let (mantissa, exponent) = unsigned.split_at(unsigned.find('e').unwrap_or(unsigned.len()));
+    let mut mantissa = mantissa.to_string();
+    if mantissa.starts_with('.') {
+        mantissa.insert(0, '0');
+    }
+    if mantissa.ends_with('.') {
+        mantissa.push('0');
+    }
+    format!("{}{}{}", sign, mantissa, exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_number;
+
+    #[test]
+    fn numbers() {
+        assert_eq!(normalize_number("0xff"), "0xFF");
+        assert_eq!(normalize_number("-0XaB"), "-0xAB");
+        assert_eq!(normalize_number(".5"), "0.5");
+        assert_eq!(normalize_number("-.5"), "-0.5");
+        assert_eq!(normalize_number("1."), "1.0");
+        assert_eq!(normalize_number("1.5E-3"), "1.5e-3");
+        assert_eq!(normalize_number("12"), "12");
+        assert_eq!(normalize_number("1.2.3"), "1.2.3");
+    }
+}
@@ -4,15 +4,26 @@ extern crate pest_derive;
 #[macro_use]
 extern crate log;
 
+pub mod cst;
+pub mod derapify;
+pub mod diff;
 mod error;
+pub mod format;
 mod linter;
 mod parser;
+pub mod patches;
 mod preprocess;
+pub mod query;
 pub mod rapify;
+pub mod resolve;
 pub mod resolver;
 pub mod simplify;
+pub mod verify;
 
 pub use error::ArmaConfigError;
-pub use linter::{InheritanceStyle, LinterOptions};
-pub use parser::parse;
-pub use preprocess::{preprocess, render, tokenize};
+pub use linter::{lint, BraceStyle, InheritanceStyle, Level, Lint, LinterOptions, Rule, RULES};
+pub use parser::{parse, Node, Statement, AST};
+pub use preprocess::{
+    preprocess, preprocess_at, preprocess_with, render, tokenize, Expansion, Flag, LineMap, Pragma,
+    Pragmas, Preprocessed, Rendered, Scope, Trace,
+};
@@ -1,2 +1,167 @@
+//! Lints for configs, run over the parsed `AST`
+
+use codespan_reporting::diagnostic::{Diagnostic, Label};
+use codespan_reporting::files::SimpleFiles;
+use codespan_reporting::term::{self, termcolor::NoColor};
+use serde::{Deserialize, Serialize};
+
+use crate::{preprocess::TokenPos, Node, Pragmas, Statement, AST};
+
 mod options;
-pub use options::{InheritanceStyle, LinterOptions};
+pub use options::{BraceStyle, InheritanceStyle, LinterOptions};
+
+mod rules;
+pub use rules::RULES;
+
+/// How the lints of a rule are reported
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Level {
+    /// The rule is not run
+    Allow,
+    Warning,
+    /// The addon fails to build
+    Error,
+}
+
+/// A check that is run on the items of the config and of every class
+pub trait Rule: Sync {
+    /// The stable code of the rule, used to configure it and in `#pragma hemtt suppress`
+    fn code(&self) -> &'static str;
+    /// A short name of the rule, it can be used instead of the code in the project config and
+    /// in pragmas
+    fn name(&self) -> &'static str;
+    fn default_level(&self) -> Level;
+    /// Checks the items of a config or class body, `path` is the names of the classes they are in
+    ///
+    /// Each lint found is pushed with the position of the node it is about
+    fn check(&self, path: &[&str], items: &[Node], found: &mut Vec<((usize, usize), String)>);
+}
+
+/// A problem found by a rule
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lint {
+    code: &'static str,
+    name: &'static str,
+    level: Level,
+    message: String,
+    position: (usize, usize),
+}
+
+impl Lint {
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// If a `#pragma hemtt suppress` of the lint's code or name applies at `line` of `path`
+    pub fn suppressed(&self, pragmas: &Pragmas, path: &str, line: usize) -> bool {
+        pragmas.suppressed_any(&[self.code, self.name], path, line)
+    }
+
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Line and column in the parsed source, both starting at 1
+    pub fn position(&self) -> (usize, usize) {
+        self.position
+    }
+
+    /// Renders the lint as an annotated snippet of the source
+    ///
+    /// `token` is where the lint is in the original files, `read` loads a file by its path
+    pub fn report<F>(&self, token: Option<&TokenPos>, read: F) -> String
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut files = SimpleFiles::new();
+        let mut labels = Vec::new();
+        if let Some(token) = token {
+            if let Some(source) = read(token.path()) {
+                let range = crate::error::range(&source, token);
+                labels.push(Label::primary(files.add(token.path(), source), range));
+            }
+        }
+        let diagnostic = match self.level {
+            Level::Error => Diagnostic::error(),
+            _ => Diagnostic::warning(),
+        }
+        .with_code(self.code)
+        .with_message(&self.message)
+        .with_labels(labels);
+        let mut writer = NoColor::new(Vec::new());
+        if term::emit(&mut writer, &term::Config::default(), &files, &diagnostic).is_err() {
+            return self.to_string();
+        }
+        String::from_utf8_lossy(&writer.into_inner()).into_owned()
+    }
+}
+
+impl std::fmt::Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.code, self.message)
+    }
+}
+
+/// Runs the rules that are not allowed by `options` over a config, in the order of the source
+pub fn lint(ast: &AST, options: &LinterOptions) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    for rule in RULES {
+        let level = options.level(*rule);
+        if level == Level::Allow {
+            continue;
+        }
+        let mut found = Vec::new();
+        walk(&ast.config, &mut Vec::new(), &mut |path, items| {
+            rule.check(path, items, &mut found)
+        });
+        lints.extend(found.into_iter().map(|(position, message)| Lint {
+            code: rule.code(),
+            name: rule.name(),
+            level,
+            message,
+            position,
+        }));
+    }
+    lints.sort_by_key(|lint| lint.position);
+    lints
+}
+
+/// Calls `f` with the items of the config and of every class in it
+fn walk<'a>(node: &'a Node, path: &mut Vec<&'a str>, f: &mut dyn FnMut(&[&str], &[Node])) {
+    let items = match node.statement {
+        Statement::Config(ref items) => items,
+        Statement::Class {
+            ref ident,
+            ref props,
+            ..
+        } => {
+            path.push(name(ident));
+            props
+        }
+        _ => return,
+    };
+    f(path, items);
+    for item in items {
+        walk(item, path, f);
+    }
+    if let Statement::Class { .. } = node.statement {
+        path.pop();
+    }
+}
+
+/// The name of a class or property
+fn name(ident: &Node) -> &str {
+    match ident.statement {
+        Statement::Ident(ref name) | Statement::IdentArray(ref name) => name,
+        _ => &ident.line,
+    }
+}
@@ -1,9 +1,69 @@
-#[derive(Copy, Clone, Debug, Default)]
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Level, Rule};
+
+/// How configs are checked and formatted, read from the `lint` table of a project
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct LinterOptions {
+    /// The level of rules by their code or name, other rules use their default level
+    rules: HashMap<String, Level>,
     class_inheritance_style: InheritanceStyle,
+    brace_style: BraceStyle,
+    /// Spaces per level of indentation
+    indent: usize,
+    /// The longest line an array is kept on before it is wrapped, one element per line
+    array_width: usize,
+}
+
+impl Default for LinterOptions {
+    fn default() -> Self {
+        Self {
+            rules: HashMap::new(),
+            class_inheritance_style: InheritanceStyle::default(),
+            brace_style: BraceStyle::default(),
+            indent: 4,
+            array_width: 100,
+        }
+    }
 }
 
-#[derive(Copy, Clone, Debug)]
+impl LinterOptions {
+    /// If every option is left at its default
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The level `rule` is reported at
+    pub fn level(&self, rule: &dyn Rule) -> Level {
+        self.rules
+            .get(rule.code())
+            .or_else(|| self.rules.get(rule.name()))
+            .copied()
+            .unwrap_or_else(|| rule.default_level())
+    }
+
+    pub fn class_inheritance_style(&self) -> InheritanceStyle {
+        self.class_inheritance_style
+    }
+
+    pub fn brace_style(&self) -> BraceStyle {
+        self.brace_style
+    }
+
+    pub fn indent(&self) -> usize {
+        self.indent
+    }
+
+    pub fn array_width(&self) -> usize {
+        self.array_width
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum InheritanceStyle {
     /// The colon is preceded by whitespace
     ///
@@ -23,3 +83,26 @@ impl Default for InheritanceStyle {
         InheritanceStyle::NoSpace
     }
 }
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BraceStyle {
+    /// The opening brace of a class is on the same line as its name
+    ///
+    ///```cpp
+    ///class my_class {
+    ///```
+    SameLine,
+    /// The opening brace of a class is on its own line
+    ///
+    ///```cpp
+    ///class my_class
+    ///{
+    ///```
+    NextLine,
+}
+impl Default for BraceStyle {
+    fn default() -> Self {
+        BraceStyle::SameLine
+    }
+}
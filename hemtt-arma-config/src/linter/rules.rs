@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+
+use super::{name, Level, Rule};
+use crate::{Node, Statement};
+
+/// Every rule, in the order of their codes
+pub static RULES: &[&dyn Rule] = &[
+    &DuplicateProperty,
+    &ParentChanged,
+    &PropertyClassName,
+    &EmptyRequiredAddons,
+    &ExpectedNumber,
+    &DeleteUndeclared,
+];
+
+/// Properties the game reads as numbers
+const NUMBER_PROPERTIES: &[&str] = &[
+    "accuracy",
+    "armor",
+    "cost",
+    "fuelCapacity",
+    "mass",
+    "maxSpeed",
+    "requiredVersion",
+    "scope",
+    "scopeArsenal",
+    "scopeCurator",
+    "side",
+    "transportSoldier",
+];
+
+/// Where items are, for messages
+fn location(path: &[&str]) -> String {
+    if path.is_empty() {
+        String::from("the root of the config")
+    } else {
+        format!("`{}`", path.join(" >> "))
+    }
+}
+
+/// A property is set twice in the same class, only the last value is used
+pub struct DuplicateProperty;
+impl Rule for DuplicateProperty {
+    fn code(&self) -> &'static str {
+        "C001"
+    }
+
+    fn name(&self) -> &'static str {
+        "duplicate_property"
+    }
+
+    fn default_level(&self) -> Level {
+        Level::Warning
+    }
+
+    fn check(&self, path: &[&str], items: &[Node], found: &mut Vec<((usize, usize), String)>) {
+        let mut seen = Vec::new();
+        for item in items {
+            if let Statement::Property {
+                ref ident,
+                expand: false,
+                shrink: false,
+                ..
+            } = item.statement
+            {
+                let key = name(ident).to_lowercase();
+                if seen.contains(&key) {
+                    found.push((
+                        item.start,
+                        format!(
+                            "`{}` is set more than once in {}",
+                            name(ident),
+                            location(path)
+                        ),
+                    ));
+                } else {
+                    seen.push(key);
+                }
+            }
+        }
+    }
+}
+
+/// A class is defined again with another parent, the game changes the parent of the class
+pub struct ParentChanged;
+impl Rule for ParentChanged {
+    fn code(&self) -> &'static str {
+        "C002"
+    }
+
+    fn name(&self) -> &'static str {
+        "parent_changed"
+    }
+
+    fn default_level(&self) -> Level {
+        Level::Error
+    }
+
+    fn check(&self, path: &[&str], items: &[Node], found: &mut Vec<((usize, usize), String)>) {
+        let mut parents: HashMap<String, Option<&str>> = HashMap::new();
+        for item in items {
+            if let Statement::Class {
+                ref ident,
+                ref extends,
+                ..
+            } = item.statement
+            {
+                let parent = extends.as_deref().map(name);
+                match parents.get(&name(ident).to_lowercase()) {
+                    Some(previous)
+                        if previous.map(str::to_lowercase) != parent.map(str::to_lowercase) =>
+                    {
+                        found.push((
+                            item.start,
+                            format!(
+                                "`{}` in {} was defined with {}, it is defined again with {}",
+                                name(ident),
+                                location(path),
+                                parent_name(*previous),
+                                parent_name(parent),
+                            ),
+                        ));
+                    }
+                    Some(_) => {}
+                    None => {
+                        parents.insert(name(ident).to_lowercase(), parent);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn parent_name(parent: Option<&str>) -> String {
+    parent.map_or_else(
+        || String::from("no parent"),
+        |parent| format!("the parent `{}`", parent),
+    )
+}
+
+/// A property has the same name as a class next to it
+pub struct PropertyClassName;
+impl Rule for PropertyClassName {
+    fn code(&self) -> &'static str {
+        "C003"
+    }
+
+    fn name(&self) -> &'static str {
+        "property_class_name"
+    }
+
+    fn default_level(&self) -> Level {
+        Level::Warning
+    }
+
+    fn check(&self, path: &[&str], items: &[Node], found: &mut Vec<((usize, usize), String)>) {
+        let classes: Vec<String> = items
+            .iter()
+            .filter_map(|item| match item.statement {
+                Statement::Class { ref ident, .. } | Statement::ClassDef(ref ident) => {
+                    Some(name(ident).to_lowercase())
+                }
+                _ => None,
+            })
+            .collect();
+        for item in items {
+            if let Statement::Property { ref ident, .. } = item.statement {
+                if classes.contains(&name(ident).to_lowercase()) {
+                    found.push((
+                        item.start,
+                        format!(
+                            "`{}` is both a property and a class in {}",
+                            name(ident),
+                            location(path)
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// An addon in `CfgPatches` does not require any addons, so it may load before the ones it changes
+pub struct EmptyRequiredAddons;
+impl Rule for EmptyRequiredAddons {
+    fn code(&self) -> &'static str {
+        "C004"
+    }
+
+    fn name(&self) -> &'static str {
+        "empty_required_addons"
+    }
+
+    fn default_level(&self) -> Level {
+        Level::Warning
+    }
+
+    fn check(&self, path: &[&str], items: &[Node], found: &mut Vec<((usize, usize), String)>) {
+        if path.len() != 2 || !path[0].eq_ignore_ascii_case("CfgPatches") {
+            return;
+        }
+        for item in items {
+            if let Statement::Property {
+                ref ident,
+                ref value,
+                expand: false,
+                shrink: false,
+            } = item.statement
+            {
+                if name(ident).eq_ignore_ascii_case("requiredAddons")
+                    && matches!(value.statement, Statement::Array(ref values) if values.is_empty())
+                {
+                    found.push((
+                        item.start,
+                        format!(
+                            "`requiredAddons` of `{}` is empty, it may load before the addons it changes",
+                            path[1]
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// A property the game reads as a number is set to a string
+pub struct ExpectedNumber;
+impl Rule for ExpectedNumber {
+    fn code(&self) -> &'static str {
+        "C005"
+    }
+
+    fn name(&self) -> &'static str {
+        "expected_number"
+    }
+
+    fn default_level(&self) -> Level {
+        Level::Warning
+    }
+
+    fn check(&self, _: &[&str], items: &[Node], found: &mut Vec<((usize, usize), String)>) {
+        for item in items {
+            if let Statement::Property {
+                ref ident,
+                ref value,
+                ..
+            } = item.statement
+            {
+                if let (Statement::Ident(ref ident), Statement::Str(ref value)) =
+                    (&ident.statement, &value.statement)
+                {
+                    if NUMBER_PROPERTIES
+                        .iter()
+                        .any(|p| p.eq_ignore_ascii_case(ident))
+                    {
+                        found.push((
+                            item.start,
+                            format!("`{}` should be a number, found \"{}\"", ident, value),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A class is deleted without being declared in the same class, so it may not exist when the
+/// config is loaded
+pub struct DeleteUndeclared;
+impl Rule for DeleteUndeclared {
+    fn code(&self) -> &'static str {
+        "C006"
+    }
+
+    fn name(&self) -> &'static str {
+        "delete_undeclared"
+    }
+
+    fn default_level(&self) -> Level {
+        Level::Warning
+    }
+
+    fn check(&self, path: &[&str], items: &[Node], found: &mut Vec<((usize, usize), String)>) {
+        for item in items {
+            if let Statement::ClassDelete(ref ident) = item.statement {
+                let declared = items.iter().any(|other| match other.statement {
+                    Statement::Class { ident: ref c, .. } | Statement::ClassDef(ref c) => {
+                        name(c).eq_ignore_ascii_case(name(ident))
+                    }
+                    _ => false,
+                });
+                if !declared {
+                    found.push((
+                        item.start,
+                        format!(
+                            "`{}` is deleted but not declared in {}, declare it with `class {};`",
+                            name(ident),
+                            location(path),
+                            name(ident)
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+}
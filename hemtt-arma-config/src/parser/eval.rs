@@ -0,0 +1,455 @@
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use super::{Node, Statement};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Str(String),
+}
+
+impl Value {
+    fn number(&self, op: &str) -> Result<f64, String> {
+        match self {
+            Value::Number(n) => Ok(*n),
+            Value::Str(s) => Err(format!("`{}` expects a number, found \"{}\"", op, s)),
+        }
+    }
+}
+
+impl From<Value> for Statement {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Number(n) => Statement::Float(n as f32),
+            Value::Str(s) => Statement::Str(s),
+        }
+    }
+}
+
+/// Variables assigned by `__EXEC` and constants defined by an `enum`,
+/// names are case insensitive like in SQF and configs
+#[derive(Clone, Debug, Default)]
+pub struct Scope {
+    variables: HashMap<String, Value>,
+    enums: HashMap<String, i64>,
+}
+
+impl Scope {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.variables.get(&name.to_lowercase())
+    }
+
+    pub fn set(&mut self, name: &str, value: Value) {
+        self.variables.insert(name.to_lowercase(), value);
+    }
+
+    pub fn constant(&self, name: &str) -> Option<i64> {
+        self.enums.get(&name.to_lowercase()).copied()
+    }
+
+    /// Defines the constants of an `enum`, a constant without a value follows the one before it
+    pub fn define_enum(&mut self, values: &[(String, Option<i64>)]) {
+        let mut next = 0;
+        for (name, value) in values {
+            let value = value.unwrap_or(next);
+            self.enums.insert(name.to_lowercase(), value);
+            next = value + 1;
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Item {
+    Number(f64),
+    Str(String),
+    Ident(String),
+    Op(char),
+    Assign,
+    Separator,
+    LeftParenthesis,
+    RightParenthesis,
+}
+
+fn lex(source: &str) -> Result<Vec<Item>, String> {
+    let mut items = Vec::new();
+    let mut chars: Peekable<Chars> = source.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' || c == '$' {
+            let mut literal = String::new();
+            while let Some(&c) = chars.peek() {
+                let exponent = (c == '-' || c == '+')
+                    && literal.ends_with(['e', 'E'])
+                    && !literal.starts_with("0x")
+                    && !literal.starts_with('$');
+                if c.is_ascii_alphanumeric() || c == '.' || c == '$' || exponent {
+                    literal.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            items.push(Item::Number(number(&literal)?));
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut string = String::new();
+            loop {
+                match chars.next() {
+                    Some(n) if n == c => {
+                        if chars.peek() == Some(&c) {
+                            chars.next();
+                            string.push(c);
+                        } else {
+                            break;
+                        }
+                    }
+                    Some(n) => string.push(n),
+                    None => return Err(String::from("unterminated string")),
+                }
+            }
+            items.push(Item::Str(string));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut ident = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_ascii_alphanumeric() || c == '_' {
+                    ident.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            items.push(Item::Ident(ident));
+        } else {
+            chars.next();
+            items.push(match c {
+                '(' => Item::LeftParenthesis,
+                ')' => Item::RightParenthesis,
+                '=' => Item::Assign,
+                ';' => Item::Separator,
+                '+' | '-' | '*' | '/' | '%' | '^' => Item::Op(c),
+                _ => return Err(format!("unexpected character `{}`", c)),
+            });
+        }
+    }
+    Ok(items)
+}
+
+fn number(literal: &str) -> Result<f64, String> {
+    let lower = literal.to_lowercase();
+    let parsed = if let Some(hex) = lower.strip_prefix("0x").or_else(|| lower.strip_prefix('$')) {
+        i64::from_str_radix(hex, 16).map(|n| n as f64).ok()
+    } else {
+        lower.parse().ok()
+    };
+    parsed.ok_or_else(|| format!("invalid number `{}`", literal))
+}
+
+struct Evaluator<'a> {
+    items: Vec<Item>,
+    pos: usize,
+    scope: &'a mut Scope,
+}
+
+impl<'a> Evaluator<'a> {
+    fn peek(&self) -> Option<&Item> {
+        self.items.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Item> {
+        let item = self.items.get(self.pos).cloned();
+        self.pos += 1;
+        item
+    }
+
+    /// Runs every statement, returning the value of the last one
+    fn statements(&mut self) -> Result<Option<Value>, String> {
+        let mut last = None;
+        while self.peek().is_some() {
+            if self.peek() == Some(&Item::Separator) {
+                self.next();
+                continue;
+            }
+            last = Some(self.statement()?);
+            match self.next() {
+                Some(Item::Separator) | None => {}
+                Some(item) => return Err(format!("unexpected {:?}", item)),
+            }
+        }
+        Ok(last)
+    }
+
+    fn statement(&mut self) -> Result<Value, String> {
+        if let Some(Item::Ident(ident)) = self.peek() {
+            let offset = if ident.eq_ignore_ascii_case("private") {
+                1
+            } else {
+                0
+            };
+            if let (Some(Item::Ident(name)), Some(Item::Assign)) = (
+                self.items.get(self.pos + offset).cloned(),
+                self.items.get(self.pos + offset + 1),
+            ) {
+                self.pos += offset + 2;
+                let value = self.binary(0)?;
+                self.scope.set(&name, value.clone());
+                return Ok(value);
+            }
+        }
+        self.binary(0)
+    }
+
+    fn binary(&mut self, min_precedence: u8) -> Result<Value, String> {
+        let mut left = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Item::Op(c)) => c.to_string(),
+                Some(Item::Ident(i)) => i.to_lowercase(),
+                _ => break,
+            };
+            let precedence = match precedence(&op) {
+                Some(p) if p >= min_precedence => p,
+                _ => break,
+            };
+            self.next();
+            let right = self.binary(precedence + 1)?;
+            left = apply(&op, left, right)?;
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Value, String> {
+        match self.next() {
+            Some(Item::Op('-')) => Ok(Value::Number(-self.unary()?.number("-")?)),
+            Some(Item::Op('+')) => Ok(Value::Number(self.unary()?.number("+")?)),
+            Some(Item::Number(n)) => Ok(Value::Number(n)),
+            Some(Item::Str(s)) => Ok(Value::Str(s)),
+            Some(Item::LeftParenthesis) => {
+                let value = self.binary(0)?;
+                match self.next() {
+                    Some(Item::RightParenthesis) => Ok(value),
+                    _ => Err(String::from("expected `)`")),
+                }
+            }
+            Some(Item::Ident(ident)) => {
+                let command = ident.to_lowercase();
+                if command == "pi" {
+                    return Ok(Value::Number(std::f64::consts::PI));
+                }
+                if UNARY.contains(&command.as_str()) {
+                    let value = self.unary()?;
+                    return call(&command, value);
+                }
+                self.scope
+                    .get(&ident)
+                    .cloned()
+                    .ok_or_else(|| format!("undefined variable `{}`", ident))
+            }
+            Some(item) => Err(format!("unexpected {:?}", item)),
+            None => Err(String::from("unexpected end of expression")),
+        }
+    }
+}
+
+const UNARY: [&str; 9] = [
+    "abs", "ceil", "floor", "round", "sqrt", "sin", "cos", "tan", "str",
+];
+
+fn precedence(op: &str) -> Option<u8> {
+    Some(match op {
+        "+" | "-" | "max" | "min" => 1,
+        "*" | "/" | "%" | "mod" => 2,
+        "^" => 3,
+        _ => return None,
+    })
+}
+
+fn apply(op: &str, left: Value, right: Value) -> Result<Value, String> {
+    if let ("+", Value::Str(l), Value::Str(r)) = (op, &left, &right) {
+        return Ok(Value::Str(format!("{}{}", l, r)));
+    }
+    let (left, right) = (left.number(op)?, right.number(op)?);
+    Ok(Value::Number(match op {
+        "+" => left + right,
+        "-" => left - right,
+        "*" => left * right,
+        "/" | "%" | "mod" if right == 0.0 => return Err(String::from("division by zero")),
+        "/" => left / right,
+        "%" | "mod" => left % right,
+        "^" => left.powf(right),
+        "max" => left.max(right),
+        "min" => left.min(right),
+        _ => return Err(format!("unknown operator `{}`", op)),
+    }))
+}
+
+fn call(command: &str, value: Value) -> Result<Value, String> {
+    if command == "str" {
+        return Ok(Value::Str(match value {
+            Value::Number(n) => n.to_string(),
+            Value::Str(s) => format!("\"{}\"", s),
+        }));
+    }
+    let n = value.number(command)?;
+    Ok(Value::Number(match command {
+        "abs" => n.abs(),
+        "ceil" => n.ceil(),
+        "floor" => n.floor(),
+        "round" => n.round(),
+        "sqrt" => n.sqrt(),
+        // SQF uses degrees
+        "sin" => n.to_radians().sin(),
+        "cos" => n.to_radians().cos(),
+        "tan" => n.to_radians().tan(),
+        _ => return Err(format!("unknown command `{}`", command)),
+    }))
+}
+
+/// Runs the statements of an `__EXEC`, storing any assignments in the scope
+pub fn execute(source: &str, scope: &mut Scope) -> Result<(), String> {
+    Evaluator {
+        items: lex(source)?,
+        pos: 0,
+        scope,
+    }
+    .statements()
+    .map(|_| ())
+}
+
+/// Evaluates the expression of an `__EVAL`, variables are read from the scope
+pub fn evaluate(source: &str, scope: &mut Scope) -> Result<Value, String> {
+    Evaluator {
+        items: lex(source)?,
+        pos: 0,
+        scope,
+    }
+    .statements()?
+    .ok_or_else(|| String::from("expected an expression"))
+}
+
+/// Replaces `__EXEC`, `__EVAL` and `enum` nodes in document order, so assignments and constants
+/// are visible to every later value
+pub fn resolve(node: &mut Node, scope: &mut Scope) -> Result<(), String> {
+    let replacement = match &mut node.statement {
+        Statement::Config(nodes) | Statement::Class { props: nodes, .. } => {
+            for node in nodes {
+                resolve(node, scope)?;
+            }
+            None
+        }
+        Statement::Array(nodes) => {
+            for node in nodes {
+                resolve_value(node, scope)?;
+            }
+            None
+        }
+        Statement::Property { value, .. } => {
+            resolve_value(value, scope)?;
+            None
+        }
+        Statement::Enum(values) => {
+            scope.define_enum(values);
+            Some(Statement::Gone)
+        }
+        Statement::Exec(source) => {
+            execute(source, scope).map_err(|e| format!("{} in `{}`", e, node.line))?;
+            Some(Statement::Gone)
+        }
+        Statement::Eval(source) => Some(
+            evaluate(source, scope)
+                .map_err(|e| format!("{} in `{}`", e, node.line))?
+                .into(),
+        ),
+        _ => None,
+    };
+    if let Some(statement) = replacement {
+        node.statement = statement;
+    }
+    Ok(())
+}
+
+/// Resolves a value, an unquoted word is an `enum` constant if one has its name or a string
+fn resolve_value(node: &mut Node, scope: &mut Scope) -> Result<(), String> {
+    if let Statement::Word(word) = &node.statement {
+        node.statement = match scope.constant(word) {
+            Some(value) => Statement::Integer(value),
+            None => Statement::Str(word.to_owned()),
+        };
+        Ok(())
+    } else {
+        resolve(node, scope)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{evaluate, execute, Scope, Value};
+
+    fn eval(source: &str) -> Result<Value, String> {
+        evaluate(source, &mut Scope::new())
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(eval("1 + 2 * 3"), Ok(Value::Number(7.0)));
+        assert_eq!(eval("(1 + 2) * 3"), Ok(Value::Number(9.0)));
+        assert_eq!(eval("2 ^ 3 - 10 mod 4"), Ok(Value::Number(6.0)));
+        assert_eq!(eval("-1.5e1 / 2"), Ok(Value::Number(-7.5)));
+        assert_eq!(eval("0x10 max $20 min 24"), Ok(Value::Number(24.0)));
+        assert_eq!(eval("floor 2.7 + round 0.5"), Ok(Value::Number(3.0)));
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(
+            eval("\"a\" + 'b' + str 5"),
+            Ok(Value::Str(String::from("ab5")))
+        );
+        assert_eq!(
+            eval("\"say \"\"hi\"\"\""),
+            Ok(Value::Str(String::from("say \"hi\"")))
+        );
+    }
+
+    #[test]
+    fn variables() {
+        let mut scope = Scope::new();
+        execute("_base = 10; private _Count = _base * 2", &mut scope).unwrap();
+        assert_eq!(
+            evaluate("_count + _BASE", &mut scope),
+            Ok(Value::Number(30.0))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!(eval("").is_err());
+        assert!(eval("1 +").is_err());
+        assert!(eval("(1").is_err());
+        assert!(eval("1 / 0").is_err());
+        assert!(eval("\"a\" * 2").is_err());
+        assert!(eval("_undefined").is_err());
+    }
+
+    #[test]
+    fn enums() {
+        let mut scope = Scope::new();
+        scope.define_enum(&[
+            (String::from("First"), None),
+            (String::from("Second"), None),
+            (String::from("Tenth"), Some(10)),
+            (String::from("Eleventh"), None),
+        ]);
+        assert_eq!(scope.constant("first"), Some(0));
+        assert_eq!(scope.constant("Second"), Some(1));
+        assert_eq!(scope.constant("TENTH"), Some(10));
+        assert_eq!(scope.constant("Eleventh"), Some(11));
+        assert_eq!(scope.constant("Twelfth"), None);
+    }
+}
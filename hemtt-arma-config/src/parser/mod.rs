@@ -1,8 +1,16 @@
-use std::{collections::HashMap, io::Write};
+use std::collections::HashMap;
 
 use pest::Parser;
 
+use crate::{
+    preprocess::{origin, LineMap, Token, TokenPos},
+    ArmaConfigError,
+};
+
+mod eval;
+
 mod node;
+pub(crate) use node::number;
 pub use node::Node;
 
 mod statement;
@@ -31,49 +39,83 @@ pub struct AST {
 
 /// Converts a raw string into an AST
 ///
+/// The `map` of the preprocessed source is used to report where a parsing error is in the original files,
+/// without it the error points at `context`
+///
 /// ```
 /// let content = "value = 123;";
-/// hemtt_arma_config::parse(content, "doc test", None);
+/// hemtt_arma_config::parse(content, "doc test", None).unwrap();
 /// ```
 pub fn parse(
     source: &str,
     context: &str,
-    map: Option<HashMap<usize, crate::preprocess::LineMap>>,
-) -> Result<AST, String> {
+    map: Option<HashMap<usize, LineMap>>,
+) -> Result<AST, ArmaConfigError> {
     let clean = source.replace('\r', "");
     let pair = ConfigParser::parse(Rule::file, &clean)
-        .unwrap_or_else(|e| {
-            let out = std::env::temp_dir().join("failed.txt");
-            let mut f = std::fs::File::create(&out).expect("failed to create failed.txt");
-            f.write_all(clean.as_bytes()).unwrap();
-            f.flush().unwrap();
-            if let Some(map) = map {
-                let (line, col) = match e.line_col {
-                    pest::error::LineColLocation::Pos(s) => s,
-                    pest::error::LineColLocation::Span(s, _) => s,
-                };
-                let spans = map.get(&line).unwrap();
-                if let Some(span) = spans.iter().find(|s| s.0 <= col && s.0 + s.1 >= col) {
-                    panic!(
-                        "failed to parse context: {}, {} ({}:{})\nerror: {}",
-                        context, span.2, span.3 .0, span.3 .1, e,
-                    )
-                }
-            }
-            panic!(
-                "failed to parse context: {}, saved at {}. error: {}",
-                context,
-                out.display(),
-                e
-            )
-        })
+        .map_err(|e| parsing_error(e, &clean, context, map.as_ref()))?
         .next()
         .unwrap();
     let pair = pair.into_inner().next().unwrap();
-    let config = Node::from_expr(std::env::current_dir().unwrap(), source, pair)?;
+    let mut config = Node::from_expr(std::env::current_dir().unwrap(), source, pair)
+        .map_err(ArmaConfigError::GENERIC)?;
+    resolve(&mut config).map_err(ArmaConfigError::GENERIC)?;
     Ok(AST { config })
 }
 
+/// Replaces the `__EXEC`, `__EVAL` and `enum` nodes of a parsed config
+pub(crate) fn resolve(config: &mut Node) -> Result<(), String> {
+    eval::resolve(config, &mut eval::Scope::new())
+}
+
+fn parsing_error(
+    error: pest::error::Error<Rule>,
+    source: &str,
+    context: &str,
+    map: Option<&HashMap<usize, LineMap>>,
+) -> ArmaConfigError {
+    let (line, col) = match error.line_col {
+        pest::error::LineColLocation::Pos(s) => s,
+        pest::error::LineColLocation::Span(s, _) => s,
+    };
+    let token = match map {
+        Some(map) => origin(map, line, col),
+        None => {
+            let word: String = source
+                .lines()
+                .nth(line - 1)
+                .unwrap_or_default()
+                .chars()
+                .skip(col - 1)
+                .take_while(|c| !c.is_whitespace())
+                .collect();
+            let end = (line, col + word.chars().count());
+            Some(TokenPos::mapped(
+                Token::Word(word),
+                context,
+                (line, col),
+                end,
+            ))
+        }
+    };
+    let (positives, negatives) = match error.variant {
+        pest::error::ErrorVariant::ParsingError {
+            positives,
+            negatives,
+        } => (
+            positives.iter().map(|r| format!("{:?}", r)).collect(),
+            negatives.iter().map(|r| format!("{:?}", r)).collect(),
+        ),
+        pest::error::ErrorVariant::CustomError { message } => (vec![message], Vec::new()),
+    };
+    ArmaConfigError::ParsingError {
+        positives,
+        negatives,
+        position: error.line_col,
+        token: token.map(Box::new),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::parse;
@@ -6,6 +6,8 @@ use super::{Rule, Statement};
 pub struct Node {
     /// Original text of the line
     pub line: String,
+    /// Line and column the node starts at in the parsed source, both starting at 1
+    pub start: (usize, usize),
     pub statement: Statement,
 }
 
@@ -19,6 +21,7 @@ impl Node {
     ) -> Result<Node, String> {
         let node = Node {
             line: pair.as_span().as_str().to_string(),
+            start: pair.as_span().start_pos().line_col(),
             statement: match pair.as_rule() {
                 Rule::config => Statement::Config(
                     pair.into_inner()
@@ -57,6 +60,28 @@ impl Node {
                 Rule::classdelete => Statement::ClassDelete(Box::new({
                     Node::from_expr(wd, source, pair.into_inner().next().unwrap())?
                 })),
+                Rule::enumdef => Statement::Enum(
+                    pair.into_inner()
+                        .map(|value| {
+                            let mut parts = value.into_inner();
+                            let ident = parts.next().unwrap().as_str().to_string();
+                            let value = match parts.next() {
+                                Some(value) => match number(value.as_str(), radix(&value))? {
+                                    Statement::Integer(value) => Some(value),
+                                    _ => {
+                                        return Err(format!(
+                                            "The value of `{}` is out of range: {}",
+                                            ident,
+                                            value.as_str()
+                                        ))
+                                    }
+                                },
+                                None => None,
+                            };
+                            Ok((ident, value))
+                        })
+                        .collect::<Result<_, String>>()?,
+                ),
                 Rule::prop => {
                     let mut parts = pair.into_inner();
                     Statement::Property {
@@ -65,6 +90,7 @@ impl Node {
                         }),
                         value: Box::new(Node::from_expr(wd, source, parts.next().unwrap())?),
                         expand: false,
+                        shrink: false,
                     }
                 }
                 Rule::propexpand => {
@@ -75,6 +101,18 @@ impl Node {
                         }),
                         value: Box::new(Node::from_expr(wd, source, parts.next().unwrap())?),
                         expand: true,
+                        shrink: false,
+                    }
+                }
+                Rule::propshrink => {
+                    let mut parts = pair.into_inner();
+                    Statement::Property {
+                        ident: Box::new({
+                            Node::from_expr(wd.clone(), source, parts.next().unwrap())?
+                        }),
+                        value: Box::new(Node::from_expr(wd, source, parts.next().unwrap())?),
+                        expand: false,
+                        shrink: true,
                     }
                 }
                 Rule::bool => Statement::Bool(pair.as_str() == "true"),
@@ -84,21 +122,31 @@ impl Node {
                         .collect::<ResultNodeVec>()?,
                 ),
                 Rule::float => Statement::Float(pair.as_str().parse().unwrap()),
-                Rule::integer => Statement::Integer(pair.as_str().parse().unwrap()),
-                Rule::hex => {
-                    Statement::Integer(i64::from_str_radix(&pair.as_str()[2..], 16).unwrap())
-                }
-                Rule::string => Statement::Str(String::from(pair.as_str())),
+                Rule::integer | Rule::hex => number(pair.as_str(), radix(&pair))?,
+                Rule::string => Statement::Str(pair.as_str().replace("\"\"", "\"")),
+                Rule::string_single => Statement::Str(pair.as_str().replace("''", "'")),
+                Rule::word => Statement::Word(pair.as_str().trim_end().to_string()),
                 Rule::ident => Statement::Ident(String::from(pair.as_str())),
                 Rule::identarray => {
                     Statement::IdentArray(String::from(pair.into_inner().next().unwrap().as_str()))
                 }
+                Rule::eval => {
+                    Statement::Eval(String::from(pair.into_inner().next().unwrap().as_str()))
+                }
+                Rule::exec => {
+                    Statement::Exec(String::from(pair.into_inner().next().unwrap().as_str()))
+                }
                 // Ignored
                 Rule::EOI => Statement::Gone,
                 Rule::file => unimplemented!(),
                 Rule::string_wrapper => unimplemented!(),
                 Rule::item => unimplemented!(),
+                Rule::enumvalue => unimplemented!(),
+                Rule::value_end => unimplemented!(),
+                Rule::exponent => unimplemented!(),
                 Rule::value => unimplemented!(),
+                Rule::expression => unimplemented!(),
+                Rule::expression_string => unimplemented!(),
                 Rule::COMMENT => unimplemented!(),
                 Rule::WHITESPACE => unimplemented!(),
             },
@@ -106,3 +154,39 @@ impl Node {
         Ok(node)
     }
 }
+
+fn radix(pair: &pest::iterators::Pair<Rule>) -> u32 {
+    if pair.as_rule() == Rule::hex {
+        16
+    } else {
+        10
+    }
+}
+
+/// Reads an integer literal such as `12`, `0x1F` or `-0x10`, shared with the CST
+///
+/// A literal too large for an integer is read as a float, like the binarizer does.
+pub(crate) fn number(literal: &str, radix: u32) -> Result<Statement, String> {
+    let (negative, digits) = match literal.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, literal),
+    };
+    let digits = if radix == 16 {
+        digits.get(2..).unwrap_or_default()
+    } else {
+        digits
+    };
+    if digits.is_empty() || !digits.chars().all(|digit| digit.is_digit(radix)) {
+        return Err(format!("Invalid number: {}", literal));
+    }
+    if let Ok(value) = i64::from_str_radix(digits, radix) {
+        return Ok(Statement::Integer(if negative { -value } else { value }));
+    }
+    let value = digits.chars().fold(0_f64, |value, digit| {
+        value * f64::from(radix) + f64::from(digit.to_digit(radix).unwrap())
+    }) as f32;
+    if !value.is_finite() {
+        return Err(format!("The number is out of range: {}", literal));
+    }
+    Ok(Statement::Float(if negative { -value } else { value }))
+}
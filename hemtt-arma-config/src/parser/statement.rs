@@ -11,7 +11,10 @@ pub enum Statement {
     Property {
         ident: Box<Node>,
         value: Box<Node>,
+        /// `+=`, the elements are added to the inherited array
         expand: bool,
+        /// `-=`, the elements are removed from the inherited array
+        shrink: bool,
     },
     Class {
         ident: Box<Node>,
@@ -20,8 +23,17 @@ pub enum Statement {
     },
     ClassDef(Box<Node>),
     ClassDelete(Box<Node>),
+    /// Constants of an `enum`, numbered from the previous one when no value is given,
+    /// removed after parsing
+    Enum(Vec<(String, Option<i64>)>),
     Ident(String),
     IdentArray(String),
+    /// An unquoted value, an `enum` constant or otherwise a string
+    Word(String),
+    /// Expression of an `__EVAL`, replaced by its result after parsing
+    Eval(String),
+    /// Statements of an `__EXEC`, removed after parsing
+    Exec(String),
 
     Gone,
 }
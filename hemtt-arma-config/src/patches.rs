@@ -0,0 +1,214 @@
+//! The addons declared in `CfgPatches` and the addons they require
+
+use std::collections::HashMap;
+
+use crate::simplify::{ArrayElement, Class, Config, Entry};
+
+/// A class of `CfgPatches`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Patch {
+    name: String,
+    required: Vec<String>,
+}
+
+impl Patch {
+    pub fn new(name: String, required: Vec<String>) -> Self {
+        Self { name, required }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The `requiredAddons` of the patch
+    pub fn required(&self) -> &[String] {
+        &self.required
+    }
+}
+
+impl Config {
+    /// The classes of `CfgPatches`, `None` when the config does not declare it
+    pub fn patches(&self) -> Option<Vec<Patch>> {
+        let patches = class(&self.root.entries, "CfgPatches")?;
+        Some(
+            patches
+                .entries
+                .iter()
+                .filter_map(|(name, entry)| match entry {
+                    Entry::Class(patch) if !patch.external && !patch.deletion => {
+                        Some(Patch::new(name.clone(), required(patch)))
+                    }
+                    _ => None,
+                })
+                .collect(),
+        )
+    }
+}
+
+fn class<'a>(entries: &'a [(String, Entry)], name: &str) -> Option<&'a Class> {
+    entries.iter().find_map(|(n, entry)| match entry {
+        Entry::Class(class) if n.eq_ignore_ascii_case(name) && !class.external => Some(class),
+        _ => None,
+    })
+}
+
+fn required(patch: &Class) -> Vec<String> {
+    patch
+        .entries
+        .iter()
+        .find_map(|(name, entry)| match entry {
+            Entry::Array(array) if name.eq_ignore_ascii_case("requiredAddons") => Some(
+                array
+                    .elements
+                    .iter()
+                    .filter_map(|element| match element {
+                        ArrayElement::Str(name) => Some(name.clone()),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// A problem with how the patches of a project require each other
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Problem {
+    /// `patch` requires an addon that is neither in the project nor external, `suggestion` is
+    /// a patch of the project with a similar name
+    Missing {
+        patch: String,
+        required: String,
+        suggestion: Option<String>,
+    },
+    /// Patches that require each other, the first is repeated at the end
+    Cycle(Vec<String>),
+    /// No other patch of the project requires the patch, not reported when the project has a
+    /// single patch
+    Unused(String),
+}
+
+/// Checks the patches of a project against each other
+///
+/// `external` is whether a patch that is not in the project comes from the game or a dependency
+pub fn check<F>(patches: &[Patch], external: F) -> Vec<Problem>
+where
+    F: Fn(&str) -> bool,
+{
+    let index: HashMap<String, &Patch> = patches
+        .iter()
+        .map(|patch| (patch.name.to_lowercase(), patch))
+        .collect();
+    let mut problems = Vec::new();
+    for patch in patches {
+        for required in &patch.required {
+            if !index.contains_key(&required.to_lowercase()) && !external(required) {
+                problems.push(Problem::Missing {
+                    patch: patch.name.clone(),
+                    required: required.clone(),
+                    suggestion: suggest(patches, required),
+                });
+            }
+        }
+    }
+    let mut visited = HashMap::new();
+    for patch in patches {
+        cycles(patch, &index, &mut visited, &mut Vec::new(), &mut problems);
+    }
+    for patch in patches.iter().filter(|_| patches.len() > 1) {
+        let required = patches.iter().any(|other| {
+            other
+                .required
+                .iter()
+                .any(|r| r.eq_ignore_ascii_case(&patch.name))
+        });
+        if !required {
+            problems.push(Problem::Unused(patch.name.clone()));
+        }
+    }
+    problems
+}
+
+/// Finds the cycles reachable from `patch` with a depth first search, `visited` is `false`
+/// while a patch is on the `stack` and `true` once all of its requirements were searched
+fn cycles<'a>(
+    patch: &'a Patch,
+    index: &HashMap<String, &'a Patch>,
+    visited: &mut HashMap<String, bool>,
+    stack: &mut Vec<&'a str>,
+    problems: &mut Vec<Problem>,
+) {
+    let key = patch.name.to_lowercase();
+    match visited.get(&key) {
+        Some(true) => return,
+        Some(false) => {
+            let start = stack
+                .iter()
+                .position(|name| name.eq_ignore_ascii_case(&patch.name))
+                .unwrap();
+            let mut cycle: Vec<String> = stack[start..].iter().map(|s| s.to_string()).collect();
+            cycle.push(patch.name.clone());
+            problems.push(Problem::Cycle(cycle));
+            return;
+        }
+        None => {}
+    }
+    visited.insert(key.clone(), false);
+    stack.push(&patch.name);
+    for required in &patch.required {
+        if let Some(required) = index.get(&required.to_lowercase()) {
+            cycles(required, index, visited, stack, problems);
+        }
+    }
+    stack.pop();
+    visited.insert(key, true);
+}
+
+/// The patch with the name closest to `name`, if it is close enough to be a typo
+///
+/// Only patches with the same tag, the part before the first `_`, are considered, patches of
+/// other mods often have names that are close to the project's.
+fn suggest(patches: &[Patch], name: &str) -> Option<String> {
+    let name = name.to_lowercase();
+    let tag = |name: &str| name.split('_').next().unwrap_or_default().to_string();
+    patches
+        .iter()
+        .map(|patch| (patch.name.to_lowercase(), patch))
+        .filter(|(lower, _)| tag(lower) == tag(&name))
+        .map(|(lower, patch)| (distance(&lower, &name), patch))
+        .filter(|(distance, _)| *distance <= 2.max(name.len() / 5))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, patch)| patch.name.clone())
+}
+
+/// The Levenshtein distance between two strings
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            current.push(
+                (previous[j] + usize::from(ca != *cb))
+                    .min(previous[j + 1] + 1)
+                    .min(current[j] + 1),
+            );
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::distance;
+
+    #[test]
+    fn distances() {
+        assert_eq!(distance("cba_main", "cba_main"), 0);
+        assert_eq!(distance("cba_main", "cba_mian"), 2);
+        assert_eq!(distance("ace_common", "ace_comon"), 1);
+        assert_eq!(distance("", "abc"), 3);
+    }
+}
@@ -0,0 +1,196 @@
+use std::cell::{Cell, RefCell};
+use std::iter::Peekable;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::vec::IntoIter;
+
+use super::{render, Token, TokenPos};
+use crate::{resolver::Resolver, ArmaConfigError};
+
+const BUILTINS: [&str; 13] = [
+    "__LINE__",
+    "__FILE__",
+    "__COUNTER__",
+    "__COUNTER_RESET__",
+    "__DATE_ARR__",
+    "__DATE_STR__",
+    "__TIME__",
+    "__TIME_UTC__",
+    "__DAY__",
+    "__MONTH__",
+    "__YEAR__",
+    "__TIMESTAMP_UTC__",
+    "__has_include",
+];
+
+/// Predefined macros that are computed when they are used, consulted before the user defines
+///
+/// Dates and times are in UTC, the `SOURCE_DATE_EPOCH` environment variable can be set
+/// to use a fixed time for reproducible builds, or a time can be given with `with_epoch`
+#[derive(Debug)]
+pub struct Builtins {
+    counter: Cell<usize>,
+    /// The outermost macro being expanded, `__LINE__` and `__FILE__` report where it was used
+    site: RefCell<Option<TokenPos>>,
+    epoch: u64,
+}
+
+impl Builtins {
+    pub fn new() -> Self {
+        let epoch = match std::env::var("SOURCE_DATE_EPOCH") {
+            Ok(value) => value.trim().parse().unwrap_or_else(|_| {
+                warn!("Ignoring invalid SOURCE_DATE_EPOCH `{}`", value);
+                now()
+            }),
+            Err(_) => now(),
+        };
+        Self::with_epoch(epoch)
+    }
+
+    /// Builtins that report `epoch`, in seconds since the Unix epoch, as the current time
+    pub fn with_epoch(epoch: u64) -> Self {
+        Self {
+            counter: Cell::new(0),
+            site: RefCell::new(None),
+            epoch,
+        }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        BUILTINS.contains(&name)
+    }
+
+    /// Marks `token` as the place macros are being expanded from, returns false if already inside an expansion
+    pub fn enter(&self, token: &TokenPos) -> bool {
+        let mut site = self.site.borrow_mut();
+        if site.is_some() {
+            false
+        } else {
+            *site = Some(token.to_owned());
+            true
+        }
+    }
+
+    pub fn leave(&self) {
+        *self.site.borrow_mut() = None;
+    }
+
+    pub fn resolve<R>(
+        &self,
+        name: &str,
+        token: &TokenPos,
+        iter: &mut Peekable<IntoIter<TokenPos>>,
+        root: &str,
+        resolver: R,
+    ) -> Result<Option<Vec<TokenPos>>, ArmaConfigError>
+    where
+        R: Resolver,
+    {
+        if !self.contains(name) {
+            return Ok(None);
+        }
+        let site = self
+            .site
+            .borrow()
+            .clone()
+            .unwrap_or_else(|| token.to_owned());
+        let (year, month, day, hour, minute, second) = civil(self.epoch);
+        let value = match name {
+            "__LINE__" => site.start().1 .0.to_string(),
+            "__FILE__" => format!("\"{}\"", site.path()),
+            "__COUNTER__" => {
+                let value = self.counter.get();
+                self.counter.set(value + 1);
+                value.to_string()
+            }
+            "__COUNTER_RESET__" => {
+                self.counter.set(0);
+                String::new()
+            }
+            "__DATE_ARR__" => format!("{},{},{},{},{}", year, month, day, hour, minute),
+            "__DATE_STR__" => format!(
+                "\"{}/{:02}/{:02}, {:02}:{:02}:{:02}\"",
+                year, month, day, hour, minute, second
+            ),
+            "__TIME__" | "__TIME_UTC__" => format!("{:02}:{:02}:{:02}", hour, minute, second),
+            "__DAY__" => day.to_string(),
+            "__MONTH__" => month.to_string(),
+            "__YEAR__" => year.to_string(),
+            "__TIMESTAMP_UTC__" => self.epoch.to_string(),
+            "__has_include" => {
+                skip_whitespace!(iter);
+                let file = if iter.peek().map(|tp| tp.token()) == Some(&Token::LeftParenthesis) {
+                    render(read_args!(iter).into_iter().flatten().collect())
+                        .export()
+                        .trim()
+                        .trim_matches(|c| c == '"' || c == '<' || c == '>')
+                        .to_owned()
+                } else {
+                    return Err(ArmaConfigError::InvalidBuiltin {
+                        token: token.to_owned(),
+                        reason: String::from("expected a path in parentheses"),
+                    });
+                };
+                if resolver.resolve(root, token.path(), &file).is_ok() {
+                    "1"
+                } else {
+                    "0"
+                }
+                .to_string()
+            }
+            _ => unreachable!(),
+        };
+        Ok(Some(
+            super::tokenize(&value, site.path())
+                .map_err(|e| ArmaConfigError::InvalidBuiltin {
+                    token: token.to_owned(),
+                    reason: e.to_string(),
+                })?
+                .into_iter()
+                .map(|tp| TokenPos::with_pos(tp.into_token(), &site))
+                .collect(),
+        ))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Converts seconds since the unix epoch to a UTC (year, month, day, hour, minute, second)
+fn civil(epoch: u64) -> (i64, u64, u64, u64, u64, u64) {
+    let days = (epoch / 86400) as i64;
+    let seconds = epoch % 86400;
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u64;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u64;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::civil;
+
+    #[test]
+    fn civil_dates() {
+        assert_eq!(civil(0), (1970, 1, 1, 0, 0, 0));
+        assert_eq!(civil(951_782_400), (2000, 2, 29, 0, 0, 0));
+        assert_eq!(civil(1_609_459_199), (2020, 12, 31, 23, 59, 59));
+    }
+}
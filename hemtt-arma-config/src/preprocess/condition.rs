@@ -0,0 +1,236 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Item {
+    Number(i64),
+    Ident,
+    Op(&'static str),
+    LeftParenthesis,
+    RightParenthesis,
+}
+
+const OPERATORS: [&str; 24] = [
+    "||", "&&", "==", "!=", "<=", ">=", "<<", ">>", "<", ">", "+", "-", "*", "/", "%", "!", "~",
+    "&", "|", "^", "?", ":", "(", ")",
+];
+
+fn lex(source: &str) -> Result<Vec<Item>, String> {
+    let mut items = Vec::new();
+    let mut chars: Peekable<Chars> = source.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() {
+            let mut literal = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_ascii_alphanumeric() {
+                    literal.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            items.push(Item::Number(number(&literal)?));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            while let Some(&c) = chars.peek() {
+                if c.is_ascii_alphanumeric() || c == '_' {
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            items.push(Item::Ident);
+        } else {
+            let rest: String = chars.clone().take(2).collect();
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(*op))
+                .ok_or_else(|| format!("unexpected character `{}`", c))?;
+            for _ in 0..op.len() {
+                chars.next();
+            }
+            items.push(match *op {
+                "(" => Item::LeftParenthesis,
+                ")" => Item::RightParenthesis,
+                op => Item::Op(op),
+            });
+        }
+    }
+    Ok(items)
+}
+
+fn number(literal: &str) -> Result<i64, String> {
+    let lower = literal.to_lowercase();
+    let trimmed = lower.trim_end_matches(['u', 'l']);
+    let parsed = if let Some(hex) = trimmed.strip_prefix("0x") {
+        i64::from_str_radix(hex, 16)
+    } else if trimmed.len() > 1 && trimmed.starts_with('0') {
+        i64::from_str_radix(&trimmed[1..], 8)
+    } else {
+        trimmed.parse()
+    };
+    parsed.map_err(|_| format!("invalid number `{}`", literal))
+}
+
+struct Evaluator {
+    items: Vec<Item>,
+    pos: usize,
+}
+
+impl Evaluator {
+    fn peek(&self) -> Option<&Item> {
+        self.items.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Item> {
+        let item = self.items.get(self.pos).cloned();
+        self.pos += 1;
+        item
+    }
+
+    fn expect(&mut self, expected: Item) -> Result<(), String> {
+        match self.next() {
+            Some(item) if item == expected => Ok(()),
+            Some(item) => Err(format!("expected {:?}, found {:?}", expected, item)),
+            None => Err(format!("expected {:?}, found end of expression", expected)),
+        }
+    }
+
+    fn ternary(&mut self) -> Result<i64, String> {
+        let condition = self.binary(0)?;
+        if self.peek() == Some(&Item::Op("?")) {
+            self.next();
+            let left = self.ternary()?;
+            self.expect(Item::Op(":"))?;
+            let right = self.ternary()?;
+            Ok(if condition != 0 { left } else { right })
+        } else {
+            Ok(condition)
+        }
+    }
+
+    fn binary(&mut self, min_precedence: u8) -> Result<i64, String> {
+        let mut left = self.unary()?;
+        while let Some(Item::Op(op)) = self.peek() {
+            let op = *op;
+            let precedence = match precedence(op) {
+                Some(p) if p >= min_precedence => p,
+                _ => break,
+            };
+            self.next();
+            let right = self.binary(precedence + 1)?;
+            left = apply(op, left, right)?;
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<i64, String> {
+        match self.next() {
+            Some(Item::Op("!")) => Ok((self.unary()? == 0) as i64),
+            Some(Item::Op("~")) => Ok(!self.unary()?),
+            Some(Item::Op("-")) => Ok(self.unary()?.wrapping_neg()),
+            Some(Item::Op("+")) => self.unary(),
+            Some(Item::Number(n)) => Ok(n),
+            Some(Item::Ident) => Ok(0),
+            Some(Item::LeftParenthesis) => {
+                let value = self.ternary()?;
+                self.expect(Item::RightParenthesis)?;
+                Ok(value)
+            }
+            Some(item) => Err(format!("unexpected {:?}", item)),
+            None => Err(String::from("unexpected end of expression")),
+        }
+    }
+}
+
+fn precedence(op: &str) -> Option<u8> {
+    Some(match op {
+        "||" => 1,
+        "&&" => 2,
+        "|" => 3,
+        "^" => 4,
+        "&" => 5,
+        "==" | "!=" => 6,
+        "<" | "<=" | ">" | ">=" => 7,
+        "<<" | ">>" => 8,
+        "+" | "-" => 9,
+        "*" | "/" | "%" => 10,
+        _ => return None,
+    })
+}
+
+fn apply(op: &str, left: i64, right: i64) -> Result<i64, String> {
+    Ok(match op {
+        "||" => (left != 0 || right != 0) as i64,
+        "&&" => (left != 0 && right != 0) as i64,
+        "|" => left | right,
+        "^" => left ^ right,
+        "&" => left & right,
+        "==" => (left == right) as i64,
+        "!=" => (left != right) as i64,
+        "<" => (left < right) as i64,
+        "<=" => (left <= right) as i64,
+        ">" => (left > right) as i64,
+        ">=" => (left >= right) as i64,
+        "<<" => left.wrapping_shl(right as u32),
+        ">>" => left.wrapping_shr(right as u32),
+        "+" => left.wrapping_add(right),
+        "-" => left.wrapping_sub(right),
+        "*" => left.wrapping_mul(right),
+        "/" | "%" if right == 0 => return Err(String::from("division by zero")),
+        "/" => left.wrapping_div(right),
+        "%" => left.wrapping_rem(right),
+        _ => return Err(format!("unknown operator `{}`", op)),
+    })
+}
+
+/// Evaluates a fully expanded `#if` or `#elif` expression
+///
+/// Like the C preprocessor, any identifier that is left over after expansion evaluates to `0`
+pub fn evaluate(source: &str) -> Result<i64, String> {
+    let mut evaluator = Evaluator {
+        items: lex(source)?,
+        pos: 0,
+    };
+    if evaluator.items.is_empty() {
+        return Err(String::from("expected an expression"));
+    }
+    let value = evaluator.ternary()?;
+    if let Some(item) = evaluator.peek() {
+        return Err(format!("unexpected {:?}", item));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::evaluate;
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(evaluate("1 + 2 * 3"), Ok(7));
+        assert_eq!(evaluate("(1 + 2) * 3"), Ok(9));
+        assert_eq!(evaluate("10 / 3 % 2"), Ok(1));
+        assert_eq!(evaluate("-4 + ~0"), Ok(-5));
+        assert_eq!(evaluate("0x10 | 010"), Ok(24));
+        assert_eq!(evaluate("1 << 4 >> 2"), Ok(4));
+    }
+
+    #[test]
+    fn logic() {
+        assert_eq!(evaluate("3 > 2 && 2 >= 2"), Ok(1));
+        assert_eq!(evaluate("!1 || 0"), Ok(0));
+        assert_eq!(evaluate("1 == 1 ? 5 : 6"), Ok(5));
+        assert_eq!(evaluate("UNDEFINED_THING == 0"), Ok(1));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(evaluate("").is_err());
+        assert!(evaluate("1 +").is_err());
+        assert!(evaluate("(1").is_err());
+        assert!(evaluate("1 / 0").is_err());
+        assert!(evaluate("1 2").is_err());
+    }
+}
@@ -4,6 +4,8 @@ pub enum IfState {
     PassingIf,
     ReadingElse,
     PassingElse,
+    /// A previous branch of the conditional was taken, skip the remaining `#elif`s
+    PassingElif,
     PassingChild,
 }
 
@@ -14,6 +16,7 @@ impl IfState {
             IfState::PassingIf => false,
             IfState::ReadingElse => true,
             IfState::PassingElse => false,
+            IfState::PassingElif => false,
             IfState::PassingChild => false,
         }
     }
@@ -38,12 +41,43 @@ impl IfStates {
         self.0.pop()
     }
 
+    /// The parent conditionals are all being read
+    fn parent_reading(&self) -> bool {
+        self.0
+            .iter()
+            .take(self.0.len().saturating_sub(1))
+            .all(|f| f.reading())
+    }
+
+    /// An `#elif` at the current level would need its condition evaluated
+    pub fn can_elif(&self) -> bool {
+        self.parent_reading() && self.0.last() == Some(&IfState::PassingIf)
+    }
+
+    pub fn elif(&mut self, condition: bool) {
+        if self.parent_reading() {
+            if let Some(new) = match self.pop() {
+                Some(IfState::PassingIf) => Some(if condition {
+                    IfState::ReadingIf
+                } else {
+                    IfState::PassingIf
+                }),
+                Some(IfState::ReadingIf) => Some(IfState::PassingElif),
+                Some(state) => Some(state),
+                None => None,
+            } {
+                self.push(new);
+            }
+        }
+    }
+
     pub fn flip(&mut self) {
-        if self.0.iter().take(self.0.len() - 1).all(|f| f.reading()) {
+        if self.parent_reading() {
             if let Some(new) = match self.pop() {
                 Some(IfState::PassingChild) => Some(IfState::PassingChild),
                 Some(IfState::PassingIf) => Some(IfState::ReadingElse),
                 Some(IfState::ReadingIf) => Some(IfState::PassingElse),
+                Some(IfState::PassingElif) => Some(IfState::PassingElse),
                 Some(IfState::PassingElse) => None,
                 Some(IfState::ReadingElse) => None,
                 None => None,
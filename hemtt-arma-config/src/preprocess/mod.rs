@@ -11,8 +11,9 @@ use token::Whitespace;
 pub use token::{PreProcessParser, Rule, Token, TokenPos};
 
 mod render;
-pub use render::{render, LineMap};
+pub use render::{origin, render, LineMap, Rendered};
 
+mod condition;
 mod define;
 use define::Define;
 mod ifstate;
@@ -25,20 +26,12 @@ pub type LineCol = (usize, (usize, usize));
 pub fn tokenize(source: &str, path: &str) -> Result<Vec<TokenPos>, Error<Rule>> {
     let mut tokens = Vec::new();
 
+    // Comments are skipped by the grammar, so positions are taken from the spans
     let pairs = PreProcessParser::parse(Rule::file, source)?;
-    let mut line = 1;
-    let mut col = 1;
-    let mut offset = 0;
     for pair in pairs {
-        let start = (offset, (line, col));
-        if let Rule::newline = pair.as_rule() {
-            line += 1;
-            col = 1;
-        } else {
-            col += pair.as_str().len();
-        }
-        offset += pair.as_str().len();
-        let end = (offset, (line, col));
+        let span = pair.as_span();
+        let start = (span.start(), span.start_pos().line_col());
+        let end = (span.end(), span.end_pos().line_col());
         tokens.push(TokenPos::new(path, pair, start, end));
     }
 
@@ -189,32 +182,46 @@ macro_rules! read_line {
     }};
 }
 
+mod builtin;
+use builtin::Builtins;
+mod pragma;
+pub use pragma::{Flag, Pragma, Pragmas, Scope};
+mod state;
+use state::State;
+pub use state::{Expansion, Trace};
+
 pub fn _resolve<R>(
     ident: &str,
+    token: &TokenPos,
     define: &Define,
     root: &str,
     resolver: R,
     defines: &HashMap<String, Define>,
+    state: &State,
 ) -> Result<Option<Vec<TokenPos>>, ArmaConfigError>
 where
     R: Resolver,
 {
     Ok(if let Some(d) = defines.get(ident) {
-        let mut ret = Vec::new();
         let mut context = defines.to_owned();
         if let Some(dargs) = &d.args {
             if let Some(args) = &define.args {
                 if dargs.len() != args.len() {
                     return Err(ArmaConfigError::ArgCoundMismatch {
-                        expected: args.len(),
-                        actual: dargs.len(),
-                        args: args.iter().map(|a| render(a.to_vec()).export()).collect(),
+                        token: token.to_owned(),
+                        expected: dargs.len(),
+                        actual: args.len(),
                     });
                 }
                 for i in 0..dargs.len() {
                     if let Token::Word(key) = &dargs[i][0].token() {
                         if args[i].len() == 1 {
                             if let Token::Word(value) = &args[i][0].token() {
+                                if value == key {
+                                    // An argument passed on under its own name is left as written
+                                    context.remove(key);
+                                    continue;
+                                }
                                 context.insert(
                                     key.to_owned(),
                                     if let Some(ed) = defines.get(value) {
@@ -242,50 +249,159 @@ where
                 }
             }
         }
-        let mut iter = d.statement.clone().into_iter().peekable();
-        while let Some(token) = iter.next() {
-            match &token.token() {
-                Token::Directive => {
-                    if let Some(tp) = iter.peek() {
-                        match tp.token() {
-                            Token::Word(_) => {
-                                if let Token::Word(w) = iter.next().unwrap().token() {
-                                    ret.push(TokenPos::with_pos(Token::DoubleQuote, &token));
-                                    ret.append(&mut _resolve_word(
+        let index = state.push(ident, token)?;
+        let expanded =
+            _expand(d, &mut context, root, resolver, state).map_err(|e| e.within(token))?;
+        state.pop(index, &expanded);
+        Some(expanded)
+    } else {
+        None
+    })
+}
+
+/// Expands the body of a define, with its arguments already in `context`
+fn _expand<R>(
+    define: &Define,
+    context: &mut HashMap<String, Define>,
+    root: &str,
+    resolver: R,
+    state: &State,
+) -> Result<Vec<TokenPos>, ArmaConfigError>
+where
+    R: Resolver,
+{
+    let mut ret = Vec::new();
+    // Indexes of words created by `##`, they are expanded again once the whole body is read
+    let mut pasted = Vec::new();
+    let mut iter = define.statement.clone().into_iter().peekable();
+    while let Some(token) = iter.next() {
+        match &token.token() {
+            Token::Directive => {
+                if let Some(tp) = iter.peek() {
+                    match tp.token() {
+                        Token::Word(_) => {
+                            // Stringify, the argument is expanded first
+                            if let Token::Word(w) = iter.next().unwrap().token() {
+                                ret.push(TokenPos::with_pos(Token::DoubleQuote, &token));
+                                ret.append(&mut _resolve_word(
+                                    &mut iter,
+                                    w,
+                                    &token,
+                                    root,
+                                    resolver.clone(),
+                                    context,
+                                    state,
+                                )?);
+                                ret.push(TokenPos::with_pos(Token::DoubleQuote, &token));
+                            }
+                        }
+                        Token::Directive => {
+                            iter.next();
+                            let mut spacing = Vec::new();
+                            while let Some(tp) = iter.peek() {
+                                if tp.token().is_whitespace() {
+                                    spacing.push(iter.next().unwrap());
+                                } else {
+                                    break;
+                                }
+                            }
+                            if let Some(next) = iter.next() {
+                                let right = if let Token::Word(w) = next.token() {
+                                    _resolve_word(
                                         &mut iter,
                                         w,
-                                        &token,
+                                        &next,
                                         root,
                                         resolver.clone(),
-                                        &mut context,
-                                    )?);
-                                    ret.push(TokenPos::with_pos(Token::DoubleQuote, &token));
-                                }
-                            }
-                            Token::Directive => {
-                                iter.next();
+                                        context,
+                                        state,
+                                    )?
+                                } else {
+                                    vec![next]
+                                };
+                                _paste(&mut ret, spacing, right, &mut pasted);
                             }
-                            _ => {}
                         }
+                        _ => {}
                     }
                 }
-                Token::Word(w) => {
-                    ret.append(&mut _resolve_word(
-                        &mut iter,
-                        w,
-                        &token,
-                        root,
-                        resolver.clone(),
-                        &mut context,
-                    )?);
+            }
+            Token::Word(w) => {
+                ret.append(&mut _resolve_word(
+                    &mut iter,
+                    w,
+                    &token,
+                    root,
+                    resolver.clone(),
+                    context,
+                    state,
+                )?);
+            }
+            _ => ret.push(token.to_owned()),
+        }
+    }
+    if pasted.is_empty() {
+        return Ok(ret);
+    }
+    let mut rescanned = Vec::new();
+    for (i, token) in ret.into_iter().enumerate() {
+        if let Token::Word(w) = token.token() {
+            if pasted.contains(&i) && context.get(w).map(|d| !d.call).unwrap_or(false) {
+                if let Some(mut r) = _resolve(
+                    w,
+                    &token,
+                    &context[w],
+                    root,
+                    resolver.clone(),
+                    context,
+                    state,
+                )? {
+                    rescanned.append(&mut r);
+                    continue;
                 }
-                _ => ret.push(token.to_owned()),
             }
         }
-        Some(ret)
-    } else {
-        None
-    })
+        rescanned.push(token);
+    }
+    Ok(rescanned)
+}
+
+/// Appends the tokens following a `##` to `left`, joining the words on either side
+///
+/// Whitespace around the `##` is dropped when joining, but a `##` that is only preceded by
+/// whitespace marks the start of a parameter (`count ##var`) and the tokens are kept apart
+fn _paste(
+    left: &mut Vec<TokenPos>,
+    mut spacing: Vec<TokenPos>,
+    mut right: Vec<TokenPos>,
+    pasted: &mut Vec<usize>,
+) {
+    let left_end = left
+        .iter()
+        .rposition(|tp| !tp.token().is_whitespace())
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let right_start = right
+        .iter()
+        .position(|tp| !tp.token().is_whitespace())
+        .unwrap_or(right.len());
+    let marker = left_end != left.len() && spacing.is_empty();
+    let joined = match (
+        left_end.checked_sub(1).map(|i| left[i].token()),
+        right.get(right_start).map(|tp| tp.token()),
+    ) {
+        (Some(Token::Word(l)), Some(Token::Word(r))) if !marker => format!("{}{}", l, r),
+        _ => {
+            left.append(&mut spacing);
+            left.append(&mut right);
+            return;
+        }
+    };
+    left.truncate(left_end);
+    let token = left.pop().unwrap();
+    left.push(TokenPos::with_pos(Token::from_word(joined), &token));
+    pasted.push(left.len() - 1);
+    left.extend(right.into_iter().skip(right_start + 1));
 }
 
 fn _resolve_word<R>(
@@ -295,32 +411,40 @@ fn _resolve_word<R>(
     root: &str,
     resolver: R,
     defines: &mut HashMap<String, Define>,
+    state: &State,
 ) -> Result<Vec<TokenPos>, ArmaConfigError>
 where
     R: Resolver,
 {
+    if let Some(r) = state
+        .builtins
+        .resolve(ident, token, iter, root, resolver.clone())?
+    {
+        return Ok(r);
+    }
     if let Some(d2) = defines.get(ident) {
         if d2.call {
             if let Some(r) = _resolve(
                 ident,
+                token,
                 &Define {
                     call: false,
                     args: Some(
                         read_args!(iter)
                             .into_iter()
-                            .map(|arg| _preprocess(arg, root, resolver.clone(), defines))
-                            .collect::<Result<Vec<Vec<TokenPos>>, ArmaConfigError>>()
-                            .unwrap(),
+                            .map(|arg| _preprocess(arg, root, resolver.clone(), defines, state))
+                            .collect::<Result<Vec<Vec<TokenPos>>, ArmaConfigError>>()?,
                     ),
                     statement: Vec::new(),
                 },
                 root,
                 resolver,
                 defines,
+                state,
             )? {
                 return Ok(r);
             }
-        } else if let Some(r) = _resolve(ident, d2, root, resolver, defines)? {
+        } else if let Some(r) = _resolve(ident, token, d2, root, resolver, defines, state)? {
             return Ok(r);
         } else {
             return Ok(vec![token.to_owned()]);
@@ -329,6 +453,70 @@ where
     Ok(vec![token.to_owned()])
 }
 
+/// Evaluates the condition of an `#if` or `#elif`
+fn _condition<R>(
+    line: Vec<TokenPos>,
+    directive: &TokenPos,
+    root: &str,
+    resolver: R,
+    defines: &mut HashMap<String, Define>,
+    state: &State,
+) -> Result<bool, ArmaConfigError>
+where
+    R: Resolver,
+{
+    let mut replaced = Vec::new();
+    let mut iter = line.into_iter().peekable();
+    while let Some(token) = iter.next() {
+        match token.token() {
+            Token::Word(w) if w == "defined" => {
+                skip_whitespace!(iter);
+                let parenthesis = if let Some(tp) = iter.peek() {
+                    tp.token() == &Token::LeftParenthesis
+                } else {
+                    false
+                };
+                if parenthesis {
+                    iter.next();
+                    skip_whitespace!(iter);
+                }
+                let name = match iter.next().map(|tp| tp.into_token()) {
+                    Some(Token::Word(name)) => name,
+                    _ => {
+                        return Err(ArmaConfigError::InvalidCondition {
+                            token: directive.to_owned(),
+                            reason: String::from("expected a name after `defined`"),
+                        })
+                    }
+                };
+                if parenthesis {
+                    skip_whitespace!(iter);
+                    if iter.next().map(|tp| tp.into_token()) != Some(Token::RightParenthesis) {
+                        return Err(ArmaConfigError::InvalidCondition {
+                            token: directive.to_owned(),
+                            reason: format!("expected `)` after `defined({}`", name),
+                        });
+                    }
+                }
+                let value = if defines.contains_key(&name) || state.builtins.contains(&name) {
+                    "1"
+                } else {
+                    "0"
+                };
+                replaced.push(TokenPos::with_pos(Token::Word(value.to_string()), &token));
+            }
+            _ => replaced.push(token),
+        }
+    }
+    let expanded = render(_preprocess(replaced, root, resolver, defines, state)?).export();
+    condition::evaluate(&expanded)
+        .map(|value| value != 0)
+        .map_err(|reason| ArmaConfigError::InvalidCondition {
+            token: directive.to_owned(),
+            reason: format!("{} in `{}`", reason, expanded.trim()),
+        })
+}
+
 pub fn preprocess<R>(
     source: Vec<TokenPos>,
     root: &str,
@@ -338,7 +526,74 @@ where
     R: Resolver,
 {
     let mut defines: HashMap<String, Define> = HashMap::new();
-    _preprocess(source, root, resolver, &mut defines)
+    _preprocess(source, root, resolver, &mut defines, &State::new(false))
+}
+
+/// Preprocesses like `preprocess`, with the date and time builtins fixed at `epoch` seconds since
+/// the Unix epoch instead of the current time
+pub fn preprocess_at<R>(
+    source: Vec<TokenPos>,
+    root: &str,
+    resolver: R,
+    epoch: u64,
+) -> Result<Vec<TokenPos>, ArmaConfigError>
+where
+    R: Resolver,
+{
+    let mut defines: HashMap<String, Define> = HashMap::new();
+    let state = State::with_builtins(false, Builtins::with_epoch(epoch));
+    _preprocess(source, root, resolver, &mut defines, &state)
+}
+
+/// The output of the preprocessor and what it collected along the way
+pub struct Preprocessed {
+    tokens: Vec<TokenPos>,
+    pragmas: Pragmas,
+    trace: Option<Trace>,
+}
+
+impl Preprocessed {
+    pub fn tokens(&self) -> &[TokenPos] {
+        &self.tokens
+    }
+
+    pub fn pragmas(&self) -> &Pragmas {
+        &self.pragmas
+    }
+
+    pub fn trace(&self) -> Option<&Trace> {
+        self.trace.as_ref()
+    }
+
+    pub fn take_trace(&mut self) -> Option<Trace> {
+        self.trace.take()
+    }
+
+    /// Renders the tokens, the pragmas are kept with the result
+    pub fn render(self) -> Rendered {
+        render(self.tokens).with_pragmas(self.pragmas)
+    }
+}
+
+/// Preprocesses like `preprocess`, keeping the `#pragma hemtt` directives, and every macro expansion if `trace` is set
+pub fn preprocess_with<R>(
+    source: Vec<TokenPos>,
+    root: &str,
+    resolver: R,
+    trace: bool,
+) -> Result<Preprocessed, ArmaConfigError>
+where
+    R: Resolver,
+{
+    let mut defines: HashMap<String, Define> = HashMap::new();
+    let state = State::new(trace);
+    let tokens = _preprocess(source, root, resolver, &mut defines, &state)?;
+    let (pragmas, trace) = state.into_parts();
+    Ok(Preprocessed {
+        tokens,
+        pragmas,
+        trace,
+    })
 }
 
 pub fn _preprocess<R>(
@@ -346,6 +601,7 @@ pub fn _preprocess<R>(
     root: &str,
     resolver: R,
     defines: &mut std::collections::HashMap<std::string::String, define::Define>,
+    state: &State,
 ) -> Result<Vec<TokenPos>, ArmaConfigError>
 where
     R: Resolver,
@@ -353,11 +609,20 @@ where
     let mut ret = Vec::new();
     let mut iter = source.into_iter().peekable();
     let mut if_state = IfStates::new();
+    // The directives that opened each conditional still waiting for an `#endif`
+    let mut opened = Vec::new();
     let mut new_line = true;
     while let Some(token) = iter.next() {
         match (&token.token(), if_state.reading(), new_line) {
             (Token::Directive, r, true) => {
-                if let Token::Word(directive) = iter.next().unwrap().token() {
+                if let Some(Token::Word(directive)) = iter.next().map(TokenPos::into_token) {
+                    match directive.as_str() {
+                        "if" | "ifdef" | "ifndef" => opened.push(token.clone()),
+                        "endif" => {
+                            opened.pop();
+                        }
+                        _ => {}
+                    }
                     match (directive.as_str(), r) {
                         ("define", true) => {
                             skip_whitespace!(iter);
@@ -374,10 +639,10 @@ where
                                                         root,
                                                         resolver.clone(),
                                                         defines,
+                                                        state,
                                                     )
                                                 })
-                                                .collect::<Result<Vec<Vec<TokenPos>>, ArmaConfigError>>()
-                                                .unwrap();
+                                                .collect::<Result<Vec<Vec<TokenPos>>, ArmaConfigError>>()?;
                                             Some(args)
                                         } else {
                                             None
@@ -386,14 +651,23 @@ where
                                         None
                                     };
                                     let body = read_line!(iter);
-                                    defines.insert(
-                                        name.to_owned(),
-                                        Define {
-                                            call: args.is_some(),
-                                            args,
-                                            statement: body,
-                                        },
-                                    );
+                                    if state.is_forced(name) {
+                                        debug!(
+                                            "Not redefining `{}` at {}:{}, it is forced by a pragma",
+                                            name,
+                                            tp.path(),
+                                            tp.start().1 .0
+                                        );
+                                    } else {
+                                        defines.insert(
+                                            name.to_owned(),
+                                            Define {
+                                                call: args.is_some(),
+                                                args,
+                                                statement: body,
+                                            },
+                                        );
+                                    }
                                 } else {
                                     return Err(ArmaConfigError::DefineWithoutName { token: tp });
                                 }
@@ -405,7 +679,16 @@ where
                             skip_whitespace!(iter);
                             if let Some(tp) = iter.next() {
                                 if let Token::Word(name) = tp.token().clone() {
-                                    defines.remove(&name);
+                                    if state.is_forced(&name) {
+                                        debug!(
+                                            "Not undefining `{}` at {}:{}, it is forced by a pragma",
+                                            name,
+                                            tp.path(),
+                                            tp.start().1 .0
+                                        );
+                                    } else {
+                                        defines.remove(&name);
+                                    }
                                 } else {
                                     return Err(ArmaConfigError::UndefineWithoutName { token: tp });
                                 }
@@ -417,7 +700,8 @@ where
                             skip_whitespace!(iter);
                             if let Some(tp) = iter.next() {
                                 if let Token::Word(name) = tp.token().clone() {
-                                    if defines.contains_key(&name) {
+                                    if defines.contains_key(&name) || state.builtins.contains(&name)
+                                    {
                                         if_state.push(IfState::ReadingIf);
                                     } else {
                                         if_state.push(IfState::PassingIf);
@@ -429,7 +713,8 @@ where
                             skip_whitespace!(iter);
                             if let Some(tp) = iter.next() {
                                 if let Token::Word(name) = tp.token().clone() {
-                                    if defines.contains_key(&name) {
+                                    if defines.contains_key(&name) || state.builtins.contains(&name)
+                                    {
                                         if_state.push(IfState::PassingIf);
                                     } else {
                                         if_state.push(IfState::ReadingIf);
@@ -437,12 +722,40 @@ where
                                 }
                             }
                         }
-                        ("ifdef", false) => {
-                            if_state.push(IfState::PassingChild);
+                        ("if", true) => {
+                            let condition = _condition(
+                                read_line!(iter),
+                                &token,
+                                root,
+                                resolver.clone(),
+                                defines,
+                                state,
+                            )?;
+                            if condition {
+                                if_state.push(IfState::ReadingIf);
+                            } else {
+                                if_state.push(IfState::PassingIf);
+                            }
                         }
-                        ("ifndef", false) => {
+                        ("ifdef", false) | ("ifndef", false) | ("if", false) => {
                             if_state.push(IfState::PassingChild);
                         }
+                        ("elif", _) => {
+                            let line = read_line!(iter);
+                            if if_state.can_elif() {
+                                let condition = _condition(
+                                    line,
+                                    &token,
+                                    root,
+                                    resolver.clone(),
+                                    defines,
+                                    state,
+                                )?;
+                                if_state.elif(condition);
+                            } else {
+                                if_state.elif(false);
+                            }
+                        }
                         ("else", _) => if_state.flip(),
                         ("endif", _) => {
                             if_state.pop();
@@ -452,15 +765,41 @@ where
                                 .export()
                                 .trim_matches('"')
                                 .to_owned();
-                            ret.append(&mut _preprocess(
-                                {
-                                    let resolved = resolver.resolve(root, token.path(), &file)?;
-                                    super::tokenize(resolved.data(), resolved.path()).unwrap()
-                                },
-                                root,
-                                resolver.clone(),
-                                defines,
-                            )?);
+                            ret.append(
+                                &mut _preprocess(
+                                    {
+                                        let resolved = resolver
+                                            .resolve(root, token.path(), &file)
+                                            .map_err(|e| ArmaConfigError::IncludeNotFound {
+                                                token: token.clone(),
+                                                reason: format!("`{}`: {}", file, e),
+                                            })?;
+                                        super::tokenize(resolved.data(), resolved.path())?
+                                    },
+                                    root,
+                                    resolver.clone(),
+                                    defines,
+                                    state,
+                                )
+                                .map_err(|e| e.within(&token))?,
+                            );
+                        }
+                        ("pragma", true) => {
+                            let line = read_line!(iter);
+                            let pragmas = pragma::parse(&token, line.clone())?;
+                            if let Some(Pragma::Define { .. }) = pragmas.first() {
+                                let (name, body) = pragma::define(&token, line)?;
+                                defines.insert(
+                                    name.clone(),
+                                    Define {
+                                        call: false,
+                                        args: None,
+                                        statement: body,
+                                    },
+                                );
+                                state.force(&name);
+                            }
+                            state.add_pragmas(pragmas);
                         }
                         (_, false) => {
                             read_line!(iter);
@@ -478,10 +817,19 @@ where
                 }
             }
             (Token::Word(text), true, _) => {
-                if defines.contains_key(text) {
+                if state.builtins.contains(text) {
+                    ret.append(
+                        &mut state
+                            .builtins
+                            .resolve(text, &token, &mut iter, root, resolver.clone())?
+                            .unwrap(),
+                    );
+                } else if defines.contains_key(text) {
+                    let entered = state.builtins.enter(&token);
                     ret.append(
                         &mut _resolve(
                             text,
+                            &token,
                             &Define {
                                 call: false,
                                 args: if let Some(tp) = iter.peek() {
@@ -495,10 +843,10 @@ where
                                                         root,
                                                         resolver.clone(),
                                                         defines,
+                                                        state,
                                                     )
                                                 })
-                                                .collect::<Result<Vec<Vec<TokenPos>>, ArmaConfigError>>()
-                                                .unwrap(),
+                                                .collect::<Result<Vec<Vec<TokenPos>>, ArmaConfigError>>()?,
                                         )
                                     } else {
                                         None
@@ -511,9 +859,13 @@ where
                             root,
                             resolver.clone(),
                             defines,
+                            state,
                         )?
                         .unwrap(),
                     );
+                    if entered {
+                        state.builtins.leave();
+                    }
                 } else {
                     ret.push(token);
                 }
@@ -532,6 +884,9 @@ where
             _ => {}
         }
     }
+    if let Some(token) = opened.pop() {
+        return Err(ArmaConfigError::UnterminatedIf { token });
+    }
     Ok(ret)
 }
 
@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+
+use super::{render, Token, TokenPos};
+use crate::ArmaConfigError;
+
+/// Build options that can be set from a file
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Flag {
+    /// Pack the file as text instead of rapifying it
+    PboNoBinarize,
+}
+
+impl Flag {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "pbo_no_binarize" => Some(Self::PboNoBinarize),
+            _ => None,
+        }
+    }
+}
+
+/// What part of a file a `suppress` pragma applies to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    /// From the pragma to the `end` of the lint, or to the end of the file
+    Start,
+    /// `#pragma hemtt suppress <code> end`, ends the region of an earlier pragma
+    End,
+    /// `#pragma hemtt suppress <code> next`, only the line after the pragma
+    Next,
+}
+
+/// A `#pragma hemtt` directive, `line` is where it was in `path`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Pragma {
+    /// `#pragma hemtt suppress <code>`, a lint that is ignored in part of its file
+    Suppress {
+        code: String,
+        path: String,
+        line: usize,
+        scope: Scope,
+    },
+    /// `#pragma hemtt flag <flag>`
+    Flag {
+        flag: Flag,
+        path: String,
+        line: usize,
+    },
+    /// `#pragma hemtt define <name> <value>`, a define that `#define` and `#undef` can not change
+    Define {
+        name: String,
+        path: String,
+        line: usize,
+    },
+}
+
+/// The pragmas of a file and everything it included, in the order they were read
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pragmas(Vec<Pragma>);
+
+impl Pragmas {
+    pub fn new(pragmas: Vec<Pragma>) -> Self {
+        Self(pragmas)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Pragma> {
+        self.0.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn flag(&self, flag: Flag) -> bool {
+        self.0
+            .iter()
+            .any(|pragma| matches!(pragma, Pragma::Flag { flag: f, .. } if *f == flag))
+    }
+
+    /// If the lint `code` is suppressed at `line` of `path`
+    pub fn suppressed(&self, code: &str, path: &str, line: usize) -> bool {
+        self.suppressed_any(&[code], path, line)
+    }
+
+    /// If a lint that can be named by any of `names` is suppressed at `line` of `path`
+    ///
+    /// A `next` pragma applies to the line after it, otherwise the last `suppress` of the lint
+    /// before the line decides, a region is ended by an `end`.
+    pub fn suppressed_any(&self, names: &[&str], path: &str, line: usize) -> bool {
+        let mut last = None;
+        for pragma in &self.0 {
+            if let Pragma::Suppress {
+                code,
+                path: p,
+                line: l,
+                scope,
+            } = pragma
+            {
+                if p != path || *l > line || !names.contains(&code.as_str()) {
+                    continue;
+                }
+                match scope {
+                    Scope::Next if *l + 1 == line => return true,
+                    Scope::Next => {}
+                    _ => {
+                        if !matches!(last, Some((last, _)) if last > *l) {
+                            last = Some((*l, *scope));
+                        }
+                    }
+                }
+            }
+        }
+        matches!(last, Some((_, Scope::Start)))
+    }
+}
+
+/// Reads the rest of a `#pragma` line, pragmas for other tools are ignored
+pub fn parse(token: &TokenPos, line: Vec<TokenPos>) -> Result<Vec<Pragma>, ArmaConfigError> {
+    let text = render(line).export();
+    let mut words = text.split_whitespace();
+    if words.next() != Some("hemtt") {
+        debug!(
+            "Ignoring pragma `{}` at {}:{}",
+            text.trim(),
+            token.path(),
+            token.start().1 .0
+        );
+        return Ok(Vec::new());
+    }
+    let invalid = |reason: String| ArmaConfigError::InvalidPragma {
+        token: token.to_owned(),
+        reason,
+    };
+    let path = token.path().to_string();
+    let line = token.start().1 .0;
+    let command = words
+        .next()
+        .ok_or_else(|| invalid(String::from("expected a command after `hemtt`")))?;
+    let args = words.collect::<Vec<_>>();
+    if args.is_empty() {
+        return Err(invalid(format!("expected a value after `{}`", command)));
+    }
+    match command {
+        "suppress" => {
+            let (codes, scope) = match args.split_last() {
+                Some((&"end", codes)) => (codes, Scope::End),
+                Some((&"next", codes)) => (codes, Scope::Next),
+                _ => (&args[..], Scope::Start),
+            };
+            if codes.is_empty() {
+                return Err(invalid(format!(
+                    "expected a lint before `{}`",
+                    args[args.len() - 1]
+                )));
+            }
+            Ok(codes
+                .iter()
+                .map(|code| Pragma::Suppress {
+                    code: (*code).to_string(),
+                    path: path.clone(),
+                    line,
+                    scope,
+                })
+                .collect())
+        }
+        "define" => Ok(vec![Pragma::Define {
+            name: args[0].to_string(),
+            path,
+            line,
+        }]),
+        "flag" => args
+            .into_iter()
+            .map(|name| {
+                Flag::from_name(name)
+                    .map(|flag| Pragma::Flag {
+                        flag,
+                        path: path.clone(),
+                        line,
+                    })
+                    .ok_or_else(|| invalid(format!("unknown flag `{}`", name)))
+            })
+            .collect(),
+        _ => Err(invalid(format!("unknown command `{}`", command))),
+    }
+}
+
+/// The name and body of a `#pragma hemtt define` line, which is read after `hemtt define`
+pub fn define(
+    token: &TokenPos,
+    line: Vec<TokenPos>,
+) -> Result<(String, Vec<TokenPos>), ArmaConfigError> {
+    let mut tokens = line
+        .into_iter()
+        .skip_while(|t| t.token().is_whitespace())
+        .peekable();
+    let mut words = 0;
+    while let Some(t) = tokens.next() {
+        if t.token().is_whitespace() {
+            continue;
+        }
+        words += 1;
+        if words < 3 {
+            continue;
+        }
+        if let Token::Word(name) = t.token() {
+            if matches!(
+                tokens.peek().map(TokenPos::token),
+                Some(Token::LeftParenthesis)
+            ) {
+                return Err(ArmaConfigError::InvalidPragma {
+                    token: token.to_owned(),
+                    reason: format!("the define `{}` can not take arguments", name),
+                });
+            }
+            let name = name.to_owned();
+            let body = tokens.skip_while(|t| t.token().is_whitespace()).collect();
+            return Ok((name, body));
+        }
+        break;
+    }
+    Err(ArmaConfigError::InvalidPragma {
+        token: token.to_owned(),
+        reason: String::from("expected the name of the define"),
+    })
+}
@@ -1,4 +1,8 @@
-pub fn wrap<S: Into<String>>(source: S) -> String {
+pub fn wrap<S1, S2>(source: S1, sources: S2) -> String
+where
+    S1: Into<String>,
+    S2: Into<String>,
+{
     let head = r#"
 <html>
   <head>
@@ -19,18 +23,38 @@ pub fn wrap<S: Into<String>>(source: S) -> String {
         .keyword {
             color: #4FC1FF;
         }
+        :target {
+            background-color: #264F78;
+        }
     </style>
+  </head>
     <body>
         <h1>HEMTT PreProcess Inspection</h1>
         <div class="code">
 <pre>
 "#;
-    let foot = r#"
+    let middle = r#"
 </pre>
         </div>
+        <h1>Sources</h1>
+"#;
+    let foot = r#"
     </body>
-  </head>
 </html>
 "#;
-    format!("{}{}{}", head, source.into(), foot)
+    format!(
+        "{}{}{}{}{}",
+        head,
+        source.into(),
+        middle,
+        sources.into(),
+        foot
+    )
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
 }
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-pub use self::rendered::{LineMap, Rendered};
+pub use self::rendered::{origin, LineMap, Rendered};
 
 use super::{token::Token, TokenPos};
 
@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 use std::fmt::Write;
 
-use crate::preprocess::token::{Token, TokenPos};
+use crate::preprocess::{
+    token::{Token, TokenPos},
+    Pragmas,
+};
 
 /// A reference from the rendered AST to the original source
 /// 0: Rendered Line
@@ -15,11 +18,21 @@ pub type LineMap = Vec<(usize, usize, String, (usize, usize), (usize, usize), To
 pub struct Rendered {
     tokens: Vec<TokenPos>,
     map: HashMap<usize, LineMap>,
+    pragmas: Pragmas,
 }
 
 impl Rendered {
     pub fn new(tokens: Vec<TokenPos>, map: HashMap<usize, LineMap>) -> Self {
-        Self { tokens, map }
+        Self {
+            tokens,
+            map,
+            pragmas: Pragmas::default(),
+        }
+    }
+
+    pub fn with_pragmas(mut self, pragmas: Pragmas) -> Self {
+        self.pragmas = pragmas;
+        self
     }
 
     pub fn tokens(&self) -> &[TokenPos] {
@@ -30,6 +43,16 @@ impl Rendered {
         &self.map
     }
 
+    /// The source token at `line` and `col` of the output, or the last one before it
+    pub fn origin(&self, line: usize, col: usize) -> Option<TokenPos> {
+        origin(&self.map, line, col)
+    }
+
+    /// The `#pragma hemtt` directives that were read while preprocessing
+    pub fn pragmas(&self) -> &Pragmas {
+        &self.pragmas
+    }
+
     pub fn export(&self) -> String {
         let mut content = String::new();
         for token in &self.tokens {
@@ -42,52 +65,98 @@ impl Rendered {
         serde_json::to_string(&self.map)
     }
 
-    pub fn export_html(&self) -> String {
+    pub fn export_pragmas_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(&self.pragmas)
+    }
+
+    /// An HTML page of the output, each token links to the line it came from in the sources
+    /// listed below it, `read` loads a source file by the path of its tokens
+    pub fn export_html<F>(&self, read: F) -> String
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut files: Vec<&str> = Vec::new();
         let mut content = String::new();
         for token in &self.tokens {
-            let title = format!("Source: {} {:?}", token.path(), token.start().1);
-            println!("D> {:?}", token.token());
+            let text = super::html::escape(&token.to_string());
             match token.token() {
-                Token::Keyword(_) => {
-                    _ = write!(
-                        content,
-                        "<span class=\"keyword\" title=\"{}\">{}</span>",
-                        title,
-                        token.to_string()
-                    )
-                }
+                Token::Newline | Token::Whitespace(_) | Token::EOI => content.push_str(&text),
+                _ if token.path().is_empty() => content.push_str(&text),
                 _ => {
+                    let file = files
+                        .iter()
+                        .position(|f| *f == token.path())
+                        .unwrap_or_else(|| {
+                            files.push(token.path());
+                            files.len() - 1
+                        });
+                    let class = if let Token::Keyword(_) = token.token() {
+                        "info keyword"
+                    } else {
+                        "info"
+                    };
+                    let (line, col) = token.start().1;
                     _ = write!(
                         content,
-                        "<span title=\"{}\">{}</span>",
-                        title,
-                        token.to_string()
-                    )
-                } // Token::Word(_) => {}
-                  // Token::Alpha(_) => {}
-                  // Token::Underscore => {}
-                  // Token::Dash => {}
-                  // Token::Assignment => {}
-                  // Token::LeftBrace => {}
-                  // Token::RightBrace => {}
-                  // Token::LeftBracket => {}
-                  // Token::RightBracket => {}
-                  // Token::LeftParenthesis => {}
-                  // Token::RightParenthesis => {}
-                  // Token::Colon => {}
-                  // Token::Semicolon => {}
-                  // Token::Directive => {}
-                  // Token::Escape => {}
-                  // Token::Comma => {}
-                  // Token::Decimal => {}
-                  // Token::DoubleQuote => {}
-                  // Token::SingleQuote => {}
-                  // Token::Char(_) => {}
-                  // Token::Newline => {}
-                  // Token::Whitespace(_) => {}
-                  // Token::EOI => {}
+                        "<a class=\"{}\" href=\"#f{}-{}\" title=\"{}:{}:{}\">{}</a>",
+                        class,
+                        file,
+                        line,
+                        super::html::escape(token.path()),
+                        line,
+                        col,
+                        text
+                    );
+                }
             }
         }
-        super::html::wrap(content)
+        let mut sources = String::new();
+        for (file, path) in files.iter().enumerate() {
+            _ = writeln!(
+                sources,
+                "<h2 id=\"f{}\">{}</h2>",
+                file,
+                super::html::escape(path)
+            );
+            if let Some(source) = read(path) {
+                sources.push_str("<div class=\"code\"><pre>\n");
+                for (i, line) in source.lines().enumerate() {
+                    _ = writeln!(
+                        sources,
+                        "<span id=\"f{}-{}\">{:>5}  {}</span>",
+                        file,
+                        i + 1,
+                        i + 1,
+                        super::html::escape(line)
+                    );
+                }
+                sources.push_str("</pre></div>\n");
+            }
+        }
+        super::html::wrap(content, sources)
     }
 }
+
+/// The source token at `line` and `col` of the preprocessed output, or the last one before it
+pub fn origin(map: &HashMap<usize, LineMap>, line: usize, col: usize) -> Option<TokenPos> {
+    let spans = (1..=line).rev().find_map(|l| {
+        map.get(&l)
+            .filter(|spans| !spans.is_empty())
+            .map(|spans| (l, spans))
+    })?;
+    let span = if spans.0 == line {
+        spans
+            .1
+            .iter()
+            .rev()
+            .find(|s| s.0 <= col && !matches!(s.5, Token::Whitespace(_)))
+            .or_else(|| spans.1.first())
+    } else {
+        spans
+            .1
+            .iter()
+            .rev()
+            .find(|s| !matches!(s.5, Token::Whitespace(_)))
+    }?;
+    Some(TokenPos::mapped(span.5.clone(), &span.2, span.3, span.4))
+}
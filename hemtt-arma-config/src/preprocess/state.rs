@@ -0,0 +1,135 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+
+use super::{
+    builtin::Builtins,
+    pragma::{Pragma, Pragmas},
+    render, TokenPos,
+};
+use crate::ArmaConfigError;
+
+/// Everything a run of the preprocessor keeps track of besides the defines
+#[derive(Debug)]
+pub struct State {
+    pub builtins: Builtins,
+    /// The macros currently being expanded, outermost first
+    active: RefCell<Vec<String>>,
+    pragmas: RefCell<Vec<Pragma>>,
+    /// The defines of `#pragma hemtt define`, which `#define` and `#undef` do not change
+    forced: RefCell<HashSet<String>>,
+    trace: Option<RefCell<Trace>>,
+}
+
+impl State {
+    pub fn new(trace: bool) -> Self {
+        Self::with_builtins(trace, Builtins::new())
+    }
+
+    pub fn with_builtins(trace: bool, builtins: Builtins) -> Self {
+        Self {
+            builtins,
+            active: RefCell::new(Vec::new()),
+            pragmas: RefCell::new(Vec::new()),
+            forced: RefCell::new(HashSet::new()),
+            trace: if trace {
+                Some(RefCell::new(Trace::default()))
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Marks `name` as being expanded where `token` used it, fails if it is already being expanded
+    pub fn push(&self, name: &str, token: &TokenPos) -> Result<Option<usize>, ArmaConfigError> {
+        let mut active = self.active.borrow_mut();
+        if let Some(start) = active.iter().position(|n| n == name) {
+            let mut cycle = active[start..].to_vec();
+            cycle.push(name.to_owned());
+            return Err(ArmaConfigError::RecursiveMacro {
+                token: token.to_owned(),
+                cycle,
+            });
+        }
+        let index = self.trace.as_ref().map(|trace| {
+            let mut trace = trace.borrow_mut();
+            trace.0.push(Expansion {
+                name: name.to_owned(),
+                site: token.to_owned(),
+                depth: active.len(),
+                output: Vec::new(),
+            });
+            trace.0.len() - 1
+        });
+        active.push(name.to_owned());
+        Ok(index)
+    }
+
+    /// Ends the expansion started by the matching `push`, recording what it produced
+    pub fn pop(&self, index: Option<usize>, output: &[TokenPos]) {
+        self.active.borrow_mut().pop();
+        if let (Some(trace), Some(index)) = (&self.trace, index) {
+            trace.borrow_mut().0[index].output = output.to_vec();
+        }
+    }
+
+    pub fn add_pragmas(&self, pragmas: Vec<Pragma>) {
+        self.pragmas.borrow_mut().extend(pragmas);
+    }
+
+    pub fn force(&self, name: &str) {
+        self.forced.borrow_mut().insert(name.to_owned());
+    }
+
+    pub fn is_forced(&self, name: &str) -> bool {
+        self.forced.borrow().contains(name)
+    }
+
+    pub fn into_parts(self) -> (Pragmas, Option<Trace>) {
+        (
+            Pragmas::new(self.pragmas.into_inner()),
+            self.trace.map(RefCell::into_inner),
+        )
+    }
+}
+
+/// A single macro expansion, macros used by its body are recorded after it with a greater depth
+#[derive(Clone, Debug)]
+pub struct Expansion {
+    pub name: String,
+    /// Where the macro was used
+    pub site: TokenPos,
+    /// How many expansions it is nested in
+    pub depth: usize,
+    /// The tokens it was replaced with
+    pub output: Vec<TokenPos>,
+}
+
+/// Every macro expansion of a file, in the order they were started
+#[derive(Clone, Debug, Default)]
+pub struct Trace(Vec<Expansion>);
+
+impl Trace {
+    pub fn expansions(&self) -> &[Expansion] {
+        &self.0
+    }
+
+    /// One line per expansion, indented by its depth
+    pub fn export(&self) -> String {
+        let mut content = String::new();
+        for expansion in &self.0 {
+            content.push_str(&format!(
+                "{}{} at {}:{}:{} -> {}\n",
+                "  ".repeat(expansion.depth),
+                expansion.name,
+                expansion.site.path(),
+                expansion.site.start().1 .0,
+                expansion.site.start().1 .1,
+                render(expansion.output.clone())
+                    .export()
+                    .trim()
+                    .replace('\n', "\\n")
+            ));
+        }
+        content
+    }
+}
@@ -36,6 +36,21 @@ impl TokenPos {
         }
     }
 
+    /// A token from a line map, which only knows its lines and columns
+    pub fn mapped<S: Into<String>>(
+        token: Token,
+        path: S,
+        start: (usize, usize),
+        end: (usize, usize),
+    ) -> Self {
+        Self {
+            start: (0, start),
+            end: (0, end),
+            path: path.into(),
+            token,
+        }
+    }
+
     pub fn with_pos(token: Token, pos: &Self) -> Self {
         Self {
             start: pos.start(),
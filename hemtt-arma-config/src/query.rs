@@ -0,0 +1,479 @@
+//! Queries that find classes and properties in a config
+//!
+//! A query is a path of names separated by `>>`, like the game's config paths:
+//!
+//! ```text
+//! CfgWeapons >> arifle_MX_F >> magazines[]
+//! CfgVehicles >> *[scope == 2] >> displayName
+//! ** >> CfgPatches >> *
+//! ```
+//!
+//! Names are matched without case and can use `*` and `?` as wildcards, `**` matches any number
+//! of classes. Names can be quoted to use other characters. A name can be followed by predicates
+//! on the properties of the class it matches:
+//!
+//! - `[scope]`, the class has the property
+//! - `[scope == 2]`, also `!=`, `<`, `<=`, `>` and `>=`. Numbers are compared as numbers,
+//!   strings without case. An array is equal to a value when it contains it.
+//!
+//! Only the properties the class sets itself are seen, inheritance is not resolved.
+
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use serde_json::{json, Map, Value};
+
+use crate::simplify::{Array, ArrayElement, Class, Config, Entry};
+use crate::ArmaConfigError;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Name {
+    /// Any number of classes
+    Descendants,
+    Pattern(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Predicate {
+    property: String,
+    comparison: Option<(Operator, String)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Segment {
+    name: Name,
+    predicates: Vec<Predicate>,
+}
+
+/// A parsed query
+#[derive(Clone, Debug, PartialEq)]
+pub struct Query {
+    segments: Vec<Segment>,
+}
+
+/// A class or property found by a query
+#[derive(Clone, Debug, PartialEq)]
+pub struct Match<'a> {
+    path: Vec<&'a str>,
+    entry: &'a Entry,
+}
+
+impl<'a> Match<'a> {
+    /// The names of the classes from the root, ending with the name of the match
+    pub fn path(&self) -> &[&'a str] {
+        &self.path
+    }
+
+    pub fn entry(&self) -> &'a Entry {
+        self.entry
+    }
+
+    /// The match as `{"path": [..], "value": ..}`, classes are `{"parent": .., "entries": {..}}`
+    pub fn to_json(&self) -> Value {
+        json!({
+            "path": self.path,
+            "value": entry_json(self.entry),
+        })
+    }
+}
+
+impl std::fmt::Display for Match<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.path.join(" >> "))?;
+        match self.entry {
+            Entry::Class(class) if !class.parent.is_empty() => write!(f, ": {}", class.parent),
+            Entry::Class(_) | Entry::Invisible(_) => Ok(()),
+            Entry::Array(array) => write!(f, "[] = {}", array_text(array)),
+            Entry::Str(value) => write!(f, " = {}", string_text(value)),
+            Entry::Float(value) => write!(f, " = {}", value),
+            Entry::Int32(value) => write!(f, " = {}", value),
+            Entry::Int64(value) => write!(f, " = {}", value),
+        }
+    }
+}
+
+impl Query {
+    /// Finds the classes and properties that match the query, in the order of the config
+    pub fn run<'a>(&self, config: &'a Config) -> Vec<Match<'a>> {
+        let mut found = Vec::new();
+        search(
+            &config.root.entries,
+            &self.segments,
+            &mut Vec::new(),
+            &mut found,
+        );
+        found
+    }
+}
+
+impl FromStr for Query {
+    type Err = ArmaConfigError;
+
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| {
+            ArmaConfigError::InvalidInput(format!("Invalid query `{}`: {}", query, reason))
+        };
+        let mut segments = Vec::new();
+        for part in split(query).map_err(|e| invalid(&e))? {
+            segments.push(segment(part.trim()).map_err(|e| invalid(&e))?);
+        }
+        Ok(Self { segments })
+    }
+}
+
+/// Splits `source` at `>>` outside of quotes and brackets
+fn split(source: &str) -> Result<Vec<&str>, String> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut depth = 0;
+    for (i, c) in source.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '[' if !quoted => depth += 1,
+            ']' if !quoted => depth -= 1,
+            '>' if !quoted && depth == 0 && i >= start && source[i..].starts_with(">>") => {
+                parts.push(&source[start..i]);
+                start = i + 2;
+            }
+            _ => {}
+        }
+    }
+    if quoted {
+        return Err(String::from("unterminated string"));
+    }
+    if depth != 0 {
+        return Err(String::from("unbalanced `[]`"));
+    }
+    parts.push(&source[start..]);
+    Ok(parts)
+}
+
+/// The position of the `]` that closes a `[` before `source`
+fn closing(source: &str) -> Option<usize> {
+    let mut quoted = false;
+    let mut depth = 0;
+    for (i, c) in source.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '[' if !quoted => depth += 1,
+            ']' if !quoted && depth == 0 => return Some(i),
+            ']' if !quoted => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn segment(source: &str) -> Result<Segment, String> {
+    let (name, mut rest) = if let Some(quoted) = source.strip_prefix('"') {
+        let end = quoted.find('"').ok_or("unterminated string")?;
+        (quoted[..end].to_string(), &quoted[end + 1..])
+    } else {
+        let end = source.find('[').unwrap_or(source.len());
+        (source[..end].trim().to_string(), &source[end..])
+    };
+    if name.is_empty() {
+        return Err(String::from("expected a name"));
+    }
+    if !source.starts_with('"') && name.contains(char::is_whitespace) {
+        return Err(format!(
+            "unexpected space in `{}`, quote names with spaces",
+            name
+        ));
+    }
+    let mut predicates = Vec::new();
+    while let Some(inner) = rest.trim_start().strip_prefix('[') {
+        let end = closing(inner).ok_or("unbalanced `[]`")?;
+        let body = inner[..end].trim();
+        rest = &inner[end + 1..];
+        if !body.is_empty() {
+            predicates.push(predicate(body)?);
+        }
+    }
+    if !rest.trim().is_empty() {
+        return Err(format!("unexpected `{}`", rest.trim()));
+    }
+    Ok(Segment {
+        name: if name == "**" {
+            Name::Descendants
+        } else {
+            Name::Pattern(name)
+        },
+        predicates,
+    })
+}
+
+fn predicate(source: &str) -> Result<Predicate, String> {
+    const OPERATORS: &[(&str, Operator)] = &[
+        ("==", Operator::Equal),
+        ("!=", Operator::NotEqual),
+        ("<=", Operator::LessEqual),
+        (">=", Operator::GreaterEqual),
+        ("<", Operator::Less),
+        (">", Operator::Greater),
+        ("=", Operator::Equal),
+    ];
+    let found = OPERATORS
+        .iter()
+        .filter_map(|(text, operator)| source.find(text).map(|i| (i, *text, *operator)))
+        .min_by_key(|(i, _, _)| *i);
+    let (property, comparison) = match found {
+        Some((i, text, operator)) => {
+            let value = source[i + text.len()..].trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+            (&source[..i], Some((operator, value.to_string())))
+        }
+        None => (source, None),
+    };
+    let property = property.trim().trim_end_matches("[]").trim();
+    if property.is_empty() {
+        return Err(format!("expected a property in `[{}]`", source));
+    }
+    Ok(Predicate {
+        property: property.to_string(),
+        comparison,
+    })
+}
+
+fn search<'a>(
+    entries: &'a [(String, Entry)],
+    segments: &[Segment],
+    path: &mut Vec<&'a str>,
+    found: &mut Vec<Match<'a>>,
+) {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+    if segment.name == Name::Descendants {
+        if rest.is_empty() {
+            for (name, entry) in entries {
+                path.push(name);
+                found.push(Match {
+                    path: path.clone(),
+                    entry,
+                });
+                if let Entry::Class(class) = entry {
+                    search(&class.entries, segments, path, found);
+                }
+                path.pop();
+            }
+            return;
+        }
+        search(entries, rest, path, found);
+        for (name, entry) in entries {
+            if let Entry::Class(class) = entry {
+                path.push(name);
+                search(&class.entries, segments, path, found);
+                path.pop();
+            }
+        }
+        return;
+    }
+    for (name, entry) in entries {
+        if !segment.matches(name, entry) {
+            continue;
+        }
+        path.push(name);
+        if rest.is_empty() {
+            found.push(Match {
+                path: path.clone(),
+                entry,
+            });
+        } else if let Entry::Class(class) = entry {
+            search(&class.entries, rest, path, found);
+        }
+        path.pop();
+    }
+}
+
+impl Segment {
+    fn matches(&self, name: &str, entry: &Entry) -> bool {
+        let pattern = match self.name {
+            Name::Pattern(ref pattern) => pattern,
+            Name::Descendants => return true,
+        };
+        if !wildcard(&pattern.to_lowercase(), &name.to_lowercase()) {
+            return false;
+        }
+        if self.predicates.is_empty() {
+            return true;
+        }
+        match entry {
+            Entry::Class(class) => self
+                .predicates
+                .iter()
+                .all(|predicate| predicate.matches(class)),
+            _ => false,
+        }
+    }
+}
+
+impl Predicate {
+    fn matches(&self, class: &Class) -> bool {
+        let value = class.entries.iter().find_map(|(name, entry)| match entry {
+            Entry::Class(_) => None,
+            _ if name.eq_ignore_ascii_case(&self.property) => Some(entry),
+            _ => None,
+        });
+        match (value, &self.comparison) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(Entry::Array(array)), Some((operator, expected))) => {
+                let contains = array.elements.iter().any(|element| {
+                    compare(&element_entry(element), expected) == Some(Ordering::Equal)
+                });
+                match operator {
+                    Operator::Equal => contains,
+                    Operator::NotEqual => !contains,
+                    _ => false,
+                }
+            }
+            (Some(value), Some((operator, expected))) => match compare(value, expected) {
+                Some(ordering) => match operator {
+                    Operator::Equal => ordering == Ordering::Equal,
+                    Operator::NotEqual => ordering != Ordering::Equal,
+                    Operator::Less => ordering == Ordering::Less,
+                    Operator::LessEqual => ordering != Ordering::Greater,
+                    Operator::Greater => ordering == Ordering::Greater,
+                    Operator::GreaterEqual => ordering != Ordering::Less,
+                },
+                None => *operator == Operator::NotEqual,
+            },
+        }
+    }
+}
+
+/// Compares a value with the text of a predicate, `None` when they can not be compared
+fn compare(value: &Entry, expected: &str) -> Option<Ordering> {
+    let number =
+        |value: f64| -> Option<Ordering> { value.partial_cmp(&expected.parse::<f64>().ok()?) };
+    match value {
+        Entry::Float(value) => number(f64::from(*value)),
+        Entry::Int32(value) => number(f64::from(*value)),
+        Entry::Int64(value) => number(*value as f64),
+        Entry::Str(value) => Some(value.to_lowercase().cmp(&expected.to_lowercase())),
+        _ => None,
+    }
+}
+
+fn element_entry(element: &ArrayElement) -> Entry {
+    match element {
+        ArrayElement::Str(value) => Entry::Str(value.clone()),
+        ArrayElement::Float(value) => Entry::Float(*value),
+        ArrayElement::Int32(value) => Entry::Int32(*value),
+        ArrayElement::Int64(value) => Entry::Int64(*value),
+        ArrayElement::Array(value) => Entry::Array(value.clone()),
+    }
+}
+
+/// Matches `name` against a pattern with `*` and `?`
+fn wildcard(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((sp, sn)) = star {
+            p = sp + 1;
+            n = sn + 1;
+            star = Some((sp, sn + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+pub(crate) fn string_text(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+pub(crate) fn element_text(element: &ArrayElement) -> String {
+    match element {
+        ArrayElement::Str(value) => string_text(value),
+        ArrayElement::Float(value) => value.to_string(),
+        ArrayElement::Int32(value) => value.to_string(),
+        ArrayElement::Int64(value) => value.to_string(),
+        ArrayElement::Array(array) => array_text(array),
+    }
+}
+
+pub(crate) fn array_text(array: &Array) -> String {
+    format!(
+        "{{{}}}",
+        array
+            .elements
+            .iter()
+            .map(element_text)
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+fn float_json(value: f32) -> Value {
+    // Through the shortest text of the `f32`, so `0.1` does not become `0.10000000149011612`
+    value
+        .to_string()
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+        .map_or(Value::Null, Value::Number)
+}
+
+pub(crate) fn element_json(element: &ArrayElement) -> Value {
+    match element {
+        ArrayElement::Str(value) => json!(value),
+        ArrayElement::Float(value) => float_json(*value),
+        ArrayElement::Int32(value) => json!(value),
+        ArrayElement::Int64(value) => json!(value),
+        ArrayElement::Array(array) => array_json(array),
+    }
+}
+
+fn array_json(array: &Array) -> Value {
+    Value::Array(array.elements.iter().map(element_json).collect())
+}
+
+pub(crate) fn entry_json(entry: &Entry) -> Value {
+    match entry {
+        Entry::Str(value) => json!(value),
+        Entry::Float(value) => float_json(*value),
+        Entry::Int32(value) => json!(value),
+        Entry::Int64(value) => json!(value),
+        Entry::Array(array) => array_json(array),
+        Entry::Class(class) => json!({
+            "parent": if class.parent.is_empty() { Value::Null } else { json!(class.parent) },
+            "entries": class
+                .entries
+                .iter()
+                .map(|(name, entry)| (name.clone(), entry_json(entry)))
+                .collect::<Map<_, _>>(),
+        }),
+        Entry::Invisible(entries) => entries
+            .iter()
+            .map(|(name, entry)| (name.clone(), entry_json(entry)))
+            .collect::<Map<_, _>>()
+            .into(),
+    }
+}
@@ -7,6 +7,9 @@ use super::simplify::{Array, ArrayElement, Class, Config, Entry};
 use crate::ArmaConfigError;
 use hemtt_io::*;
 
+/// Classes nested deeper than this are taken as class offsets that point back at a parent
+pub(crate) const MAX_DEPTH: u32 = 128;
+
 impl ArrayElement {
     pub fn rapified_length(&self) -> usize {
         match self {
@@ -63,7 +66,7 @@ impl Array {
         let mut elements: Vec<ArrayElement> = Vec::with_capacity(num_elements as usize);
 
         for _i in 0..num_elements {
-            let element_type: u8 = input.bytes().next().unwrap()?;
+            let element_type: u8 = input.read_u8()?;
 
             if element_type == 0 {
                 elements.push(ArrayElement::Str(input.read_cstring()?));
@@ -85,6 +88,7 @@ impl Array {
 
         Ok(Array {
             expand: false,
+            shrink: false,
             elements,
         })
     }
@@ -102,7 +106,7 @@ impl Entry {
                 let len = 1
                     + compressed_int_len(a.elements.len() as u32)
                     + usize::sum(a.elements.iter().map(|e| e.rapified_length()));
-                if a.expand {
+                if a.expand || a.shrink {
                     len + 4
                 } else {
                     len
@@ -122,22 +126,18 @@ impl Entry {
 
 impl Class {
     pub fn rapified_length(&self) -> usize {
-        if !self.entries.is_empty() {
-            self.parent.len()
-                + 1
-                + compressed_int_len(self.entries.len() as u32)
-                + usize::sum(self.entries.iter().map(|(k, v)| {
-                    k.len()
-                        + 1
-                        + v.rapified_length()
-                        + match v {
-                            Entry::Class(c) => c.rapified_length(),
-                            _ => 0,
-                        }
-                }))
-        } else {
-            0
-        }
+        self.parent.len()
+            + 1
+            + compressed_int_len(self.entries.len() as u32)
+            + usize::sum(self.entries.iter().map(|(k, v)| {
+                k.len()
+                    + 1
+                    + v.rapified_length()
+                    + match v {
+                        Entry::Class(c) if !c.external && !c.deletion => c.rapified_length(),
+                        _ => 0,
+                    }
+            }))
     }
 
     pub fn write_rapified<O: Write>(
@@ -145,90 +145,88 @@ impl Class {
         output: &mut O,
         offset: usize,
     ) -> Result<usize, ArmaConfigError> {
-        let mut written = 0;
-
-        if !self.entries.is_empty() {
-            output.write_cstring(&self.parent)?;
-            written += self.parent.len() + 1;
-
-            written += output.write_compressed_int(self.entries.len() as u32)?;
-
-            let entries_len = usize::sum(
-                self.entries
-                    .iter()
-                    .map(|(k, v)| k.len() + 1 + v.rapified_length()),
-            );
-            let mut class_offset = offset + written + entries_len;
-            let mut class_bodies: Vec<Cursor<Box<[u8]>>> = Vec::new();
-            let pre_entries = written;
-
-            for (name, entry) in &self.entries {
-                let pre_write = written;
-                match entry {
-                    Entry::Str(s) => {
-                        output.write_all(&[1, 0])?;
-                        output.write_cstring(name)?;
-                        output.write_cstring(s)?;
-                        written += name.len() + s.len() + 4;
-                    }
-                    Entry::Float(f) => {
-                        output.write_all(&[1, 1])?;
-                        output.write_cstring(name)?;
-                        output.write_f32::<LittleEndian>(*f)?;
-                        written += name.len() + 7;
-                    }
-                    Entry::Int32(i) => {
-                        output.write_all(&[1, 2])?;
-                        output.write_cstring(name)?;
-                        output.write_i32::<LittleEndian>(*i)?;
-                        written += name.len() + 7;
+        output.write_cstring(&self.parent)?;
+        let mut written = self.parent.len() + 1;
+
+        written += output.write_compressed_int(self.entries.len() as u32)?;
+
+        let entries_len = usize::sum(
+            self.entries
+                .iter()
+                .map(|(k, v)| k.len() + 1 + v.rapified_length()),
+        );
+        let mut class_offset = offset + written + entries_len;
+        let mut class_bodies: Vec<Cursor<Box<[u8]>>> = Vec::new();
+        let pre_entries = written;
+
+        for (name, entry) in &self.entries {
+            let pre_write = written;
+            match entry {
+                Entry::Str(s) => {
+                    output.write_all(&[1, 0])?;
+                    output.write_cstring(name)?;
+                    output.write_cstring(s)?;
+                    written += name.len() + s.len() + 4;
+                }
+                Entry::Float(f) => {
+                    output.write_all(&[1, 1])?;
+                    output.write_cstring(name)?;
+                    output.write_f32::<LittleEndian>(*f)?;
+                    written += name.len() + 7;
+                }
+                Entry::Int32(i) => {
+                    output.write_all(&[1, 2])?;
+                    output.write_cstring(name)?;
+                    output.write_i32::<LittleEndian>(*i)?;
+                    written += name.len() + 7;
+                }
+                Entry::Array(a) => {
+                    if a.expand || a.shrink {
+                        output.write_all(&[5])?;
+                        output.write_u32::<LittleEndian>(if a.shrink { 2 } else { 1 })?;
+                        written += 4;
+                    } else {
+                        output.write_all(&[2])?;
                     }
-                    Entry::Array(a) => {
-                        output.write_all(if a.expand { &[5] } else { &[2] })?;
-                        if a.expand {
-                            output.write_all(&[1, 0, 0, 0])?;
-                            written += 4;
-                        }
+                    output.write_cstring(name)?;
+                    written += name.len() + 2 + a.write_rapified(output)?;
+                }
+                Entry::Class(c) => {
+                    if c.external || c.deletion {
+                        output.write_all(if c.deletion { &[4] } else { &[3] })?;
                         output.write_cstring(name)?;
-                        written += name.len() + 2 + a.write_rapified(output)?;
-                    }
-                    Entry::Class(c) => {
-                        if c.external || c.deletion {
-                            output.write_all(if c.deletion { &[4] } else { &[3] })?;
-                            output.write_cstring(name)?;
-                            written += name.len() + 2;
-                        } else {
-                            output.write_all(&[0])?;
-                            output.write_cstring(name)?;
-                            output.write_u32::<LittleEndian>(class_offset as u32)?;
-                            written += name.len() + 6;
-
-                            let buffer: Box<[u8]> = vec![0; c.rapified_length()].into_boxed_slice();
-                            let mut cursor: Cursor<Box<[u8]>> = Cursor::new(buffer);
-                            class_offset += c.write_rapified(&mut cursor, class_offset)?;
-                            class_bodies.push(cursor);
-                        }
-                    }
-                    Entry::Int64(i) => {
-                        output.write_all(&[1, 6])?;
+                        written += name.len() + 2;
+                    } else {
+                        output.write_all(&[0])?;
                         output.write_cstring(name)?;
-                        output.write_i64::<LittleEndian>(*i)?;
-                        written += name.len() + 7;
+                        output.write_u32::<LittleEndian>(class_offset as u32)?;
+                        written += name.len() + 6;
+
+                        let buffer: Box<[u8]> = vec![0; c.rapified_length()].into_boxed_slice();
+                        let mut cursor: Cursor<Box<[u8]>> = Cursor::new(buffer);
+                        class_offset += c.write_rapified(&mut cursor, class_offset)?;
+                        class_bodies.push(cursor);
                     }
-                    Entry::Invisible(_) => {}
                 }
-                assert_eq!(
-                    written - pre_write,
-                    entry.rapified_length() + name.len() + 1
-                );
+                Entry::Int64(i) => {
+                    output.write_all(&[1, 6])?;
+                    output.write_cstring(name)?;
+                    output.write_i64::<LittleEndian>(*i)?;
+                    written += name.len() + 11;
+                }
+                Entry::Invisible(_) => {}
             }
+            assert_eq!(
+                written - pre_write,
+                entry.rapified_length() + name.len() + 1
+            );
+        }
 
-            assert_eq!(written - pre_entries, entries_len);
+        assert_eq!(written - pre_entries, entries_len);
 
-            for cursor in class_bodies {
-                output.write_all(cursor.get_ref())?;
-                written += cursor.get_ref().len();
-            }
+        for cursor in class_bodies {
+            output.write_all(cursor.get_ref())?;
+            written += cursor.get_ref().len();
         }
 
         Ok(written)
@@ -238,6 +236,11 @@ impl Class {
         input: &mut I,
         level: u32,
     ) -> Result<Class, ArmaConfigError> {
+        if level > MAX_DEPTH {
+            return Err(ArmaConfigError::InvalidInput(String::from(
+                "Classes are nested too deep, the class offsets are likely wrong.",
+            )));
+        }
         let mut fp = 0;
         if level == 0 {
             input.seek(SeekFrom::Start(16))?;
@@ -253,7 +256,7 @@ impl Class {
         let mut entries: Vec<(String, Entry)> = Vec::with_capacity(num_entries as usize);
 
         for _i in 0..num_entries {
-            let entry_type: u8 = input.bytes().next().unwrap()?;
+            let entry_type: u8 = input.read_u8()?;
 
             if entry_type == 0 {
                 let name = input.read_cstring()?;
@@ -261,7 +264,7 @@ impl Class {
                 let class_entry = Class::read_rapified(input, level + 1)?;
                 entries.push((name, Entry::Class(class_entry)));
             } else if entry_type == 1 {
-                let subtype: u8 = input.bytes().next().unwrap()?;
+                let subtype: u8 = input.read_u8()?;
                 let name = input.read_cstring()?;
 
                 if subtype == 0 {
@@ -279,13 +282,16 @@ impl Class {
                     )));
                 }
             } else if entry_type == 2 || entry_type == 5 {
-                if entry_type == 5 {
-                    input.seek(SeekFrom::Current(4))?;
-                }
+                let flag = if entry_type == 5 {
+                    input.read_u32::<LittleEndian>()?
+                } else {
+                    0
+                };
 
                 let name = input.read_cstring()?;
                 let mut array = Array::read_rapified(input)?;
-                array.expand = entry_type == 5;
+                array.expand = flag == 1;
+                array.shrink = flag == 2;
 
                 entries.push((name.clone(), Entry::Array(array)));
             } else if entry_type == 3 || entry_type == 4 {
@@ -293,7 +299,7 @@ impl Class {
                 let class_entry = Class {
                     parent: String::from(""),
                     external: entry_type == 3,
-                    deletion: entry_type == 5,
+                    deletion: entry_type == 4,
                     entries: Vec::new(),
                 };
 
@@ -336,14 +342,20 @@ impl Config {
 
         writer.write_all(cursor.get_ref())?;
 
-        writer.write_all(b"\0\0\0\0")?;
+        writer.write_u32::<LittleEndian>(self.enums.len() as u32)?;
+        for (name, value) in &self.enums {
+            writer.write_cstring(name)?;
+            writer.write_i32::<LittleEndian>(*value)?;
+        }
 
         Ok(())
     }
 
     /// Returns the rapified config as a `Cursor`.
     pub fn to_cursor(&self) -> Result<Cursor<Box<[u8]>>, ArmaConfigError> {
-        let len = self.root.rapified_length() + 20;
+        let len = self.root.rapified_length()
+            + 20
+            + usize::sum(self.enums.iter().map(|(name, _)| name.len() + 5));
 
         let buffer: Box<[u8]> = vec![0; len].into_boxed_slice();
         let mut cursor: Cursor<Box<[u8]>> = Cursor::new(buffer);
@@ -365,8 +377,20 @@ impl Config {
             ));
         }
 
-        Ok(Config {
-            root: Class::read_rapified(&mut reader, 0)?,
-        })
+        reader.seek(SeekFrom::Start(12))?;
+        let enum_offset = reader.read_u32::<LittleEndian>()?;
+        let root = Class::read_rapified(&mut reader, 0)?;
+
+        reader.seek(SeekFrom::Start(enum_offset.into()))?;
+        let mut enums = Vec::new();
+        // Some tools end the file before the enums section instead of writing an empty one
+        if let Ok(count) = reader.read_u32::<LittleEndian>() {
+            for _ in 0..count {
+                let name = reader.read_cstring()?;
+                enums.push((name, reader.read_i32::<LittleEndian>()?));
+            }
+        }
+
+        Ok(Config { root, enums })
     }
 }
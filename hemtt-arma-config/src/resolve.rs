@@ -0,0 +1,420 @@
+//! Configs of several addons merged the way the game loads them, with inheritance resolved
+
+use std::collections::HashMap;
+
+use crate::simplify::{Array, Class, Config, Entry};
+use crate::ArmaConfigError;
+
+/// Inheritance deeper than this is treated as a cycle
+const MAX_DEPTH: usize = 64;
+
+/// Where a part of a merged config was loaded from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Origin {
+    addon: String,
+    path: String,
+}
+
+impl Origin {
+    pub fn new(addon: String, path: String) -> Self {
+        Self { addon, path }
+    }
+
+    pub fn addon(&self) -> &str {
+        &self.addon
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+/// The effective value of a property
+#[derive(Clone, Debug, PartialEq)]
+pub struct Property {
+    value: Entry,
+    origin: Origin,
+    class: Vec<String>,
+}
+
+impl Property {
+    /// The value with `+=` and `-=` applied to the inherited array
+    pub fn value(&self) -> &Entry {
+        &self.value
+    }
+
+    /// The config that last set the property
+    pub fn origin(&self) -> &Origin {
+        &self.origin
+    }
+
+    /// The path of the class that sets the property, the class itself or one it inherits from
+    pub fn class(&self) -> &[String] {
+        &self.class
+    }
+}
+
+#[derive(Clone, Debug)]
+struct MergedClass {
+    parent: String,
+    /// Only declared with `class Name;`
+    external: bool,
+    origin: Origin,
+    entries: Vec<(String, Item)>,
+}
+
+#[derive(Clone, Debug)]
+enum Item {
+    Value(Entry, Origin),
+    Class(MergedClass),
+}
+
+impl MergedClass {
+    fn new(origin: &Origin) -> Self {
+        Self {
+            parent: String::new(),
+            external: true,
+            origin: origin.clone(),
+            entries: Vec::new(),
+        }
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|(n, _)| n.eq_ignore_ascii_case(name))
+    }
+
+    fn class(&self, name: &str) -> Option<(&str, &Self)> {
+        self.entries.iter().find_map(|(n, item)| match item {
+            Item::Class(class) if n.eq_ignore_ascii_case(name) => Some((n.as_str(), class)),
+            _ => None,
+        })
+    }
+
+    /// Merges the entries of `class` into the class, like a later config changing it
+    fn merge(&mut self, entries: &[(String, Entry)], origin: &Origin) {
+        for (name, entry) in entries {
+            let position = self.position(name);
+            match entry {
+                Entry::Invisible(entries) => self.merge(entries, origin),
+                Entry::Class(class) if class.deletion => {
+                    if let Some(position) = position {
+                        self.entries.remove(position);
+                    }
+                }
+                Entry::Class(class) => {
+                    if let Some(Item::Class(existing)) =
+                        position.map(|position| &mut self.entries[position].1)
+                    {
+                        existing.define(class, origin);
+                        continue;
+                    }
+                    let mut merged = Self::new(origin);
+                    merged.define(class, origin);
+                    self.set(position, name, Item::Class(merged));
+                }
+                Entry::Array(array) if array.expand || array.shrink => {
+                    if let Some(Item::Value(Entry::Array(existing), existing_origin)) =
+                        position.map(|position| &mut self.entries[position].1)
+                    {
+                        *existing = combine(existing, array);
+                        *existing_origin = origin.clone();
+                        continue;
+                    }
+                    self.set(position, name, Item::Value(entry.clone(), origin.clone()));
+                }
+                _ => self.set(position, name, Item::Value(entry.clone(), origin.clone())),
+            }
+        }
+    }
+
+    /// Applies a definition of the class, `class Name;` only declares it
+    fn define(&mut self, class: &Class, origin: &Origin) {
+        if class.external {
+            return;
+        }
+        if self.external {
+            self.origin = origin.clone();
+            self.external = false;
+        }
+        self.parent = class.parent.clone();
+        self.merge(&class.entries, origin);
+    }
+
+    fn set(&mut self, position: Option<usize>, name: &str, item: Item) {
+        match position {
+            Some(position) => self.entries[position] = (name.to_string(), item),
+            None => self.entries.push((name.to_string(), item)),
+        }
+    }
+}
+
+/// Applies `+=` or `-=` of `change` to `base`, the result keeps the flags of `base`
+fn combine(base: &Array, change: &Array) -> Array {
+    let mut elements = base.elements.clone();
+    if change.shrink {
+        elements.retain(|element| !change.elements.contains(element));
+    } else {
+        elements.extend(change.elements.iter().cloned());
+    }
+    Array {
+        elements,
+        ..base.clone()
+    }
+}
+
+/// Configs merged in load order, queried through inheritance
+///
+/// Classes and properties are looked up without case, like the game does. Paths are the names of
+/// the classes from the root, a class that is inherited is found through the class inheriting it.
+#[derive(Clone, Debug)]
+pub struct Resolved {
+    root: MergedClass,
+}
+
+impl Resolved {
+    pub fn new() -> Self {
+        Self {
+            root: MergedClass {
+                external: false,
+                ..MergedClass::new(&Origin::new(String::new(), String::new()))
+            },
+        }
+    }
+
+    /// Merges configs in the order the game loads them
+    ///
+    /// A config is loaded after the configs with the patches in its `requiredAddons`, otherwise
+    /// configs keep their order. Requirements that none of the configs have are ignored.
+    pub fn load(configs: Vec<(Origin, Config)>) -> Result<Self, ArmaConfigError> {
+        let mut resolved = Self::new();
+        for (origin, config) in load_order(configs)? {
+            resolved.merge(&config, &origin);
+        }
+        Ok(resolved)
+    }
+
+    /// Merges a config over the ones already merged
+    pub fn merge(&mut self, config: &Config, origin: &Origin) {
+        self.root.merge(&config.root.entries, origin);
+    }
+
+    /// The effective value of a property, the last element of `path` is its name
+    pub fn get(&self, path: &[&str]) -> Option<Property> {
+        let (name, class) = path.split_last()?;
+        self.property(&self.locate(class)?, name, 0)
+    }
+
+    /// Whether there is a class at `path`, it may only be declared with `class Name;`
+    pub fn has_class(&self, path: &[&str]) -> bool {
+        self.locate(path).is_some()
+    }
+
+    /// The config that first defined the class at `path`
+    pub fn class_origin(&self, path: &[&str]) -> Option<&Origin> {
+        Some(&self.class_at(&self.locate(path)?)?.origin)
+    }
+
+    /// The paths of the class at `path` and every class it inherits from, in order
+    pub fn inheritance(&self, path: &[&str]) -> Option<Vec<Vec<String>>> {
+        let mut chain = vec![self.locate(path)?];
+        while let Some(parent) = self.parent(chain.last().unwrap(), 0) {
+            if chain.contains(&parent) || chain.len() > MAX_DEPTH {
+                break;
+            }
+            chain.push(parent);
+        }
+        Some(chain)
+    }
+
+    /// Every property of the class at `path` with its effective value, inherited ones included
+    ///
+    /// Properties are in the order they were first set, starting from the class furthest up the
+    /// inheritance chain.
+    pub fn flatten(&self, path: &[&str]) -> Option<Vec<(String, Property)>> {
+        let mut names: Vec<String> = Vec::new();
+        for class in self.inheritance(path)?.iter().rev() {
+            for (name, item) in &self.class_at(class)?.entries {
+                if let Item::Value(..) = item {
+                    if !names.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+                        names.push(name.clone());
+                    }
+                }
+            }
+        }
+        let location = self.locate(path)?;
+        Some(
+            names
+                .into_iter()
+                .filter_map(|name| {
+                    let property = self.property(&location, &name, 0)?;
+                    Some((name, property))
+                })
+                .collect(),
+        )
+    }
+
+    /// Every class with a parent that can not be found, with the name of the parent
+    ///
+    /// A parent is found when it is declared, `class Name;` is enough.
+    pub fn undefined_parents(&self) -> Vec<(Vec<String>, String)> {
+        let mut undefined = Vec::new();
+        walk(&self.root, &mut Vec::new(), &mut |location, class| {
+            if !class.parent.is_empty() && self.parent(location, 0).is_none() {
+                undefined.push((location.to_vec(), class.parent.clone()));
+            }
+        });
+        undefined
+    }
+
+    /// The class defined at `location`, without following inheritance
+    fn class_at(&self, location: &[String]) -> Option<&MergedClass> {
+        location
+            .iter()
+            .try_fold(&self.root, |class, name| Some(class.class(name)?.1))
+    }
+
+    /// Where the class at `path` is defined, following inheritance for each step
+    fn locate(&self, path: &[&str]) -> Option<Vec<String>> {
+        path.iter()
+            .try_fold(Vec::new(), |location, name| self.member(&location, name, 0))
+    }
+
+    /// Where the class `name` of the class at `scope` is defined, it may be inherited
+    fn member(&self, scope: &[String], name: &str, depth: usize) -> Option<Vec<String>> {
+        let class = self.class_at(scope)?;
+        if let Some((real, _)) = class.class(name) {
+            let mut location = scope.to_vec();
+            location.push(real.to_string());
+            return Some(location);
+        }
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        self.member(&self.parent(scope, depth + 1)?, name, depth + 1)
+    }
+
+    /// Where the parent of the class at `location` is defined
+    ///
+    /// The parent is searched in the class containing it and then in the classes containing that.
+    fn parent(&self, location: &[String], depth: usize) -> Option<Vec<String>> {
+        let (_, mut scope) = location.split_last()?;
+        let parent = &self.class_at(location)?.parent;
+        if parent.is_empty() || depth > MAX_DEPTH {
+            return None;
+        }
+        loop {
+            if let Some(found) = self.member(scope, parent, depth + 1) {
+                if found != location {
+                    return Some(found);
+                }
+            }
+            scope = scope.split_last()?.1;
+        }
+    }
+
+    fn property(&self, location: &[String], name: &str, depth: usize) -> Option<Property> {
+        let class = self.class_at(location)?;
+        match class
+            .position(name)
+            .map(|position| &class.entries[position].1)
+        {
+            Some(Item::Value(value, origin)) => {
+                let value = match value {
+                    Entry::Array(array) if array.expand || array.shrink => {
+                        let inherited = self
+                            .parent(location, depth + 1)
+                            .and_then(|parent| self.property(&parent, name, depth + 1));
+                        let base = match inherited.as_ref().map(Property::value) {
+                            Some(Entry::Array(base)) => base.clone(),
+                            _ => Array {
+                                expand: false,
+                                shrink: false,
+                                elements: Vec::new(),
+                            },
+                        };
+                        Entry::Array(combine(&base, array))
+                    }
+                    value => value.clone(),
+                };
+                Some(Property {
+                    value,
+                    origin: origin.clone(),
+                    class: location.to_vec(),
+                })
+            }
+            Some(Item::Class(_)) => None,
+            None if depth > MAX_DEPTH => None,
+            None => self.property(&self.parent(location, depth + 1)?, name, depth + 1),
+        }
+    }
+}
+
+/// Calls `f` with the location of every class in `class`
+fn walk(
+    class: &MergedClass,
+    location: &mut Vec<String>,
+    f: &mut dyn FnMut(&[String], &MergedClass),
+) {
+    for (name, item) in &class.entries {
+        if let Item::Class(child) = item {
+            location.push(name.clone());
+            f(location, child);
+            walk(child, location, f);
+            location.pop();
+        }
+    }
+}
+
+impl Default for Resolved {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sorts configs so each comes after the configs with the patches it requires
+fn load_order(configs: Vec<(Origin, Config)>) -> Result<Vec<(Origin, Config)>, ArmaConfigError> {
+    let patches: Vec<_> = configs
+        .iter()
+        .map(|(_, config)| config.patches().unwrap_or_default())
+        .collect();
+    let mut provided = HashMap::new();
+    for (index, patches) in patches.iter().enumerate() {
+        for patch in patches {
+            provided.insert(patch.name().to_lowercase(), index);
+        }
+    }
+    let requires: Vec<Vec<usize>> = patches
+        .iter()
+        .enumerate()
+        .map(|(index, patches)| {
+            patches
+                .iter()
+                .flat_map(|patch| patch.required())
+                .filter_map(|required| provided.get(&required.to_lowercase()).copied())
+                .filter(|required| *required != index)
+                .collect()
+        })
+        .collect();
+    let mut configs: Vec<Option<(Origin, Config)>> = configs.into_iter().map(Some).collect();
+    let mut loaded = vec![false; configs.len()];
+    let mut order = Vec::new();
+    while order.len() < configs.len() {
+        let next = (0..configs.len())
+            .find(|index| !loaded[*index] && requires[*index].iter().all(|r| loaded[*r]))
+            .ok_or_else(|| {
+                ArmaConfigError::InvalidInput(format!(
+                    "`requiredAddons` form a cycle between {}",
+                    (0..configs.len())
+                        .filter(|index| !loaded[*index])
+                        .map(|index| format!("`{}`", configs[index].as_ref().unwrap().0.path()))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            })?;
+        loaded[next] = true;
+        order.push(configs[next].take().unwrap());
+    }
+    Ok(order)
+}
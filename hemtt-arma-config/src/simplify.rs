@@ -1,12 +1,15 @@
 use super::parser::{Node, Statement, AST};
 use crate::ArmaConfigError;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Config {
     pub root: Class,
+    /// The constants of the enums section, the parser resolves enums in source configs so
+    /// only rapified configs have them
+    pub enums: Vec<(String, i32)>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Class {
     pub parent: String,
     pub external: bool,
@@ -14,7 +17,7 @@ pub struct Class {
     pub entries: Vec<(String, Entry)>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Entry {
     Str(String),
     Float(f32),
@@ -37,13 +40,16 @@ impl From<Entry> for ArrayElement {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Array {
+    /// Added to the inherited array with `+=`
     pub expand: bool,
+    /// Removed from the inherited array with `-=`
+    pub shrink: bool,
     pub elements: Vec<ArrayElement>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ArrayElement {
     Str(String),
     Float(f32),
@@ -62,6 +68,7 @@ impl Config {
                     deletion: false,
                     entries: get_entries(inner)?,
                 },
+                enums: Vec::new(),
             })
         } else {
             Err(ArmaConfigError::NotRoot)
@@ -144,16 +151,21 @@ pub fn get_entry(node: Node) -> Result<Option<(String, Entry)>, ArmaConfigError>
             ident,
             value,
             expand,
+            shrink,
         } => Some((
             match ident.statement {
                 Statement::Ident(i) => i,
                 Statement::IdentArray(i) => i,
                 _ => panic!("{:?}", ident.statement),
             },
-            get_value(value.statement, expand)?,
+            match get_value(value.statement, expand)? {
+                Entry::Array(array) => Entry::Array(Array { shrink, ..array }),
+                value => value,
+            },
         )),
         Statement::Config(inner) => Some((String::new(), Entry::Invisible(get_entries(inner)?))),
         // Ignore
+        Statement::Gone => None,
         _ => {
             panic!("Not ready for {:#?}", node);
         }
@@ -171,8 +183,11 @@ pub fn get_value(statement: Statement, expand: bool) -> Result<Entry, ArmaConfig
         }
         Statement::Float(val) => Entry::Float(val),
         Statement::Str(val) => Entry::Str(val),
+        // The binarizer keeps `true` and `false` as strings, the game reads them as numbers
+        Statement::Bool(val) => Entry::Str(val.to_string()),
         Statement::Array(val) => Entry::Array(Array {
             expand,
+            shrink: false,
             elements: get_array(val)?,
         }),
         _ => {
@@ -0,0 +1,188 @@
+//! Checks rapified configs against what they were rapified from
+//!
+//! The offsets of a rapified config are calculated before it is written, a wrong length
+//! shifts everything after it. Reading the output back and comparing it to the source finds
+//! those mistakes, comparing it to a `config.bin` of another tool finds where the two differ.
+
+use std::io::{Cursor, Seek, SeekFrom};
+use std::ops::Range;
+
+use byteorder::{LittleEndian, ReadBytesExt};
+use hemtt_io::*;
+
+use crate::rapify::MAX_DEPTH;
+use crate::simplify::{Array, Class, Config, Entry};
+use crate::ArmaConfigError;
+
+/// The first difference between two configs
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mismatch {
+    /// An entry that is different or only in one of the configs, or a class with a different
+    /// parent or kind
+    Entry(Vec<String>),
+    /// The enums section is different
+    Enums,
+    /// The first byte that is different, with the class whose body it is in, `None` when it is
+    /// in the header or the enums section
+    Byte {
+        offset: u64,
+        class: Option<Vec<String>>,
+    },
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Entry(path) if path.is_empty() => write!(f, "the root class is different"),
+            Self::Entry(path) => write!(f, "`{}` is different", path.join(" >> ")),
+            Self::Enums => write!(f, "the enums are different"),
+            Self::Byte { offset, class } => {
+                write!(f, "the bytes differ at offset {:#x}", offset)?;
+                match class {
+                    Some(class) if class.is_empty() => write!(f, " in the root class"),
+                    Some(class) => write!(f, " in the body of `{}`", class.join(" >> ")),
+                    None => write!(f, " outside of the class bodies"),
+                }
+            }
+        }
+    }
+}
+
+/// Compares a config to the config read back from its rapified output
+pub fn compare(expected: &Config, found: &Config) -> Option<Mismatch> {
+    if let Some(path) = compare_class(&expected.root, &found.root, &mut Vec::new()) {
+        return Some(Mismatch::Entry(path));
+    }
+    if expected.enums != found.enums {
+        return Some(Mismatch::Enums);
+    }
+    None
+}
+
+fn compare_class(expected: &Class, found: &Class, path: &mut Vec<String>) -> Option<Vec<String>> {
+    if expected.parent != found.parent
+        || expected.external != found.external
+        || expected.deletion != found.deletion
+    {
+        return Some(path.clone());
+    }
+    for i in 0..expected.entries.len().max(found.entries.len()) {
+        match (expected.entries.get(i), found.entries.get(i)) {
+            (Some((name, Entry::Class(expected))), Some((other, Entry::Class(found))))
+                if name == other =>
+            {
+                path.push(name.clone());
+                if let Some(path) = compare_class(expected, found, path) {
+                    return Some(path);
+                }
+                path.pop();
+            }
+            (Some((name, expected)), Some((other, found)))
+                if name == other && expected == found => {}
+            (Some((name, _)), _) | (None, Some((name, _))) => {
+                let mut path = path.clone();
+                path.push(name.clone());
+                return Some(path);
+            }
+            (None, None) => unreachable!(),
+        }
+    }
+    None
+}
+
+/// Compares a rapified config to a reference, byte for byte
+///
+/// The class is found in the layout of the reference, or in the layout of `found` when the
+/// reference can not be read.
+pub fn compare_bytes(expected: &[u8], found: &[u8]) -> Option<Mismatch> {
+    let offset = expected
+        .iter()
+        .zip(found)
+        .position(|(e, f)| e != f)
+        .or_else(|| {
+            if expected.len() == found.len() {
+                None
+            } else {
+                Some(expected.len().min(found.len()))
+            }
+        })? as u64;
+    let class = layout(expected)
+        .or_else(|_| layout(found))
+        .ok()
+        .and_then(|layout| {
+            layout
+                .into_iter()
+                .find(|(_, range)| range.contains(&offset))
+                .map(|(path, _)| path)
+        });
+    Some(Mismatch::Byte { offset, class })
+}
+
+/// A class and the range of its body, without the bodies of the classes inside it
+type Body = (Vec<String>, Range<u64>);
+
+/// The class bodies of a rapified config
+fn layout(data: &[u8]) -> Result<Vec<Body>, ArmaConfigError> {
+    let mut input = Cursor::new(data);
+    let mut classes = Vec::new();
+    let mut queue: Vec<(Vec<String>, u64)> = vec![(Vec::new(), 16)];
+    while let Some((path, start)) = queue.pop() {
+        if path.len() > MAX_DEPTH as usize || start >= data.len() as u64 {
+            return Err(ArmaConfigError::InvalidInput(format!(
+                "Invalid offset {:#x} for the body of `{}`",
+                start,
+                path.join(" >> ")
+            )));
+        }
+        input.seek(SeekFrom::Start(start))?;
+        input.read_cstring()?;
+        for _ in 0..input.read_compressed_int()? {
+            match input.read_u8()? {
+                0 => {
+                    let mut child = path.clone();
+                    child.push(input.read_cstring()?);
+                    queue.push((child, input.read_u32::<LittleEndian>()?.into()));
+                }
+                1 => {
+                    let subtype = input.read_u8()?;
+                    input.read_cstring()?;
+                    match subtype {
+                        0 => {
+                            input.read_cstring()?;
+                        }
+                        1 | 2 => {
+                            input.read_u32::<LittleEndian>()?;
+                        }
+                        6 => {
+                            input.read_u64::<LittleEndian>()?;
+                        }
+                        _ => {
+                            return Err(ArmaConfigError::InvalidInput(format!(
+                                "Unrecognized variable entry subtype: {}.",
+                                subtype
+                            )))
+                        }
+                    }
+                }
+                kind @ 2 | kind @ 5 => {
+                    if kind == 5 {
+                        input.read_u32::<LittleEndian>()?;
+                    }
+                    input.read_cstring()?;
+                    Array::read_rapified(&mut input)?;
+                }
+                3 | 4 => {
+                    input.read_cstring()?;
+                }
+                kind => {
+                    return Err(ArmaConfigError::InvalidInput(format!(
+                        "Unrecognized class entry type: {}.",
+                        kind
+                    )))
+                }
+            }
+        }
+        classes.push((path, start..input.position()));
+    }
+    Ok(classes)
+}
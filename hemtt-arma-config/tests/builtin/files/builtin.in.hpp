@@ -0,0 +1,23 @@
+#define LOG(msg) diag_log [__FILE__, __LINE__, msg]
+line = __LINE__;
+LOG("a");
+
+LOG(__LINE__);
+c0 = __COUNTER__;
+c1 = __COUNTER__;
+__COUNTER_RESET__
+c2 = __COUNTER__;
+date[] = {__DATE_ARR__};
+time = "__TIME__";
+str = __DATE_STR__;
+#if __has_include("tests/builtin/files/builtin.out.hpp")
+has = 1;
+#else
+has = 0;
+#endif
+#if __has_include("tests/builtin/files/missing.hpp")
+missing = 1;
+#endif
+#ifdef __LINE__
+defined = 1;
+#endif
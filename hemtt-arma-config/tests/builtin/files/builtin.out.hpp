@@ -0,0 +1,17 @@
+line = 2;
+diag_log ["tests/builtin/files/builtin.in.hpp", 3, "a"];
+
+diag_log ["tests/builtin/files/builtin.in.hpp", 5, 5];
+c0 = 0;
+c1 = 1;
+
+c2 = 0;
+date[] = {2020,12,31,23,59};
+time = "23:59:59";
+str = "2020/12/31, 23:59:59";
+has = 1;
+
+
+
+defined = 1;
+
@@ -0,0 +1,33 @@
+#[test]
+fn builtin() {
+    let config = hemtt_arma_config::preprocess_at(
+        hemtt_arma_config::tokenize(
+            &std::fs::read_to_string("tests/builtin/files/builtin.in.hpp").unwrap(),
+            "tests/builtin/files/builtin.in.hpp",
+        )
+        .unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+        1609459199,
+    );
+    let config = hemtt_arma_config::render(config.unwrap());
+    assert_eq!(
+        std::fs::read_to_string("tests/builtin/files/builtin.out.hpp")
+            .unwrap()
+            .replace('\r', ""),
+        config.export()
+    );
+}
+
+#[test]
+fn has_include_without_path() {
+    let config = hemtt_arma_config::preprocess(
+        hemtt_arma_config::tokenize("#if __has_include\n#endif\n", "").unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+    );
+    assert!(matches!(
+        config,
+        Err(hemtt_arma_config::ArmaConfigError::InvalidBuiltin { .. })
+    ));
+}
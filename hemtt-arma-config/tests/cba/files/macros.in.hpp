@@ -0,0 +1,28 @@
+#define PREFIX ace
+#define COMPONENT frag
+#include "tests/cba/script_macros_common.hpp"
+
+addon = QUOTE(ADDON);
+mainAddon = QUOTE(MAIN_ADDON);
+gvar = QUOTE(GVAR(enabled));
+egvar = QUOTE(EGVAR(medical,enabled));
+qgvar = QGVAR(enabled);
+qegvar = QEGVAR(medical,enabled);
+qqgvar = QUOTE(QQGVAR(enabled));
+gvarmain = QGVARMAIN(enabled);
+func = QFUNC(init);
+efunc = QUOTE(EFUNC(common,init));
+qfuncInner = QFUNC_INNER(common,init);
+doubles = QUOTE(DOUBLES(a,b));
+triples = QUOTE(TRIPLES(a,b,c));
+nested = QUOTE(DOUBLES(DOUBLES(a,b),TRIPLES(c,d,e)));
+pathtof = QPATHTOF(data\icon.paa);
+pathto = QUOTE(PATHTO(XEH_preInit));
+lstring = LSTRING(name);
+elstring = ELSTRING(common,name);
+cstring = CSTRING(name);
+ecstring = ECSTRING(common,name);
+array[] = {ARR_3(1,QUOTE(two),GVAR(three))};
+logic = CREATELOGICLOCALS(a,b);
+getvars = GETVARS(a,b,c);
+ifcount = QUOTE(IFCOUNT(list,1,item));
@@ -0,0 +1,24 @@
+addon = "ace_frag";
+mainAddon = "ace_main";
+gvar = "ace_frag_enabled";
+egvar = "ace_medical_enabled";
+qgvar = "ace_frag_enabled";
+qegvar = "ace_medical_enabled";
+qqgvar = """ace_frag_enabled""";
+gvarmain = "ace_enabled";
+func = "ace_frag_fnc_init";
+efunc = "ace_common_fnc_init";
+qfuncInner = "ace_common_fnc_init";
+doubles = "a_b";
+triples = "a_b_c";
+nested = "a_b_c_d_e";
+pathtof = "\x\ace\addons\frag\data\icon.paa";
+pathto = "\x\ace\addons\frag\XEH_preInit.sqf";
+lstring = "STR_ace_frag_name";
+elstring = "STR_ace_common_name";
+cstring = "$STR_ace_frag_name";
+ecstring = "$STR_ace_common_name";
+array[] = {1, "two", ace_frag_three};
+logic = a_b = "LOGIC" createVehicleLocal [0, 0, 0];
+getvars = (a_b getVariable "c");
+ifcount = "if (count list > 1) then { item = list select 1 };";
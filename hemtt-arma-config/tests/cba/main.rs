@@ -18,3 +18,23 @@ fn cba_common_macros() {
     let mut buf = Vec::new();
     simplified.write_rapified(&mut buf).unwrap();
 }
+
+#[test]
+fn cba_macro_conformance() {
+    let config = hemtt_arma_config::preprocess(
+        hemtt_arma_config::tokenize(
+            &std::fs::read_to_string("tests/cba/files/macros.in.hpp").unwrap(),
+            "tests/cba/files/macros.in.hpp",
+        )
+        .unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+    );
+    let config = hemtt_arma_config::render(config.unwrap());
+    assert_eq!(
+        std::fs::read_to_string("tests/cba/files/macros.out.hpp")
+            .unwrap()
+            .replace('\r', ""),
+        config.export().trim_start()
+    );
+}
@@ -0,0 +1,75 @@
+// Written after the layout of the vanilla CfgVehicles
+#define private 0
+#define protected 1
+#define public 2
+
+enum {
+    DestructNo,
+    DestructBuilding,
+    DestructEngine,
+    DestructTree,
+    DestructTent,
+    DestructMan,
+    DestructDefault,
+    DestructWreck
+};
+
+class CfgPatches {
+    class A3_Soft_F_Corpus {
+        author = "$STR_A3_Bohemia_Interactive";
+        name = "Arma 3 - Corpus Vehicles";
+        requiredAddons[] = {"A3_Data_F", "A3_Soft_F"};
+        requiredVersion = 0.1;
+        units[] = {"C_Offroad_01_corpus_F"};
+        weapons[] = {};
+    };
+};
+
+class CfgVehicles {
+    class Car_F;
+    class Offroad_01_base_F: Car_F {
+        scope = protected;
+        destrType = DestructWreck;
+        model = \A3\soft_F\Offroad_01\Offroad_01_unarmed_F;
+        picture = \A3\soft_F\Offroad_01\Data\UI\Offroad_01_base_CA.paa;
+        icon = iconCar;
+        maxSpeed = 140;
+        fuelCapacity = 45;
+        brakeIdleSpeed = 1.78;
+        turnCoef = 2.5;
+        terrainCoef = 2.5e0;
+        waterLeakiness = 1e+1;
+        slowSpeedForwardCoef = 0.25;
+        accelAidForceCoef = .5;
+        engineShiftY = -1.5E-1;
+        hiddenSelections[] = {"Camo", "Camo2",};
+        hiddenSelectionsTextures[] = {
+            "\A3\soft_F\Offroad_01\Data\Offroad_01_ext_CO.paa",
+            '\A3\soft_F\Offroad_01\Data\Offroad_01_ext_CO.paa'
+        };
+        textureList[] = {Guerilla_01, 1, Guerilla_02, 0x1, Guerilla_03, -0x01};
+        class HitPoints {
+            class HitEngine {
+                armor = 0.5;
+                material = -1;
+                name = "engine";
+                visual = "";
+                passThrough = 0.2;
+                explosionShielding = 0x0a;
+            };
+        };
+        class EventHandlers {
+            init = "if (local (_this select 0)) then {[(_this select 0), """", [], false] call bis_fnc_initVehicle;};";
+        };
+    };
+    class C_Offroad_01_corpus_F: Offroad_01_base_F {
+        scope = public;
+        displayName = $STR_A3_CfgVehicles_C_Offroad_01;
+        crew = C_man_1;
+        side = 3;
+        faction = CIV_F;
+        typicalCargo[] = {C_man_1};
+        animationList[] = {HideDoor1, 0, HideDoor2, 0.25};
+        hiddenSelectionsTextures[] += {"\A3\soft_F\Offroad_01\Data\Offroad_01_ext_BASE01_CO.paa"};
+    };
+};
@@ -0,0 +1,58 @@
+// Written after the layout of the vanilla CfgWeapons and CfgMagazines
+class Mode_SemiAuto;
+class Mode_FullAuto;
+
+class CfgMagazines {
+    class CA_Magazine;
+    class 30Rnd_65x39_caseless_mag: CA_Magazine {
+        scope = 2;
+        displayName = "6.5mm 30Rnd STANAG Mag";
+        picture = "\A3\weapons_f\data\ui\m_30stanag_CA.paa";
+        ammo = B_65x39_Caseless;
+        count = 30;
+        initSpeed = 760;
+        tracersEvery = 0;
+        lastRoundsTracer = 4;
+        mass = 8;
+        descriptionShort = "Caliber: 6.5x39 mm<br />Rounds: 30<br />Used in: MX";
+    };
+};
+
+class CfgWeapons {
+    class Rifle_Base_F;
+    class arifle_MX_Base_F: Rifle_Base_F {
+        magazines[] = {30Rnd_65x39_caseless_mag, 30Rnd_65x39_caseless_mag_Tracer};
+        magazineWell[] = {"MX_65x39", "MX_65x39_Large"};
+        modes[] = {Single, FullAuto, fullauto_medium, single_medium_optics1};
+        reloadAction = "GestureReloadMX";
+        recoil = recoil_mx;
+        maxZeroing = 1000;
+        dexterity = 1.6;
+        class Single: Mode_SemiAuto {
+            sounds[] = {StandardSound, SilencedSound};
+            class BaseSoundModeType {
+                closure1[] = {"A3\sounds_f\weapons\closure\closure_rifle_6", 0.630957, 1, 10};
+                soundClosure[] = {closure1, 0.5};
+            };
+            reloadTime = 0.096;
+            dispersion = 0.00087;
+            minRange = 2;
+            minRangeProbab = 0.5;
+            midRange = 200;
+            midRangeProbab = 0.7;
+            maxRange = 400;
+            maxRangeProbab = 0.3;
+        };
+        class FullAuto: Mode_FullAuto {
+            reloadTime = 0.096;
+            dispersion = 8.7e-4;
+            aiRateOfFire = 1e-6;
+        };
+    };
+    class arifle_MX_F: arifle_MX_Base_F {
+        scope = 2;
+        displayName = 'MX 6.5 mm';
+        model = \A3\weapons_F\Rifles\MX\MX_F.p3d;
+        magazines[] -= {30Rnd_65x39_caseless_mag_Tracer};
+    };
+};
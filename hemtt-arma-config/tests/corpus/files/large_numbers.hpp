@@ -0,0 +1,7 @@
+class CfgLargeNumbers {
+    largest = 9223372036854775807;
+    decimal = 99999999999999999999;
+    negative = -99999999999999999999;
+    hex = 0xFFFFFFFFFFFFFFFFF;
+    values[] = {99999999999999999999, 0x7FFFFFFF};
+};
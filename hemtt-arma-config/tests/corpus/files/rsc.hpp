@@ -0,0 +1,48 @@
+// Written after the layout of the vanilla UI configs
+#define CT_STATIC 0
+#define ST_LEFT 0x00
+#define ST_CENTER 0x02
+
+class RscText {
+    access = 0;
+    type = CT_STATIC;
+    idc = -1;
+    style = ST_LEFT;
+    linespacing = 1;
+    colorBackground[] = {0, 0, 0, 0};
+    colorText[] = {1, 1, 1, 1};
+    text = "";
+    shadow = 1;
+    font = RobotoCondensed;
+    SizeEx = "(((((safezoneW / safezoneH) min 1.2) / 1.2) / 25) * 1)";
+    fixedWidth = 0;
+    x = 0;
+    y = 0;
+    h = 0.037;
+    w = 0.3;
+    tooltipColorText[] = {1, 1, 1, 1};
+    tooltipColorBox[] = {1, 1, 1, 1};
+    tooltipColorShade[] = {0, 0, 0, 0.65};
+};
+
+class RscTitles {
+    class Default {
+        idd = -1;
+        fadein = 0;
+        fadeout = 0;
+        duration = 0;
+    };
+    class RscCorpusTitle {
+        idd = -1;
+        movingEnable = false;
+        duration = 1e+6;
+        onLoad = "uiNamespace setVariable ['RscCorpusTitle', _this select 0]";
+        class controls {
+            class Text: RscText {
+                style = ST_CENTER;
+                x = "safezoneX + 0.1 * safezoneW";
+                colorText[] = {"(profilenamespace getvariable ['GUI_BCG_RGB_R',0.13])", 0.54, 0.21, 0.8};
+            };
+        };
+    };
+};
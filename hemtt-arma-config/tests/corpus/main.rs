@@ -0,0 +1,132 @@
+use hemtt_arma_config::simplify::{ArrayElement, Class, Config, Entry};
+
+fn simplified(path: &str) -> Config {
+    let config = hemtt_arma_config::preprocess(
+        hemtt_arma_config::tokenize(&std::fs::read_to_string(path).unwrap(), path).unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+    )
+    .unwrap();
+    Config::from_ast(
+        hemtt_arma_config::parse(&hemtt_arma_config::render(config).export(), path, None).unwrap(),
+    )
+    .unwrap()
+}
+
+fn class<'a>(class: &'a Class, name: &str) -> &'a Class {
+    match class.entries.iter().find(|(n, _)| n == name) {
+        Some((_, Entry::Class(c))) => c,
+        _ => panic!("expected a class `{}`", name),
+    }
+}
+
+fn entry<'a>(class: &'a Class, name: &str) -> &'a Entry {
+    &class
+        .entries
+        .iter()
+        .find(|(n, _)| n == name)
+        .unwrap_or_else(|| panic!("expected an entry `{}`", name))
+        .1
+}
+
+#[test]
+fn corpus() {
+    for file in std::fs::read_dir("tests/corpus/files").unwrap() {
+        let path = file.unwrap().path();
+        let config = simplified(&path.display().to_string());
+        let mut buf = Vec::new();
+        config.write_rapified(&mut buf).unwrap();
+        let read = Config::read_rapified(&mut std::io::Cursor::new(buf)).unwrap();
+        assert_eq!(
+            read.root.entries.len(),
+            config.root.entries.len(),
+            "{}",
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn numbers() {
+    let config = simplified("tests/corpus/files/cfg_vehicles.hpp");
+    let offroad = class(class(&config.root, "CfgVehicles"), "Offroad_01_base_F");
+    assert!(matches!(entry(offroad, "terrainCoef"), Entry::Float(v) if *v == 2.5));
+    assert!(matches!(entry(offroad, "waterLeakiness"), Entry::Float(v) if *v == 10.0));
+    assert!(matches!(entry(offroad, "accelAidForceCoef"), Entry::Float(v) if *v == 0.5));
+    assert!(matches!(entry(offroad, "engineShiftY"), Entry::Float(v) if *v == -0.15));
+    assert!(matches!(entry(offroad, "destrType"), Entry::Int32(7)));
+    let engine = class(class(offroad, "HitPoints"), "HitEngine");
+    assert!(matches!(
+        entry(engine, "explosionShielding"),
+        Entry::Int32(10)
+    ));
+    if let Entry::Array(a) = entry(offroad, "textureList") {
+        assert!(matches!(a.elements[3], ArrayElement::Int32(1)));
+        assert!(matches!(a.elements[5], ArrayElement::Int32(-1)));
+    } else {
+        panic!("expected an array")
+    }
+}
+
+#[test]
+fn large_numbers() {
+    let config = simplified("tests/corpus/files/large_numbers.hpp");
+    let numbers = class(&config.root, "CfgLargeNumbers");
+    assert!(matches!(entry(numbers, "largest"), Entry::Int64(i64::MAX)));
+    assert!(matches!(entry(numbers, "decimal"), Entry::Float(v) if *v == 1e20));
+    assert!(matches!(entry(numbers, "negative"), Entry::Float(v) if *v == -1e20));
+    assert!(matches!(entry(numbers, "hex"), Entry::Float(v) if *v == 2.951_479e20));
+    if let Entry::Array(a) = entry(numbers, "values") {
+        assert!(matches!(a.elements[0], ArrayElement::Float(v) if v == 1e20));
+        assert!(matches!(a.elements[1], ArrayElement::Int32(i32::MAX)));
+    } else {
+        panic!("expected an array")
+    }
+    assert!(hemtt_arma_config::parse("x = 0x1;", "test", None).is_ok());
+    assert!(hemtt_arma_config::parse(&format!("x = {};", "9".repeat(40)), "test", None).is_err());
+}
+
+#[test]
+fn strings() {
+    let config = simplified("tests/corpus/files/cfg_vehicles.hpp");
+    let vehicles = class(&config.root, "CfgVehicles");
+    let offroad = class(vehicles, "Offroad_01_base_F");
+    assert!(
+        matches!(entry(offroad, "model"), Entry::Str(v) if v == "\\A3\\soft_F\\Offroad_01\\Offroad_01_unarmed_F")
+    );
+    assert!(matches!(entry(offroad, "icon"), Entry::Str(v) if v == "iconCar"));
+    if let Entry::Array(a) = entry(offroad, "hiddenSelectionsTextures") {
+        assert!(
+            matches!(&a.elements[1], ArrayElement::Str(v) if v == "\\A3\\soft_F\\Offroad_01\\Data\\Offroad_01_ext_CO.paa")
+        );
+    } else {
+        panic!("expected an array")
+    }
+    let init = entry(class(offroad, "EventHandlers"), "init");
+    assert!(matches!(init, Entry::Str(v) if v.contains("[(_this select 0), \"\", [], false]")));
+    let civilian = class(vehicles, "C_Offroad_01_corpus_F");
+    assert!(
+        matches!(entry(civilian, "displayName"), Entry::Str(v) if v == "$STR_A3_CfgVehicles_C_Offroad_01")
+    );
+}
+
+#[test]
+fn array_operators() {
+    let config = simplified("tests/corpus/files/cfg_weapons.hpp");
+    let mx = class(class(&config.root, "CfgWeapons"), "arifle_MX_F");
+    if let Entry::Array(a) = entry(mx, "magazines") {
+        assert!(a.shrink);
+        assert!(!a.expand);
+    } else {
+        panic!("expected an array")
+    }
+    let mut buf = Vec::new();
+    config.write_rapified(&mut buf).unwrap();
+    let read = Config::read_rapified(&mut std::io::Cursor::new(buf)).unwrap();
+    let mx = class(class(&read.root, "CfgWeapons"), "arifle_MX_F");
+    assert!(matches!(entry(mx, "magazines"), Entry::Array(a) if a.shrink));
+
+    let config = simplified("tests/corpus/files/cfg_vehicles.hpp");
+    let civilian = class(class(&config.root, "CfgVehicles"), "C_Offroad_01_corpus_F");
+    assert!(matches!(entry(civilian, "hiddenSelectionsTextures"), Entry::Array(a) if a.expand));
+}
@@ -0,0 +1,134 @@
+use hemtt_arma_config::{
+    cst::{Cst, Kind},
+    simplify::Config,
+    ArmaConfigError,
+};
+
+const SOURCES: &[&str] = &[
+    "tests/basic_class/config.cpp",
+    "tests/cba/config.hpp",
+    "tests/cba/script_macros_common.hpp",
+    "tests/corpus/files/cfg_vehicles.hpp",
+    "tests/corpus/files/cfg_weapons.hpp",
+    "tests/corpus/files/large_numbers.hpp",
+    "tests/corpus/files/rsc.hpp",
+    "tests/enum/files/enum.hpp",
+    "tests/eval/files/eval.in.hpp",
+];
+
+fn rapified(config: Config) -> Vec<u8> {
+    let mut buf = Vec::new();
+    config.write_rapified(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn lossless() {
+    for path in SOURCES {
+        let source = std::fs::read_to_string(path).unwrap();
+        let cst = Cst::parse(source.clone(), path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+        assert_eq!(cst.to_string(), source, "{}", path);
+        assert_eq!(cst.root().span(), 0..source.len());
+    }
+}
+
+#[test]
+fn same_as_parser() {
+    for path in SOURCES.iter().filter(|p| p.starts_with("tests/corpus")) {
+        let config = hemtt_arma_config::preprocess(
+            hemtt_arma_config::tokenize(&std::fs::read_to_string(path).unwrap(), path).unwrap(),
+            ".",
+            hemtt_arma_config::resolver::Basic,
+        )
+        .unwrap();
+        let preprocessed = hemtt_arma_config::render(config).export();
+        let parsed =
+            Config::from_ast(hemtt_arma_config::parse(&preprocessed, path, None).unwrap()).unwrap();
+        let cst =
+            Config::from_ast(Cst::parse(preprocessed, path).unwrap().to_ast().unwrap()).unwrap();
+        assert_eq!(rapified(parsed), rapified(cst), "{}", path);
+    }
+}
+
+#[test]
+fn spans_and_trivia() {
+    let source = "// Header\nclass Test: Base /* parent */ {\n    value[] += {1, \"two\"}; // trailing\n};\n";
+    let cst = Cst::parse(source, "spans.hpp").unwrap();
+    let root = cst.root();
+    assert_eq!(root.children()[0].kind(), Kind::Comment);
+    let class = root.significant().next().unwrap();
+    assert_eq!(class.kind(), Kind::Class);
+    assert!(cst.text(class).starts_with("class Test"));
+    assert!(cst.text(class).ends_with("};"));
+    assert!(class
+        .children()
+        .iter()
+        .any(|n| n.kind() == Kind::Comment && cst.text(n) == "/* parent */"));
+    let property = class
+        .children()
+        .iter()
+        .find(|n| n.kind() == Kind::Property)
+        .unwrap();
+    assert_eq!(cst.text(property), "value[] += {1, \"two\"};");
+    assert_eq!(cst.text(property.significant().last().unwrap()), ";");
+    let array = property
+        .children()
+        .iter()
+        .find(|n| n.kind() == Kind::Array)
+        .unwrap();
+    let kinds: Vec<Kind> = array
+        .significant()
+        .filter(|n| n.kind() != Kind::Symbol)
+        .map(|n| n.kind())
+        .collect();
+    assert_eq!(kinds, vec![Kind::Number, Kind::Str]);
+}
+
+#[test]
+fn macros_and_directives() {
+    let source = "#include \"script_component.hpp\"\n#define LONG(a) \\\n    a\nclass CfgPatches {\n    class ADDON {\n        units[] = {QGVAR(unit)};\n        VERSION_CONFIG;\n    };\n};\n";
+    let cst = Cst::parse(source, "macros.hpp").unwrap();
+    assert_eq!(cst.to_string(), source);
+    let directives: Vec<&str> = cst
+        .root()
+        .children()
+        .iter()
+        .filter(|n| n.kind() == Kind::Directive)
+        .map(|n| cst.text(n))
+        .collect();
+    assert_eq!(
+        directives,
+        vec![
+            "#include \"script_component.hpp\"",
+            "#define LONG(a) \\\n    a"
+        ]
+    );
+    assert!(cst.to_ast().is_err());
+}
+
+#[test]
+fn parsing_error() {
+    let error = Cst::parse("class Test {\n    value = 1\n};\n", "error.hpp").unwrap_err();
+    assert!(matches!(error, ArmaConfigError::ParsingError { .. }));
+    assert_eq!(error.token().unwrap().start().1, (3, 1));
+    assert_eq!(error.message(), "Expected `;`, found `}`");
+}
+
+#[test]
+fn large_numbers() {
+    use hemtt_arma_config::{cst::number, Statement};
+    assert!(matches!(
+        number("9223372036854775807"),
+        Some(Statement::Integer(i64::MAX))
+    ));
+    assert!(matches!(
+        number("-99999999999999999999"),
+        Some(Statement::Float(f)) if f == -1e20
+    ));
+    assert!(matches!(
+        number("0xFFFFFFFFFFFFFFFFF"),
+        Some(Statement::Float(_))
+    ));
+    assert!(number("0x").is_none());
+    assert!(number("0xZZ").is_none());
+}
@@ -0,0 +1,101 @@
+use hemtt_arma_config::{derapify::derapify, simplify::Config, LinterOptions};
+
+fn simplified(path: &str) -> Config {
+    let config = hemtt_arma_config::preprocess(
+        hemtt_arma_config::tokenize(&std::fs::read_to_string(path).unwrap(), path).unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+    )
+    .unwrap();
+    Config::from_ast(
+        hemtt_arma_config::parse(&hemtt_arma_config::render(config).export(), path, None).unwrap(),
+    )
+    .unwrap()
+}
+
+fn parsed(source: &str) -> Config {
+    Config::from_ast(hemtt_arma_config::parse(source, "config.cpp", None).unwrap()).unwrap()
+}
+
+fn rapified(config: &Config) -> Config {
+    let mut buf = Vec::new();
+    config.write_rapified(&mut buf).unwrap();
+    Config::read_rapified(&mut std::io::Cursor::new(buf)).unwrap()
+}
+
+#[test]
+fn corpus() {
+    for file in std::fs::read_dir("tests/corpus/files").unwrap() {
+        let path = file.unwrap().path().display().to_string();
+        let config = rapified(&simplified(&path));
+        let source = derapify(&config, &LinterOptions::default());
+        assert_eq!(parsed(&source), config, "{}", path);
+    }
+}
+
+#[test]
+fn written() {
+    let config = rapified(&parsed(
+        r#"class CfgVehicles {
+    class Car;
+    delete Unused;
+    class Empty {};
+    class abe_car: Car {
+        displayName = "Truck ""Big""";
+        scope = 2;
+        mass = 2.0;
+        price = 3000000000;
+        weapons[] += {"horn", {1, -0.5}};
+        magazines[] -= {};
+    };
+};"#,
+    ));
+    assert_eq!(
+        derapify(&config, &LinterOptions::default()),
+        r#"class CfgVehicles {
+    class Car;
+    delete Unused;
+    class Empty {};
+    class abe_car: Car {
+        displayName = "Truck ""Big""";
+        scope = 2;
+        mass = 2.0;
+        price = 3000000000;
+        weapons[] += {"horn", {1, -0.5}};
+        magazines[] -= {};
+    };
+};
+"#
+    );
+}
+
+#[test]
+fn wide_arrays() {
+    let config = parsed(
+        r#"class CfgWeapons {
+    class abe_rifle {
+        magazines[] = {"abe_30Rnd_65x39_caseless_mag", "abe_30Rnd_65x39_caseless_mag_Tracer", "abe_100Rnd_65x39_caseless_mag"};
+    };
+};"#,
+    );
+    let source = derapify(&config, &LinterOptions::default());
+    assert!(
+        source.contains("        magazines[] = {\n            \"abe_30Rnd_65x39_caseless_mag\",\n")
+    );
+    assert_eq!(parsed(&source), config);
+}
+
+#[test]
+fn enums() {
+    let mut config = parsed("class CfgVehicles { class House { destrType = 3; }; };");
+    config.enums = vec![
+        (String::from("DestructNo"), 0),
+        (String::from("DestructBuilding"), 1),
+    ];
+    let read = rapified(&config);
+    assert_eq!(read.enums, config.enums);
+    assert_eq!(
+        derapify(&read, &LinterOptions::default()),
+        "enum {\n    DestructNo = 0,\n    DestructBuilding = 1\n};\n\nclass CfgVehicles {\n    class House {\n        destrType = 3;\n    };\n};\n"
+    );
+}
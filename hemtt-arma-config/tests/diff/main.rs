@@ -0,0 +1,118 @@
+use hemtt_arma_config::{diff::diff, simplify::Config};
+
+const OLD: &str = r#"
+class CfgVehicles {
+    class Car;
+    class abe_car: Car {
+        scope = 2;
+        displayName = "Car";
+        maxSpeed = 120;
+        weapons[] = {"horn", "siren", "lights"};
+        class Turrets {
+            class MainTurret {
+                gunner = "driver";
+            };
+        };
+    };
+    class abe_old: abe_car {};
+};
+"#;
+
+const NEW: &str = r#"
+class CfgVehicles {
+    class Car;
+    class Car_F;
+    class ABE_Car: Car_F {
+        scope = 2;
+        displayName = "Car ""Fast""";
+        weapons[] = {"horn", "lights", "flare"};
+        fuelCapacity = 50.5;
+        class Turrets {
+            class MainTurret {
+                gunner = "commander";
+            };
+        };
+    };
+    class abe_new: abe_car {
+        scope = 1;
+    };
+};
+"#;
+
+fn config(source: &str) -> Config {
+    Config::from_ast(hemtt_arma_config::parse(source, "config.cpp", None).unwrap()).unwrap()
+}
+
+#[test]
+fn text() {
+    assert_eq!(
+        diff(&config(OLD), &config(NEW))
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        vec![
+            "~ class CfgVehicles >> ABE_Car: Car -> Car_F",
+            r#"~ CfgVehicles >> ABE_Car >> displayName: "Car" -> "Car ""Fast""""#,
+            "- CfgVehicles >> ABE_Car >> maxSpeed = 120",
+            r#"~ CfgVehicles >> ABE_Car >> weapons[]: -"siren", +"flare""#,
+            r#"~ CfgVehicles >> ABE_Car >> Turrets >> MainTurret >> gunner: "driver" -> "commander""#,
+            "+ CfgVehicles >> ABE_Car >> fuelCapacity = 50.5",
+            "- class CfgVehicles >> abe_old",
+            "+ class CfgVehicles >> abe_new: abe_car",
+        ]
+    );
+}
+
+#[test]
+fn same() {
+    assert!(diff(&config(OLD), &config(OLD)).is_empty());
+    assert!(diff(&config(OLD), &config(&OLD.replace("class Car;", ""))).is_empty());
+}
+
+#[test]
+fn json() {
+    let changes = diff(&config(OLD), &config(NEW));
+    assert_eq!(
+        changes[0].to_json(),
+        serde_json::json!({
+            "change": "parent_changed",
+            "path": ["CfgVehicles", "ABE_Car"],
+            "old": "Car",
+            "new": "Car_F",
+        })
+    );
+    assert_eq!(
+        changes[3].to_json(),
+        serde_json::json!({
+            "change": "array_changed",
+            "path": ["CfgVehicles", "ABE_Car", "weapons"],
+            "removed": ["siren"],
+            "added": ["flare"],
+        })
+    );
+    assert_eq!(
+        changes[5].to_json(),
+        serde_json::json!({
+            "change": "added",
+            "path": ["CfgVehicles", "ABE_Car", "fuelCapacity"],
+            "new": 50.5,
+        })
+    );
+}
+
+#[test]
+fn kinds() {
+    let changes = diff(
+        &config("a = 1; b[] = {1}; class c {}; d[] += {1};"),
+        &config("a[] = {1}; b[] = {1, 2}; c = 2; d[] = {1};"),
+    );
+    assert_eq!(
+        changes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        vec![
+            "~ a: 1 -> {1}",
+            "~ b[]: +2",
+            "~ c: class -> 2",
+            "~ d: += {1} -> {1}",
+        ]
+    );
+}
@@ -0,0 +1,13 @@
+enum {
+    DestructNo,
+    DestructBuilding = 3,
+    DestructEngine,
+    DestructTree = 0x10,
+};
+
+class CfgVehicles {
+    class House {
+        destrType = DestructBuilding;
+        destrTypes[] = {DestructNo, DestructEngine, destructtree};
+    };
+};
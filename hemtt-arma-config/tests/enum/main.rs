@@ -0,0 +1,77 @@
+use hemtt_arma_config::simplify::{ArrayElement, Config, Entry};
+
+fn simplified(path: &str) -> Config {
+    let config = hemtt_arma_config::preprocess(
+        hemtt_arma_config::tokenize(&std::fs::read_to_string(path).unwrap(), path).unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+    )
+    .unwrap();
+    Config::from_ast(
+        hemtt_arma_config::parse(&hemtt_arma_config::render(config).export(), "test", None)
+            .unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn enum_values() {
+    let config = simplified("tests/enum/files/enum.hpp");
+    assert_eq!(config.root.entries.len(), 1);
+    let house = if let Entry::Class(c) = &config.root.entries[0].1 {
+        if let Entry::Class(c) = &c.entries[0].1 {
+            c
+        } else {
+            panic!("expected a class")
+        }
+    } else {
+        panic!("expected a class")
+    };
+    let entries = &house.entries;
+    assert!(matches!(entries[0], (ref n, Entry::Int32(3)) if n == "destrType"));
+    if let (n, Entry::Array(a)) = &entries[1] {
+        assert_eq!(n, "destrTypes");
+        assert!(matches!(a.elements[0], ArrayElement::Int32(0)));
+        assert!(matches!(a.elements[1], ArrayElement::Int32(4)));
+        assert!(matches!(a.elements[2], ArrayElement::Int32(16)));
+    } else {
+        panic!("expected an array")
+    }
+}
+
+#[test]
+fn enum_is_not_rapified() {
+    let mut out = Vec::new();
+    simplified("tests/enum/files/enum.hpp")
+        .write_rapified(&mut out)
+        .unwrap();
+    let mut without = Vec::new();
+    Config::from_ast(
+        hemtt_arma_config::parse(
+            "class CfgVehicles {\n    class House {\n        destrType = 3;\n        destrTypes[] = {0, 4, 16};\n    };\n};\n",
+            "test",
+            None,
+        )
+        .unwrap(),
+    )
+    .unwrap()
+    .write_rapified(&mut without)
+    .unwrap();
+    assert_eq!(out, without);
+}
+
+#[test]
+fn unknown_enum_value() {
+    let config =
+        Config::from_ast(hemtt_arma_config::parse("value = Missing;", "test", None).unwrap())
+            .unwrap();
+    assert!(
+        matches!(config.root.entries[0], (ref n, Entry::Str(ref v)) if n == "value" && v == "Missing")
+    );
+}
+
+#[test]
+fn enum_out_of_range() {
+    assert!(hemtt_arma_config::parse("enum { A = 99999999999999999999 };", "test", None).is_err());
+    assert!(hemtt_arma_config::parse("enum { A = 0xFFFFFFFFFFFFFFFFF };", "test", None).is_err());
+}
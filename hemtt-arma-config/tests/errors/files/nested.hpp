@@ -0,0 +1,4 @@
+#define PAIR(a,b) a, b
+#define WRAP(a) {PAIR(a)}
+
+value[] = WRAP(1);
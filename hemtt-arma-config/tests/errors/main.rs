@@ -0,0 +1,136 @@
+use hemtt_arma_config::ArmaConfigError;
+
+fn preprocess_error(content: &str, path: &str) -> ArmaConfigError {
+    hemtt_arma_config::preprocess(
+        hemtt_arma_config::tokenize(content, path).unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+    )
+    .unwrap_err()
+}
+
+#[test]
+fn missing_include() {
+    let error = preprocess_error("#include \"tests/errors/files/missing.hpp\"\n", "");
+    assert!(matches!(error, ArmaConfigError::IncludeNotFound { .. }));
+    assert_eq!(error.token().unwrap().start().1, (1, 1));
+}
+
+#[test]
+fn unterminated_if() {
+    let error = preprocess_error("value = 1;\n#ifdef A\n#ifndef B\n#endif\n", "");
+    assert!(matches!(error, ArmaConfigError::UnterminatedIf { .. }));
+    assert_eq!(error.token().unwrap().start().1, (2, 1));
+}
+
+#[test]
+fn traced_arg_count() {
+    let path = "tests/errors/files/nested.hpp";
+    let content = std::fs::read_to_string(path).unwrap().replace('\r', "");
+    let error = preprocess_error(&content, path);
+    if let ArmaConfigError::Traced { error, stack } = &error {
+        assert!(matches!(
+            **error,
+            ArmaConfigError::ArgCoundMismatch {
+                expected: 2,
+                actual: 1,
+                ..
+            }
+        ));
+        assert_eq!(stack.len(), 1);
+        assert_eq!(stack[0].start().1, (4, 11));
+    } else {
+        panic!("expected a traced error, got {:?}", error);
+    }
+    assert_eq!(error.token().unwrap().start().1, (2, 18));
+    let report = error.report(|_| Some(content.clone()));
+    assert!(report.contains("Expected 2 arguments for PAIR, got 1"));
+    assert!(report.contains("nested.hpp:2:18"));
+    assert!(report.contains("in WRAP"));
+}
+
+#[test]
+fn recursive_define() {
+    let error = preprocess_error("#define LOOP 1 + LOOP\nvalue = LOOP;\n", "");
+    assert_eq!(error.message(), "Recursive macro expansion: LOOP -> LOOP");
+    assert_eq!(error.token().unwrap().start().1, (1, 18));
+}
+
+#[test]
+fn mutually_recursive_defines() {
+    let content = "#define PING(a) PONG(a)\n#define PONG(a) [a, PING(a)]\nvalue = PING(1);\n";
+    let error = preprocess_error(content, "");
+    assert_eq!(
+        error.message(),
+        "Recursive macro expansion: PING -> PONG -> PING"
+    );
+    if let ArmaConfigError::Traced { stack, .. } = &error {
+        assert_eq!(stack.len(), 2);
+        assert_eq!(stack[1].start().1, (3, 9));
+    } else {
+        panic!("expected a traced error, got {:?}", error);
+    }
+}
+
+#[test]
+fn argument_named_like_parameter() {
+    let content = "#define WRAP(var1) [var1]\nvalue[] = WRAP(var1);\n";
+    let processed = hemtt_arma_config::preprocess(
+        hemtt_arma_config::tokenize(content, "").unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+    )
+    .unwrap();
+    assert_eq!(
+        "value[] = [var1];\n",
+        hemtt_arma_config::render(processed).export()
+    );
+}
+
+#[test]
+fn invalid_pragma() {
+    let error = preprocess_error("value = 1;\n#pragma hemtt flag binarize_everything\n", "");
+    assert!(matches!(error, ArmaConfigError::InvalidPragma { .. }));
+    assert_eq!(error.token().unwrap().start().1, (2, 1));
+    let error = preprocess_error("#pragma hemtt\n", "");
+    assert!(matches!(error, ArmaConfigError::InvalidPragma { .. }));
+}
+
+#[test]
+fn parsing_error() {
+    let error = hemtt_arma_config::parse(
+        "value = 1;\nclass Test {\n    other 2;\n};\n",
+        "test.cpp",
+        None,
+    )
+    .unwrap_err();
+    assert!(matches!(error, ArmaConfigError::ParsingError { .. }));
+    assert_eq!(error.token().unwrap().path(), "test.cpp");
+    assert_eq!(error.token().unwrap().start().1, (3, 5));
+    assert!(error.message().starts_with("Expected "));
+}
+
+#[test]
+fn mapped_parsing_error() {
+    let content = "#define BROKEN(a) a 2\nvalue = 1;\nclass Test {\n    BROKEN(other);\n};\n";
+    let processed = hemtt_arma_config::preprocess(
+        hemtt_arma_config::tokenize(content, "mapped.hpp").unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+    )
+    .unwrap();
+    let rendered = hemtt_arma_config::render(processed);
+    let error = hemtt_arma_config::parse(
+        &rendered.export(),
+        "mapped.hpp",
+        Some(rendered.map().clone()),
+    )
+    .unwrap_err();
+    let token = error.token().unwrap();
+    assert_eq!(token.path(), "mapped.hpp");
+    assert_eq!(token.to_string(), "other");
+    assert_eq!(token.start().1, (4, 12));
+    let report = error.report(|_| Some(content.to_string()));
+    assert!(report.contains("mapped.hpp:4:12"));
+    assert!(report.contains("found `other`"));
+}
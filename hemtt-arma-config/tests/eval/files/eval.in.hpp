@@ -0,0 +1,11 @@
+#define SPEED(x) __EVAL(x * 3.6)
+
+__EXEC(_base = 10; _name = "hemtt")
+
+class Vehicle {
+    maxSpeed = SPEED(25);
+    armor = __EVAL(_base * 2 + 1);
+    __EXEC(_base = _base + 5)
+    displayName = __EVAL(_name + " " + str _base);
+    sizes[] = {__EVAL(_base / 3), __EVAL((_base + 1) mod 4)};
+};
@@ -0,0 +1,45 @@
+use hemtt_arma_config::simplify::{ArrayElement, Config, Entry};
+
+fn simplified(path: &str) -> Config {
+    let config = hemtt_arma_config::preprocess(
+        hemtt_arma_config::tokenize(&std::fs::read_to_string(path).unwrap(), path).unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+    )
+    .unwrap();
+    Config::from_ast(
+        hemtt_arma_config::parse(&hemtt_arma_config::render(config).export(), "test", None)
+            .unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn eval_exec() {
+    let config = simplified("tests/eval/files/eval.in.hpp");
+    assert_eq!(config.root.entries.len(), 1);
+    let vehicle = if let Entry::Class(c) = &config.root.entries[0].1 {
+        c
+    } else {
+        panic!("expected a class")
+    };
+    let entries = &vehicle.entries;
+    assert_eq!(entries.len(), 4);
+    assert!(matches!(entries[0], (ref n, Entry::Float(v)) if n == "maxSpeed" && v == 90.0));
+    assert!(matches!(entries[1], (ref n, Entry::Float(v)) if n == "armor" && v == 21.0));
+    assert!(
+        matches!(entries[2], (ref n, Entry::Str(ref v)) if n == "displayName" && v == "hemtt 15")
+    );
+    if let (n, Entry::Array(a)) = &entries[3] {
+        assert_eq!(n, "sizes");
+        assert!(matches!(a.elements[0], ArrayElement::Float(v) if v == 5.0));
+        assert!(matches!(a.elements[1], ArrayElement::Float(v) if v == 0.0));
+    } else {
+        panic!("expected an array")
+    }
+}
+
+#[test]
+fn undefined_variable() {
+    assert!(hemtt_arma_config::parse("value = __EVAL(_missing + 1);", "test", None).is_err());
+}
@@ -0,0 +1,37 @@
+#include "script_component.hpp"
+class CfgPatches
+{
+  class ADDON {
+        name=COMPONENT_NAME;
+     units[]={};
+    weapons[] = {  };
+        requiredVersion =REQUIRED_VERSION;
+    requiredAddons[]={"cba_main","ace_common"}; // dependencies
+    author = "Me";
+
+
+     VERSION_CONFIG;
+  };
+};
+
+enum {destructengine=2,DESTRUCTDEFAULT,destructwreck};
+
+class CfgVehicles{
+    class Car;
+    class MyCar : Car
+    {
+        scope=2;;
+        maxSpeed = .5E3;
+        armor = 0X1f;
+        fuelCapacity = 1.;
+        // The hitpoints
+        hiddenSelections[] = {"camo1", "camo2", "camo3", "camo4", "camo5", "camo6", "camo7", "camo8"};
+        magazines[] += {"a",{1,2}, "b",};
+        colors[] = {
+            1, // red
+            2
+        };
+        class HitPoints{};
+    };
+    delete   OldCar ;
+};
@@ -0,0 +1,115 @@
+use hemtt_arma_config::{cst::Cst, format::format, LinterOptions};
+
+const SOURCES: &[&str] = &[
+    "tests/basic_class/config.cpp",
+    "tests/cba/config.hpp",
+    "tests/corpus/files/cfg_vehicles.hpp",
+    "tests/corpus/files/cfg_weapons.hpp",
+    "tests/corpus/files/rsc.hpp",
+    "tests/enum/files/enum.hpp",
+    "tests/format/files/unformatted.hpp",
+];
+
+fn format_str(source: &str, options: &LinterOptions) -> String {
+    format(&Cst::parse(source, "test.hpp").unwrap(), options)
+}
+
+fn rapified(source: &str, path: &str) -> Vec<u8> {
+    let config = hemtt_arma_config::preprocess(
+        hemtt_arma_config::tokenize(source, path).unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+    )
+    .unwrap();
+    let config = hemtt_arma_config::simplify::Config::from_ast(
+        hemtt_arma_config::parse(&hemtt_arma_config::render(config).export(), path, None).unwrap(),
+    )
+    .unwrap();
+    let mut buf = Vec::new();
+    config.write_rapified(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn formats() {
+    let source = std::fs::read_to_string("tests/format/files/unformatted.hpp").unwrap();
+    let expected = std::fs::read_to_string("tests/format/files/formatted.hpp").unwrap();
+    assert_eq!(format_str(&source, &LinterOptions::default()), expected);
+}
+
+#[test]
+fn idempotent() {
+    for path in SOURCES {
+        let source = std::fs::read_to_string(path).unwrap();
+        let once = format_str(&source, &LinterOptions::default());
+        assert_eq!(
+            format_str(&once, &LinterOptions::default()),
+            once,
+            "{}",
+            path
+        );
+    }
+}
+
+#[test]
+fn same_config() {
+    for path in SOURCES.iter().filter(|p| p.starts_with("tests/corpus")) {
+        let source = std::fs::read_to_string(path).unwrap();
+        let formatted = format_str(&source, &LinterOptions::default());
+        assert_eq!(
+            rapified(&formatted, path),
+            rapified(&source, path),
+            "{}",
+            path
+        );
+    }
+}
+
+#[test]
+fn options() {
+    let options: LinterOptions = serde_json::from_str(
+        r#"{
+            "class_inheritance_style": "space",
+            "brace_style": "next_line",
+            "indent": 2,
+            "array_width": 30
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        format_str(
+            "class A: B { class C {x[] = {1, 2, 3}; y[] = {\"one\", \"two\", \"three\"};}; };",
+            &options
+        ),
+        r#"class A : B
+{
+  class C
+  {
+    x[] = {1, 2, 3};
+    y[] = {
+      "one",
+      "two",
+      "three"
+    };
+  };
+};
+"#
+    );
+}
+
+#[test]
+fn comments_kept() {
+    let source = "class A /* parent */ : B {\n    x = 1;\n};\nclass C {\n    y = /* one */ 1;\n  /* block\n   comment */\n};\n";
+    assert_eq!(
+        format_str(source, &LinterOptions::default()),
+        "class A /* parent */ : B {\n    x = 1;\n};\nclass C {\n    y = /* one */ 1;\n    /* block\n   comment */\n};\n"
+    );
+}
+
+#[test]
+fn crlf() {
+    assert_eq!(
+        format_str("class A{x=1;};\r\n", &LinterOptions::default()),
+        "class A {\r\n    x = 1;\r\n};\r\n"
+    );
+}
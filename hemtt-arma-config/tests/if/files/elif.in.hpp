@@ -0,0 +1,21 @@
+#define LEVEL 2
+
+#if LEVEL == 1
+level = 1;
+#elif LEVEL == 2
+level = 2;
+#elif LEVEL >= 2
+level = 3;
+#else
+level = 0;
+#endif
+
+#ifdef LEVEL
+#if LEVEL < 0
+sign = "negative";
+#elif defined LEVEL
+sign = "positive";
+#endif
+#elif LEVEL
+skipped = "true";
+#endif
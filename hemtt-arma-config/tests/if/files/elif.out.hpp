@@ -0,0 +1,8 @@
+
+level = 2;
+
+
+
+sign = "positive";
+
+
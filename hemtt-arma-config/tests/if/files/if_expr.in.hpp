@@ -0,0 +1,24 @@
+#define VERSION 3
+#define ENABLED
+
+#if VERSION > 2
+version = "new";
+#else
+version = "old";
+#endif
+
+#if defined(ENABLED) && !defined(DISABLED)
+enabled = "true";
+#endif
+
+#if (VERSION * 2 == 6) || UNDEFINED
+math = "true";
+#endif
+
+#if 0
+#if 1
+nested = "true";
+#endif
+#else
+nested = "false";
+#endif
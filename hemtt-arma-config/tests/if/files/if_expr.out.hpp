@@ -0,0 +1,13 @@
+
+version = "new";
+
+
+enabled = "true";
+
+
+math = "true";
+
+
+
+nested = "false";
+
@@ -37,3 +37,56 @@ fn nested_if() {
         config.export()
     );
 }
+
+#[test]
+fn if_expr() {
+    let config = hemtt_arma_config::preprocess(
+        hemtt_arma_config::tokenize(
+            &std::fs::read_to_string("tests/if/files/if_expr.in.hpp").unwrap(),
+            "tests/if/files/if_expr.in.hpp",
+        )
+        .unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+    );
+    let config = hemtt_arma_config::render(config.unwrap());
+    assert_eq!(
+        std::fs::read_to_string("tests/if/files/if_expr.out.hpp")
+            .unwrap()
+            .replace('\r', ""),
+        config.export()
+    );
+}
+
+#[test]
+fn elif() {
+    let config = hemtt_arma_config::preprocess(
+        hemtt_arma_config::tokenize(
+            &std::fs::read_to_string("tests/if/files/elif.in.hpp").unwrap(),
+            "tests/if/files/elif.in.hpp",
+        )
+        .unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+    );
+    let config = hemtt_arma_config::render(config.unwrap());
+    assert_eq!(
+        std::fs::read_to_string("tests/if/files/elif.out.hpp")
+            .unwrap()
+            .replace('\r', ""),
+        config.export()
+    );
+}
+
+#[test]
+fn invalid_condition() {
+    let config = hemtt_arma_config::preprocess(
+        hemtt_arma_config::tokenize("#if 1 +\nvalue = 1;\n#endif\n", "").unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+    );
+    assert!(matches!(
+        config,
+        Err(hemtt_arma_config::ArmaConfigError::InvalidCondition { .. })
+    ));
+}
@@ -0,0 +1,22 @@
+class CfgPatches {
+    class test_main {
+        units[] = {};
+        requiredAddons[] = {};
+    };
+};
+
+class CfgVehicles {
+    class Car;
+    class Tank;
+    class Test_Car: Car {
+        scope = 2;
+        displayName = "Test Car";
+        displayName = "Test Car 2";
+        Turrets = 1;
+        class Turrets {};
+    };
+    class Test_Car: Tank {
+        maxSpeed = "fast";
+    };
+    delete Old_Car;
+};
@@ -0,0 +1,147 @@
+use hemtt_arma_config::{lint, Level, LinterOptions};
+
+fn lints(source: &str, options: &LinterOptions) -> Vec<(&'static str, Level, (usize, usize))> {
+    lint(
+        &hemtt_arma_config::parse(source, "lints.hpp", None).unwrap(),
+        options,
+    )
+    .into_iter()
+    .map(|lint| (lint.code(), lint.level(), lint.position()))
+    .collect()
+}
+
+#[test]
+fn rules() {
+    let source = std::fs::read_to_string("tests/lint/files/lints.hpp").unwrap();
+    assert_eq!(
+        lints(&source, &LinterOptions::default()),
+        vec![
+            ("C004", Level::Warning, (4, 9)),
+            ("C001", Level::Warning, (14, 9)),
+            ("C003", Level::Warning, (15, 9)),
+            ("C002", Level::Error, (18, 5)),
+            ("C005", Level::Warning, (19, 9)),
+            ("C006", Level::Warning, (21, 5)),
+        ]
+    );
+}
+
+#[test]
+fn messages() {
+    let source = "class CfgVehicles {\n    class A;\n    class B: A {};\n    class B {};\n    delete C;\n};\n";
+    let found = lint(
+        &hemtt_arma_config::parse(source, "lints.hpp", None).unwrap(),
+        &LinterOptions::default(),
+    );
+    let messages: Vec<String> = found.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "[C002] `B` in `CfgVehicles` was defined with the parent `A`, it is defined again with no parent",
+            "[C006] `C` is deleted but not declared in `CfgVehicles`, declare it with `class C;`",
+        ]
+    );
+}
+
+#[test]
+fn options() {
+    let source = std::fs::read_to_string("tests/lint/files/lints.hpp").unwrap();
+    let options: LinterOptions = serde_json::from_str(
+        r#"{
+            "rules": {
+                "C001": "error",
+                "parent_changed": "warning",
+                "empty_required_addons": "allow",
+                "C003": "allow",
+                "C005": "allow",
+                "C006": "allow"
+            }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        lints(&source, &options),
+        vec![
+            ("C001", Level::Error, (14, 9)),
+            ("C002", Level::Warning, (18, 5)),
+        ]
+    );
+}
+
+#[test]
+fn expansions_are_not_duplicates() {
+    let source = "class A {\n    items[] = {1};\n    items[] += {2};\n    items[] -= {1};\n};\n";
+    assert!(lints(source, &LinterOptions::default()).is_empty());
+}
+
+#[test]
+fn report() {
+    let source = "class A {\n    x = 1;\n    x = 2;\n};\n";
+    let rendered = hemtt_arma_config::render(
+        hemtt_arma_config::preprocess(
+            hemtt_arma_config::tokenize(source, "report.hpp").unwrap(),
+            ".",
+            hemtt_arma_config::resolver::Basic,
+        )
+        .unwrap(),
+    );
+    let ast = hemtt_arma_config::parse(&rendered.export(), "report.hpp", None).unwrap();
+    let found = lint(&ast, &LinterOptions::default());
+    assert_eq!(found.len(), 1);
+    let (line, col) = found[0].position();
+    let token = rendered.origin(line, col);
+    let report = found[0].report(token.as_ref(), |_| Some(source.to_string()));
+    assert!(report.starts_with("warning[C001]: `x` is set more than once in `A`"));
+    assert!(report.contains("report.hpp:3:5"), "{}", report);
+}
+
+/// The lines of the lints that are not suppressed by a pragma
+fn unsuppressed(source: &str) -> Vec<usize> {
+    let rendered = hemtt_arma_config::preprocess_with(
+        hemtt_arma_config::tokenize(source, "suppressed.hpp").unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+        false,
+    )
+    .unwrap()
+    .render();
+    let ast = hemtt_arma_config::parse(&rendered.export(), "suppressed.hpp", None).unwrap();
+    lint(&ast, &LinterOptions::default())
+        .into_iter()
+        .filter_map(|lint| {
+            let (line, col) = lint.position();
+            let token = rendered.origin(line, col).unwrap();
+            let line = token.start().1 .0;
+            if lint.suppressed(rendered.pragmas(), token.path(), line) {
+                None
+            } else {
+                Some(line)
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn suppressed() {
+    let source =
+        "class A {\n    x = 1;\n    x = 2;\n#pragma hemtt suppress C001\n    y = 1;\n    y = 2;\n};\n";
+    assert_eq!(unsuppressed(source), vec![3]);
+}
+
+#[test]
+fn suppressed_next_line() {
+    let source = r#"class A {
+    x = 1;
+#pragma hemtt suppress duplicate_property next
+    x = 2;
+    x = 3;
+#pragma hemtt suppress C001
+    y = 1;
+    y = 2;
+#pragma hemtt suppress duplicate_property end
+    z = 1;
+    z = 2;
+};
+"#;
+    assert_eq!(unsuppressed(source), vec![5, 11]);
+}
@@ -0,0 +1,127 @@
+use hemtt_arma_config::{
+    patches::{check, Patch, Problem},
+    simplify::Config,
+};
+
+fn patch(name: &str, required: &[&str]) -> Patch {
+    Patch::new(
+        name.to_string(),
+        required.iter().map(ToString::to_string).collect(),
+    )
+}
+
+fn vanilla(name: &str) -> bool {
+    name.to_lowercase().starts_with("a3_")
+}
+
+#[test]
+fn from_config() {
+    let config = Config::from_ast(
+        hemtt_arma_config::parse(
+            r#"class CfgPatches {
+    class abe_main {
+        units[] = {};
+        requiredAddons[] = {"A3_Data_F", "cba_main"};
+    };
+    class abe_extra {
+        requiredAddons[] = {};
+    };
+    class abe_old;
+};
+class CfgVehicles {};
+"#,
+            "config.cpp",
+            None,
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        config.patches(),
+        Some(vec![
+            patch("abe_main", &["A3_Data_F", "cba_main"]),
+            patch("abe_extra", &[]),
+        ])
+    );
+    let config = Config::from_ast(
+        hemtt_arma_config::parse("class CfgVehicles {};", "config.cpp", None).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(config.patches(), None);
+}
+
+#[test]
+fn valid() {
+    let patches = vec![
+        patch("abe_main", &["A3_Data_F"]),
+        patch("abe_common", &["abe_main"]),
+        patch("abe_weapons", &["abe_common", "abe_main"]),
+        patch("abe_compat", &["abe_weapons"]),
+    ];
+    assert_eq!(
+        check(&patches, vanilla),
+        vec![Problem::Unused(String::from("abe_compat"))]
+    );
+}
+
+#[test]
+fn single() {
+    assert_eq!(check(&[patch("abe_main", &["A3_Data_F"])], vanilla), vec![]);
+}
+
+#[test]
+fn missing() {
+    let patches = vec![
+        patch("abe_main", &["cba_main"]),
+        patch("abe_common", &["ABE_Main", "abe_mian"]),
+        patch("abe_weapons", &["abe_common", "abe_vehicles"]),
+    ];
+    assert_eq!(
+        check(&patches, vanilla),
+        vec![
+            Problem::Missing {
+                patch: String::from("abe_main"),
+                required: String::from("cba_main"),
+                suggestion: None,
+            },
+            Problem::Missing {
+                patch: String::from("abe_common"),
+                required: String::from("abe_mian"),
+                suggestion: Some(String::from("abe_main")),
+            },
+            Problem::Missing {
+                patch: String::from("abe_weapons"),
+                required: String::from("abe_vehicles"),
+                suggestion: None,
+            },
+            Problem::Unused(String::from("abe_weapons")),
+        ]
+    );
+    assert_eq!(
+        check(&patches, |name| vanilla(name) || name.starts_with("cba_")).len(),
+        3
+    );
+}
+
+#[test]
+fn cycles() {
+    let patches = vec![
+        patch("abe_main", &[]),
+        patch("abe_a", &["abe_main", "abe_b"]),
+        patch("abe_b", &["abe_c"]),
+        patch("abe_c", &["abe_a"]),
+        patch("abe_self", &["abe_self", "abe_a"]),
+    ];
+    assert_eq!(
+        check(&patches, vanilla),
+        vec![
+            Problem::Cycle(vec![
+                String::from("abe_a"),
+                String::from("abe_b"),
+                String::from("abe_c"),
+                String::from("abe_a"),
+            ]),
+            Problem::Cycle(vec![String::from("abe_self"), String::from("abe_self")]),
+        ]
+    );
+}
@@ -339,3 +339,175 @@ shown = true;
     let config = hemtt_arma_config::render(config.unwrap());
     assert_eq!("\n    \n\n\nshown = true;\n\n", config.export());
 }
+
+#[test]
+fn paste() {
+    let content = r#"
+#define JOIN(a,b) a ## b
+#define FOO 42
+#define IFCOUNT(list,i) count ##list > ##i
+spaced = JOIN(x, y);
+rescanned = JOIN(FO,O);
+marker = IFCOUNT(items,1);
+"#;
+    let config = hemtt_arma_config::preprocess(
+        hemtt_arma_config::tokenize(content, "").unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+    );
+    let config = hemtt_arma_config::render(config.unwrap());
+    assert_eq!(
+        "\nspaced = xy;\nrescanned = 42;\nmarker = count items > 1;\n",
+        config.export()
+    );
+}
+
+#[test]
+fn stringify_expanded() {
+    let content = r#"
+#define FOO 42
+#define STR(x) #x
+value = STR(FOO);
+"#;
+    let config = hemtt_arma_config::preprocess(
+        hemtt_arma_config::tokenize(content, "").unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+    );
+    let config = hemtt_arma_config::render(config.unwrap());
+    assert_eq!("\nvalue = \"42\";\n", config.export());
+}
+
+#[test]
+fn expansion_trace() {
+    let content = r#"
+#define DOUBLES(var1,var2) var1##_##var2
+#define GVAR(var1) DOUBLES(tag,var1)
+value = GVAR(test);
+"#;
+    let processed = hemtt_arma_config::preprocess_with(
+        hemtt_arma_config::tokenize(content, "trace.hpp").unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+        true,
+    )
+    .unwrap();
+    assert_eq!(
+        "\nvalue = tag_test;\n",
+        hemtt_arma_config::render(processed.tokens().to_vec()).export()
+    );
+    let trace = processed.trace().unwrap();
+    let expansions = trace.expansions();
+    assert_eq!(expansions[0].name, "GVAR");
+    assert_eq!(expansions[0].depth, 0);
+    assert_eq!(expansions[0].site.start().1, (4, 9));
+    assert!(expansions
+        .iter()
+        .any(|e| e.name == "DOUBLES" && e.depth == 1));
+    assert!(trace
+        .export()
+        .starts_with("GVAR at trace.hpp:4:9 -> tag_test\n"));
+}
+
+#[test]
+fn inspection_html() {
+    let content = "#define LESS(a) a < 2\nvalue = LESS(1);\n";
+    let config = hemtt_arma_config::preprocess(
+        hemtt_arma_config::tokenize(content, "inspect.hpp").unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+    )
+    .unwrap();
+    let html = hemtt_arma_config::render(config).export_html(|_| Some(content.to_string()));
+    assert!(html.contains("<a class=\"info\" href=\"#f0-2\" title=\"inspect.hpp:2:1\">value</a>"));
+    assert!(html.contains("&lt;"));
+    assert!(html.contains("<h2 id=\"f0\">inspect.hpp</h2>"));
+    assert!(html.contains("<span id=\"f0-2\">    2  value = LESS(1);</span>"));
+}
+
+#[test]
+fn pragmas() {
+    let content = r#"value = 1;
+#pragma hemtt flag pbo_no_binarize
+#pragma hemtt suppress missing_patch unused_define
+#pragma once
+other = 2;
+"#;
+    let processed = hemtt_arma_config::preprocess_with(
+        hemtt_arma_config::tokenize(content, "pragma.hpp").unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+        false,
+    )
+    .unwrap();
+    let rendered = processed.render();
+    assert_eq!("value = 1;\nother = 2;\n", rendered.export());
+    let pragmas = rendered.pragmas();
+    assert_eq!(pragmas.iter().count(), 3);
+    assert!(pragmas.flag(hemtt_arma_config::Flag::PboNoBinarize));
+    assert!(pragmas.suppressed("missing_patch", "pragma.hpp", 5));
+    assert!(!pragmas.suppressed("missing_patch", "pragma.hpp", 1));
+    assert!(!pragmas.suppressed("missing_patch", "other.hpp", 5));
+    let json = rendered.export_pragmas_json().unwrap();
+    let read: hemtt_arma_config::Pragmas = serde_json::from_str(&json).unwrap();
+    assert_eq!(&read, pragmas);
+}
+
+#[test]
+fn pragma_regions() {
+    let content = r#"#pragma hemtt suppress missing_patch
+one = 1;
+#pragma hemtt suppress missing_patch end
+two = 2;
+#pragma hemtt suppress missing_patch
+three = 3;
+"#;
+    let processed = hemtt_arma_config::preprocess_with(
+        hemtt_arma_config::tokenize(content, "pragma.hpp").unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+        false,
+    )
+    .unwrap();
+    let rendered = processed.render();
+    let pragmas = rendered.pragmas();
+    assert!(pragmas.suppressed("missing_patch", "pragma.hpp", 2));
+    assert!(!pragmas.suppressed("missing_patch", "pragma.hpp", 4));
+    assert!(pragmas.suppressed("missing_patch", "pragma.hpp", 6));
+
+    let content = "#pragma hemtt suppress end\n";
+    assert!(hemtt_arma_config::preprocess_with(
+        hemtt_arma_config::tokenize(content, "pragma.hpp").unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+        false,
+    )
+    .is_err());
+}
+
+#[test]
+fn pragma_define() {
+    let content = r#"#pragma hemtt define DEBUG_MODE_FULL 1
+#define DEBUG_MODE_FULL 0
+#undef DEBUG_MODE_FULL
+value = DEBUG_MODE_FULL;
+"#;
+    let processed = hemtt_arma_config::preprocess_with(
+        hemtt_arma_config::tokenize(content, "pragma.hpp").unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+        false,
+    )
+    .unwrap();
+    let rendered = processed.render();
+    assert_eq!("\nvalue = 1;\n", rendered.export());
+
+    let content = "#pragma hemtt define DOUBLE(x) x x\n";
+    assert!(hemtt_arma_config::preprocess_with(
+        hemtt_arma_config::tokenize(content, "pragma.hpp").unwrap(),
+        ".",
+        hemtt_arma_config::resolver::Basic,
+        false,
+    )
+    .is_err());
+}
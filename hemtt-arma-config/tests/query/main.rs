@@ -0,0 +1,167 @@
+use hemtt_arma_config::{query::Query, simplify::Config};
+
+const CONFIG: &str = r#"
+class CfgPatches {
+    class abe_main {
+        requiredAddons[] = {"A3_Data_F"};
+    };
+};
+class CfgVehicles {
+    class Car;
+    class abe_car: Car {
+        scope = 2;
+        displayName = "Car";
+        maxSpeed = 120.5;
+        weapons[] = {"horn", "siren"};
+    };
+    class abe_car_base: Car {
+        scope = 0;
+        displayName = "Base";
+    };
+    class abe_truck: abe_car_base {
+        scope = 2;
+        displayName = "Truck ""Big""";
+        maxSpeed = 80;
+        class Turrets {
+            class MainTurret {
+                gunner = "commander";
+            };
+        };
+    };
+};
+"#;
+
+fn config() -> Config {
+    Config::from_ast(hemtt_arma_config::parse(CONFIG, "config.cpp", None).unwrap()).unwrap()
+}
+
+fn run(query: &str) -> Vec<String> {
+    let config = config();
+    query
+        .parse::<Query>()
+        .unwrap()
+        .run(&config)
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn paths() {
+    assert_eq!(
+        run("CfgVehicles >> abe_car >> displayName"),
+        vec![r#"CfgVehicles >> abe_car >> displayName = "Car""#]
+    );
+    assert_eq!(
+        run("cfgvehicles>>ABE_CAR>>weapons[]"),
+        vec![r#"CfgVehicles >> abe_car >> weapons[] = {"horn", "siren"}"#]
+    );
+    assert_eq!(
+        run("CfgVehicles >> abe_truck"),
+        vec!["CfgVehicles >> abe_truck: abe_car_base"]
+    );
+    assert_eq!(
+        run(r#"CfgVehicles >> "abe_truck" >> displayName"#),
+        vec![r#"CfgVehicles >> abe_truck >> displayName = "Truck ""Big""""#]
+    );
+    assert!(run("CfgVehicles >> abe_car >> missing").is_empty());
+}
+
+#[test]
+fn wildcards() {
+    assert_eq!(
+        run("CfgVehicles >> abe_*_base"),
+        vec!["CfgVehicles >> abe_car_base: Car"]
+    );
+    assert_eq!(
+        run("CfgVehicles >> * >> maxSpeed"),
+        vec![
+            "CfgVehicles >> abe_car >> maxSpeed = 120.5",
+            "CfgVehicles >> abe_truck >> maxSpeed = 80",
+        ]
+    );
+    assert_eq!(
+        run("** >> gunner"),
+        vec![r#"CfgVehicles >> abe_truck >> Turrets >> MainTurret >> gunner = "commander""#]
+    );
+    assert_eq!(run("CfgPatches >> **").len(), 2);
+}
+
+#[test]
+fn predicates() {
+    assert_eq!(
+        run("CfgVehicles >> *[scope == 2]"),
+        vec![
+            "CfgVehicles >> abe_car: Car",
+            "CfgVehicles >> abe_truck: abe_car_base"
+        ]
+    );
+    assert_eq!(
+        run("CfgVehicles >> *[scope = 2][maxSpeed > 100] >> displayName"),
+        vec![r#"CfgVehicles >> abe_car >> displayName = "Car""#]
+    );
+    assert_eq!(
+        run(r#"CfgVehicles >> *[displayName != "car"][scope]"#),
+        vec![
+            "CfgVehicles >> abe_car_base: Car",
+            "CfgVehicles >> abe_truck: abe_car_base"
+        ]
+    );
+    assert_eq!(
+        run(r#"CfgVehicles >> *[weapons[] == "SIREN"]"#),
+        vec!["CfgVehicles >> abe_car: Car"]
+    );
+    assert_eq!(run("CfgVehicles >> *[maxSpeed <= 80]").len(), 1);
+    assert!(run("CfgVehicles >> *[Turrets]").is_empty());
+}
+
+#[test]
+fn json() {
+    let config = config();
+    let found = "CfgVehicles >> abe_truck"
+        .parse::<Query>()
+        .unwrap()
+        .run(&config);
+    assert_eq!(
+        found[0].to_json(),
+        serde_json::json!({
+            "path": ["CfgVehicles", "abe_truck"],
+            "value": {
+                "parent": "abe_car_base",
+                "entries": {
+                    "scope": 2,
+                    "displayName": "Truck \"Big\"",
+                    "maxSpeed": 80,
+                    "Turrets": {
+                        "parent": null,
+                        "entries": {
+                            "MainTurret": {
+                                "parent": null,
+                                "entries": { "gunner": "commander" }
+                            }
+                        }
+                    }
+                }
+            }
+        })
+    );
+    let found = "CfgVehicles >> abe_car >> maxSpeed"
+        .parse::<Query>()
+        .unwrap()
+        .run(&config);
+    assert_eq!(found[0].to_json()["value"], serde_json::json!(120.5));
+}
+
+#[test]
+fn invalid() {
+    for query in &[
+        "",
+        "CfgVehicles >> ",
+        "CfgVehicles >> *[scope == 2",
+        "CfgVehicles >> \"abe",
+        "CfgVehicles >> *[== 2]",
+        "CfgVehicles >> * junk",
+    ] {
+        assert!(query.parse::<Query>().is_err(), "{}", query);
+    }
+}
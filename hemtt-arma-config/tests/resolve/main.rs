@@ -0,0 +1,220 @@
+use hemtt_arma_config::{
+    resolve::{Origin, Resolved},
+    simplify::{Array, ArrayElement, Config, Entry},
+};
+
+fn config(source: &str) -> Config {
+    Config::from_ast(hemtt_arma_config::parse(source, "config.cpp", None).unwrap()).unwrap()
+}
+
+fn origin(addon: &str) -> Origin {
+    Origin::new(addon.to_string(), format!("{}/config.cpp", addon))
+}
+
+fn strings(elements: &[&str]) -> Entry {
+    Entry::Array(Array {
+        expand: false,
+        shrink: false,
+        elements: elements
+            .iter()
+            .map(|e| ArrayElement::Str(e.to_string()))
+            .collect(),
+    })
+}
+
+const BASE: &str = r#"
+class CfgPatches {
+    class base_main {
+        requiredAddons[] = {};
+    };
+};
+class CfgVehicles {
+    class Car {
+        maxSpeed = 100;
+        weapons[] = {"horn"};
+        class Turrets {
+            class MainTurret {
+                gunner = "driver";
+            };
+        };
+    };
+    class Car_F: Car {
+        displayName = "Car";
+        fuelCapacity = 50;
+    };
+    class Tank: Car {};
+};
+"#;
+
+const MOD: &str = r#"
+class CfgPatches {
+    class mod_main {
+        requiredAddons[] = {"base_main"};
+    };
+};
+class CfgVehicles {
+    class Car_F;
+    class Car;
+    class MyCar: Car_F {
+        displayName = "My Car";
+        weapons[] += {"siren"};
+    };
+    class Tank {
+        maxSpeed = 60;
+    };
+    delete Unused;
+};
+"#;
+
+fn loaded() -> Resolved {
+    Resolved::load(vec![
+        (origin("mod"), config(MOD)),
+        (origin("base"), config(BASE)),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn inherited() {
+    let resolved = loaded();
+    let speed = resolved.get(&["CfgVehicles", "MyCar", "maxSpeed"]).unwrap();
+    assert_eq!(speed.value(), &Entry::Int32(100));
+    assert_eq!(speed.origin(), &origin("base"));
+    assert_eq!(speed.class(), &["CfgVehicles", "Car"]);
+    let name = resolved
+        .get(&["cfgvehicles", "mycar", "DISPLAYNAME"])
+        .unwrap();
+    assert_eq!(name.value(), &Entry::Str(String::from("My Car")));
+    assert_eq!(name.origin(), &origin("mod"));
+    assert_eq!(
+        resolved
+            .get(&["CfgVehicles", "MyCar", "Turrets", "MainTurret", "gunner"])
+            .unwrap()
+            .value(),
+        &Entry::Str(String::from("driver"))
+    );
+    assert!(resolved.get(&["CfgVehicles", "MyCar", "armor"]).is_none());
+    assert!(resolved.get(&["CfgVehicles", "MyCar", "Turrets"]).is_none());
+}
+
+#[test]
+fn expanded() {
+    let resolved = loaded();
+    assert_eq!(
+        resolved
+            .get(&["CfgVehicles", "MyCar", "weapons"])
+            .unwrap()
+            .value(),
+        &strings(&["horn", "siren"])
+    );
+    let mut shrink = Resolved::new();
+    shrink.merge(&config(BASE), &origin("base"));
+    shrink.merge(
+        &config("class CfgVehicles { class Car; class Car_F: Car { weapons[] -= {\"horn\"}; }; };"),
+        &origin("mod"),
+    );
+    assert_eq!(
+        shrink
+            .get(&["CfgVehicles", "Car_F", "weapons"])
+            .unwrap()
+            .value(),
+        &strings(&[])
+    );
+}
+
+#[test]
+fn merged() {
+    let resolved = loaded();
+    let speed = resolved.get(&["CfgVehicles", "Tank", "maxSpeed"]).unwrap();
+    assert_eq!(speed.value(), &Entry::Int32(60));
+    assert_eq!(speed.origin(), &origin("mod"));
+    assert_eq!(
+        resolved.class_origin(&["CfgVehicles", "Tank"]),
+        Some(&origin("base"))
+    );
+    assert_eq!(
+        resolved
+            .inheritance(&["CfgVehicles", "Tank"])
+            .unwrap()
+            .len(),
+        1,
+        "redefining without a parent removes it"
+    );
+
+    let mut deleted = loaded();
+    deleted.merge(
+        &config("class CfgVehicles { class MyCar; delete MyCar; };"),
+        &origin("other"),
+    );
+    assert!(deleted.has_class(&["CfgVehicles", "Car"]));
+    assert!(!deleted.has_class(&["CfgVehicles", "MyCar"]));
+}
+
+#[test]
+fn inheritance() {
+    assert_eq!(
+        loaded()
+            .inheritance(&["CfgVehicles", "MyCar"])
+            .unwrap()
+            .into_iter()
+            .map(|path| path.join(">>"))
+            .collect::<Vec<_>>(),
+        vec![
+            "CfgVehicles>>MyCar",
+            "CfgVehicles>>Car_F",
+            "CfgVehicles>>Car"
+        ]
+    );
+}
+
+#[test]
+fn flatten() {
+    assert_eq!(
+        loaded()
+            .flatten(&["CfgVehicles", "MyCar"])
+            .unwrap()
+            .into_iter()
+            .map(|(name, property)| (name, property.value().clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (String::from("maxSpeed"), Entry::Int32(100)),
+            (String::from("weapons"), strings(&["horn", "siren"])),
+            (
+                String::from("displayName"),
+                Entry::Str(String::from("My Car"))
+            ),
+            (String::from("fuelCapacity"), Entry::Int32(50)),
+        ]
+    );
+}
+
+#[test]
+fn undefined_parents() {
+    let resolved = Resolved::load(vec![(
+        origin("mod"),
+        config("class CfgVehicles { class Car; class A: Car {}; class B: Missing {}; class C { class D: A {}; }; };"),
+    )])
+    .unwrap();
+    assert_eq!(
+        resolved.undefined_parents(),
+        vec![(
+            vec![String::from("CfgVehicles"), String::from("B")],
+            String::from("Missing")
+        )]
+    );
+}
+
+#[test]
+fn load_order() {
+    let cycle = Resolved::load(vec![
+        (
+            origin("a"),
+            config("class CfgPatches { class a { requiredAddons[] = {\"b\"}; }; };"),
+        ),
+        (
+            origin("b"),
+            config("class CfgPatches { class b { requiredAddons[] = {\"a\"}; }; };"),
+        ),
+    ]);
+    assert!(cycle.is_err());
+}
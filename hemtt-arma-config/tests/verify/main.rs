@@ -0,0 +1,119 @@
+use hemtt_arma_config::{
+    simplify::{Config, Entry},
+    verify::{compare, compare_bytes, Mismatch},
+};
+
+const CONFIG: &str = r#"
+class CfgVehicles {
+    class Car;
+    class abe_car: Car {
+        scope = 2;
+        weapons[] = {"horn", "siren"};
+        class Turrets {};
+    };
+    class abe_truck: abe_car {
+        maxSpeed = 80;
+    };
+};
+"#;
+
+fn config() -> Config {
+    Config::from_ast(hemtt_arma_config::parse(CONFIG, "config.cpp", None).unwrap()).unwrap()
+}
+
+fn rapified(config: &Config) -> Vec<u8> {
+    let mut buf = Vec::new();
+    config.write_rapified(&mut buf).unwrap();
+    buf
+}
+
+fn path(path: &[&str]) -> Vec<String> {
+    path.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn read_back() {
+    let config = config();
+    let read = || Config::read_rapified(&mut std::io::Cursor::new(rapified(&config))).unwrap();
+    assert_eq!(compare(&config, &read()), None);
+
+    let mut changed = read();
+    if let Entry::Class(vehicles) = &mut changed.root.entries[0].1 {
+        vehicles.entries.pop();
+    }
+    assert_eq!(
+        compare(&config, &changed),
+        Some(Mismatch::Entry(path(&["CfgVehicles", "abe_truck"])))
+    );
+
+    let other = Config::from_ast(
+        hemtt_arma_config::parse(
+            &CONFIG.replace("scope = 2", "scope = 1"),
+            "config.cpp",
+            None,
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        compare(&config, &other),
+        Some(Mismatch::Entry(path(&["CfgVehicles", "abe_car", "scope"])))
+    );
+}
+
+#[test]
+fn bytes() {
+    let reference = rapified(&config());
+    assert_eq!(compare_bytes(&reference, &reference), None);
+
+    let siren = reference.windows(5).position(|w| w == b"siren").unwrap();
+    let mut found = reference.clone();
+    found[siren] = b'S';
+    assert_eq!(
+        compare_bytes(&reference, &found),
+        Some(Mismatch::Byte {
+            offset: siren as u64,
+            class: Some(path(&["CfgVehicles", "abe_car"])),
+        })
+    );
+
+    let mut found = reference.clone();
+    found[5] = 1;
+    assert_eq!(
+        compare_bytes(&reference, &found),
+        Some(Mismatch::Byte {
+            offset: 5,
+            class: None
+        })
+    );
+
+    let found = &reference[..reference.len() - 2];
+    assert_eq!(
+        compare_bytes(&reference, found),
+        Some(Mismatch::Byte {
+            offset: found.len() as u64,
+            class: None
+        })
+    );
+    assert_eq!(
+        Mismatch::Byte {
+            offset: 0x2a,
+            class: Some(path(&["CfgVehicles", "abe_car"]))
+        }
+        .to_string(),
+        "the bytes differ at offset 0x2a in the body of `CfgVehicles >> abe_car`"
+    );
+}
+
+#[test]
+fn corrupt() {
+    let mut found = rapified(&config());
+    // The offset of the body of `CfgVehicles`, pointed at the root class
+    let offset = found
+        .windows(12)
+        .position(|w| w == b"CfgVehicles\0")
+        .unwrap()
+        + 12;
+    found[offset..offset + 4].copy_from_slice(&16u32.to_le_bytes());
+    assert!(Config::read_rapified(&mut std::io::Cursor::new(&found)).is_err());
+}
@@ -3,6 +3,7 @@ use std::io::{Read, Write};
 
 #[cfg(feature = "async-tokio")]
 pub mod async_tokio;
+pub mod lzss;
 
 pub trait ReadExt: Read {
     fn read_cstring(&mut self) -> io::Result<String>;
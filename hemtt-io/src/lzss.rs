@@ -0,0 +1,170 @@
+//! The LZSS compression of PBO entries
+//!
+//! Each flag byte is followed by eight items, a set bit (from the lowest) is a literal byte and
+//! a clear bit is a two byte reference to up to 18 bytes that were already written, up to 4095
+//! bytes back. References to before the start of the data are read as spaces. The data ends
+//! with the sum of the decompressed bytes.
+
+use std::io;
+
+/// How far back a reference can point
+const WINDOW: usize = 4095;
+/// The shortest match that is written as a reference
+const MIN_MATCH: usize = 3;
+/// The longest match a reference can hold
+const MAX_MATCH: usize = 18;
+/// How many earlier positions are tried for each match
+const MAX_CHAIN: usize = 256;
+const HASH_SIZE: usize = 1 << 12;
+const NONE: usize = usize::MAX;
+
+/// Decompresses `size` bytes and checks them against the checksum after them
+///
+/// # Errors
+/// If the data ends early, has an invalid reference or the checksum does not match
+pub fn decompress(data: &[u8], size: usize) -> io::Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(size);
+    let mut input = data.iter().copied();
+    let mut next = || input.next().ok_or_else(|| invalid("the data ends early"));
+    while out.len() < size {
+        let flags = next()?;
+        for bit in 0..8 {
+            if out.len() >= size {
+                break;
+            }
+            if flags & (1 << bit) != 0 {
+                out.push(next()?);
+                continue;
+            }
+            let low = usize::from(next()?);
+            let high = usize::from(next()?);
+            let distance = low | ((high & 0xF0) << 4);
+            if distance == 0 {
+                return Err(invalid("a reference points at itself"));
+            }
+            let length = (high & 0x0F) + MIN_MATCH;
+            for _ in 0..length.min(size - out.len()) {
+                out.push(if distance > out.len() {
+                    b' '
+                } else {
+                    out[out.len() - distance]
+                });
+            }
+        }
+    }
+    let mut stored = [0; 4];
+    for byte in &mut stored {
+        *byte = next()?;
+    }
+    if u32::from_le_bytes(stored) != checksum(&out) {
+        return Err(invalid("the checksum does not match"));
+    }
+    Ok(out)
+}
+
+/// Compresses data, the output can be larger than the input when little repeats
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + data.len() / 8 + 5);
+    let mut chains = Chains::new(data);
+    let mut flags = 0;
+    let mut bit = 8;
+    let mut position = 0;
+    while position < data.len() {
+        if bit == 8 {
+            flags = out.len();
+            out.push(0);
+            bit = 0;
+        }
+        let (distance, length) = chains.longest_match(position);
+        if length >= MIN_MATCH {
+            out.push((distance & 0xFF) as u8);
+            out.push((((distance >> 4) & 0xF0) | (length - MIN_MATCH)) as u8);
+            for position in position..position + length {
+                chains.insert(position);
+            }
+            position += length;
+        } else {
+            out[flags] |= 1 << bit;
+            out.push(data[position]);
+            chains.insert(position);
+            position += 1;
+        }
+        bit += 1;
+    }
+    out.extend_from_slice(&checksum(data).to_le_bytes());
+    out
+}
+
+/// The earlier positions of the data that start with the same three bytes
+struct Chains<'a> {
+    data: &'a [u8],
+    /// The last position of each hash
+    head: Vec<usize>,
+    /// The position before each position with the same hash
+    previous: Vec<usize>,
+}
+
+impl<'a> Chains<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            head: vec![NONE; HASH_SIZE],
+            previous: vec![NONE; data.len()],
+        }
+    }
+
+    fn insert(&mut self, position: usize) {
+        if position + MIN_MATCH <= self.data.len() {
+            let hash = hash(&self.data[position..]);
+            self.previous[position] = self.head[hash];
+            self.head[hash] = position;
+        }
+    }
+
+    /// The distance and length of the longest earlier match of the data at `position`
+    fn longest_match(&self, position: usize) -> (usize, usize) {
+        let data = self.data;
+        if position + MIN_MATCH > data.len() {
+            return (0, 0);
+        }
+        let max = MAX_MATCH.min(data.len() - position);
+        let (mut best_distance, mut best_length) = (0, 0);
+        let mut candidate = self.head[hash(&data[position..])];
+        for _ in 0..MAX_CHAIN {
+            if candidate == NONE || position - candidate > WINDOW {
+                break;
+            }
+            // The match can run past `position`, the decompressor copies one byte at a time
+            let length = (0..max)
+                .take_while(|&i| data[candidate + i] == data[position + i])
+                .count();
+            if length > best_length {
+                best_distance = position - candidate;
+                best_length = length;
+                if length == max {
+                    break;
+                }
+            }
+            candidate = self.previous[candidate];
+        }
+        (best_distance, best_length)
+    }
+}
+
+fn hash(bytes: &[u8]) -> usize {
+    ((usize::from(bytes[0]) << 8) ^ (usize::from(bytes[1]) << 4) ^ usize::from(bytes[2]))
+        % HASH_SIZE
+}
+
+/// The sum of the bytes, stored after the compressed data
+fn checksum(data: &[u8]) -> u32 {
+    data.iter()
+        .fold(0_u32, |sum, byte| sum.wrapping_add(u32::from(*byte)))
+}
+
+fn invalid(reason: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid LZSS data, {}", reason),
+    )
+}
@@ -22,6 +22,9 @@ impl Timestamp {
     }
 }
 
+/// The method of a file that is compressed with LZSS
+pub(crate) const COMPRESSED: u32 = 0x4370_7273;
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Header {
     pub(crate) filename: String,
@@ -80,6 +83,10 @@ impl Header {
     pub const fn size(&self) -> u32 {
         self.size
     }
+    /// If the file is stored compressed, `original` is then its size once decompressed
+    pub const fn is_compressed(&self) -> bool {
+        self.method == COMPRESSED
+    }
 }
 
 #[cfg(feature = "async-tokio")]
@@ -0,0 +1,81 @@
+use std::io::{Error, ErrorKind, Read, Result, Seek, SeekFrom};
+
+use crate::Header;
+
+/// A file in a PBO that is read from the PBO as it is needed
+///
+/// Positions are relative to the start of the file, and reading stops at its end.
+pub struct Entry<'a, I: Seek + Read> {
+    input: &'a mut I,
+    header: &'a Header,
+    start: u64,
+    position: u64,
+}
+
+impl<'a, I: Seek + Read> Entry<'a, I> {
+    pub(crate) fn new(input: &'a mut I, header: &'a Header, start: u64) -> Self {
+        Self {
+            input,
+            header,
+            start,
+            position: 0,
+        }
+    }
+
+    pub const fn header(&self) -> &Header {
+        self.header
+    }
+
+    /// The number of bytes the file is stored with
+    pub fn len(&self) -> u64 {
+        u64::from(self.header.size())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a, I: Seek + Read> Read for Entry<'a, I> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let remaining = self.len().saturating_sub(self.position);
+        if remaining == 0 || buf.is_empty() {
+            return Ok(0);
+        }
+        let count = buf.len().min(remaining as usize);
+        // The input can be shared with other entries, so its position is set on every read
+        self.input
+            .seek(SeekFrom::Start(self.start + self.position))?;
+        let read = self.input.read(&mut buf[..count])?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<'a, I: Seek + Read> Seek for Entry<'a, I> {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => offset_by(self.len(), offset),
+            SeekFrom::Current(offset) => offset_by(self.position, offset),
+        };
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            }
+            None => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+fn offset_by(base: u64, offset: i64) -> Option<u64> {
+    if offset < 0 {
+        base.checked_sub(offset.unsigned_abs())
+    } else {
+        base.checked_add(offset as u64)
+    }
+}
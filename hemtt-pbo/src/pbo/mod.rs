@@ -1,5 +1,7 @@
+mod entry;
 mod reading;
 mod writing;
 
+pub use entry::*;
 pub use reading::*;
 pub use writing::*;
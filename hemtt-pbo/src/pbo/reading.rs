@@ -1,10 +1,11 @@
+use std::collections::HashMap;
 use std::io::{Cursor, Error, Read, Seek, SeekFrom, Write};
 
 use hemtt_io::*;
 use indexmap::IndexMap;
 use sha1::{Digest, Sha1};
 
-use crate::{Header, Timestamp};
+use crate::{Entry, Header, Timestamp};
 
 #[derive(Default)]
 pub struct ReadablePbo<I: Seek + Read> {
@@ -13,6 +14,10 @@ pub struct ReadablePbo<I: Seek + Read> {
     checksum: Vec<u8>,
     input: I,
     blob_start: u64,
+    /// Where the data of each file starts, in the order of `headers`
+    offsets: Vec<u64>,
+    /// The position in `headers` of each file by its name in lowercase
+    index: HashMap<String, usize>,
 }
 
 impl<I: Seek + Read> ReadablePbo<I> {
@@ -24,6 +29,8 @@ impl<I: Seek + Read> ReadablePbo<I> {
             checksum: Vec::new(),
             input,
             blob_start: 0,
+            offsets: Vec::new(),
+            index: HashMap::new(),
         };
         loop {
             let (header, size) = Header::read(&mut pbo.input)?;
@@ -46,13 +53,15 @@ impl<I: Seek + Read> ReadablePbo<I> {
             }
         }
 
-        for header in &pbo.headers {
-            pbo.input
-                .seek(SeekFrom::Current(i64::from(header.size())))
-                .unwrap();
+        let mut offset = pbo.blob_start;
+        for (i, header) in pbo.headers.iter().enumerate() {
+            pbo.offsets.push(offset);
+            offset += u64::from(header.size());
+            // The first of files with the same name is the one that is read
+            pbo.index.entry(key(header.filename())).or_insert(i);
         }
 
-        pbo.input.seek(SeekFrom::Current(1))?;
+        pbo.input.seek(SeekFrom::Start(offset + 1))?;
         let mut checksum = vec![0; 20];
         pbo.input.read_exact(&mut checksum)?;
         pbo.checksum = checksum;
@@ -105,13 +114,10 @@ impl<I: Seek + Read> ReadablePbo<I> {
     }
 
     /// Finds a header if it exists
-    pub fn header(&mut self, filename: &str) -> Option<Header> {
-        for header in &self.headers {
-            if header.filename() == filename.replace('/', "\\").as_str() {
-                return Some(header.clone());
-            }
-        }
-        None
+    pub fn header(&self, filename: &str) -> Option<Header> {
+        self.index
+            .get(&key(filename))
+            .map(|i| self.headers[*i].clone())
     }
 
     pub fn extension(&self, key: &str) -> Option<&String> {
@@ -167,29 +173,97 @@ impl<I: Seek + Read> ReadablePbo<I> {
         h.update(headers.get_ref());
 
         for header in &files {
-            let cursor = self.retrieve(header.filename()).unwrap();
-            h.update(cursor.get_ref());
+            if let Some((_, data)) = self.read_file(header.filename())? {
+                h.update(&data);
+            }
         }
 
         Ok(h.finalize().to_vec())
     }
 
-    /// Retrieves a file from a PBO
+    /// Retrieves a file from a PBO, decompressing it if it is compressed
     pub fn retrieve(&mut self, filename: &str) -> Option<Cursor<Vec<u8>>> {
-        let filename_owned = filename.replace('/', "\\");
-        let filename = filename_owned.as_str();
-        self.input.seek(SeekFrom::Start(self.blob_start)).unwrap();
-        for h in &self.headers {
-            if h.filename().to_lowercase() == filename.to_lowercase() {
-                let mut buffer: Vec<u8> = vec![0; h.size() as usize];
-                self.input.read_exact(&mut buffer).unwrap();
-                return Some(Cursor::new(buffer));
-            } else {
-                self.input
-                    .seek(SeekFrom::Current(i64::from(h.size())))
-                    .unwrap();
+        let (header, data) = self.read_logged(filename)?;
+        if !header.is_compressed() {
+            return Some(Cursor::new(data));
+        }
+        match lzss::decompress(&data, header.original() as usize) {
+            Ok(data) => Some(Cursor::new(data)),
+            Err(e) => {
+                error!("Unable to decompress `{}`: {}", header.filename(), e);
+                None
             }
         }
-        None
     }
+
+    /// Retrieves a file from a PBO as it is stored, checksums and signatures are of these bytes
+    pub fn retrieve_stored(&mut self, filename: &str) -> Option<Cursor<Vec<u8>>> {
+        self.read_logged(filename)
+            .map(|(_, data)| Cursor::new(data))
+    }
+
+    /// A view of the data a file is stored with, without reading it into memory
+    ///
+    /// A compressed file is not decompressed, use [`ReadablePbo::retrieve`] for its contents.
+    pub fn entry(&mut self, filename: &str) -> Option<Entry<'_, I>> {
+        let i = *self.index.get(&key(filename))?;
+        Some(Entry::new(
+            &mut self.input,
+            &self.headers[i],
+            self.offsets[i],
+        ))
+    }
+
+    /// Reads the data a file is stored with, `None` if there is no such file
+    pub(crate) fn read_file(&mut self, filename: &str) -> Result<Option<(Header, Vec<u8>)>, Error> {
+        let i = match self.index.get(&key(filename)) {
+            Some(i) => *i,
+            None => return Ok(None),
+        };
+        let header = &self.headers[i];
+        self.input.seek(SeekFrom::Start(self.offsets[i]))?;
+        let mut buffer: Vec<u8> = vec![0; header.size() as usize];
+        self.input.read_exact(&mut buffer)?;
+        Ok(Some((header.clone(), buffer)))
+    }
+
+    fn read_logged(&mut self, filename: &str) -> Option<(Header, Vec<u8>)> {
+        match self.read_file(filename) {
+            Ok(file) => file,
+            Err(e) => {
+                error!("Unable to read `{}`: {}", filename, e);
+                None
+            }
+        }
+    }
+}
+
+impl<T: AsRef<[u8]>> ReadablePbo<Cursor<T>> {
+    /// The data a file is stored with, borrowed from a PBO that is in memory or mapped
+    pub fn entry_bytes(&self, filename: &str) -> Option<&[u8]> {
+        let i = *self.index.get(&key(filename))?;
+        let start = self.offsets[i] as usize;
+        self.input
+            .get_ref()
+            .as_ref()
+            .get(start..start + self.headers[i].size() as usize)
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl ReadablePbo<Cursor<memmap2::Mmap>> {
+    /// Open a PBO by mapping it into memory, for tools that read many PBOs
+    ///
+    /// The file must not be changed while it is open.
+    pub fn open_mmap<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Error> {
+        let file = std::fs::File::open(path)?;
+        // Safety: the map is read only, a file that changes while it is mapped is not supported
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Self::from(Cursor::new(map))
+    }
+}
+
+/// The name a file is found by
+fn key(filename: &str) -> String {
+    filename.replace('/', "\\").to_lowercase()
 }
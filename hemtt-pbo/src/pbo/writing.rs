@@ -1,21 +1,61 @@
 use std::convert::TryFrom;
-use std::io::{Cursor, Read, Result, Seek};
+use std::io::{BufWriter, Cursor, Read, Result, Seek};
 use std::{
     collections::HashMap,
     io::{SeekFrom, Write},
 };
 
-use hemtt_io::WriteExt;
+use hemtt_io::{lzss, WriteExt};
 use indexmap::IndexMap;
 use sha1::{Digest, Sha1};
 
+use crate::header::COMPRESSED;
 use crate::pbo::ReadablePbo;
 use crate::{Header, Timestamp};
 
+/// Which files are compressed when a PBO is written
+///
+/// A file is only stored compressed when that makes it smaller.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Compression {
+    extensions: Vec<String>,
+    min_size: u32,
+}
+
+impl Compression {
+    /// Compress files with one of the extensions, or with any extension when there are none,
+    /// that are at least `min_size` bytes
+    pub fn new(extensions: Vec<String>, min_size: u32) -> Self {
+        Self {
+            extensions: extensions
+                .into_iter()
+                .map(|ext| ext.trim_start_matches('.').to_lowercase())
+                .collect(),
+            min_size,
+        }
+    }
+
+    /// If a file of this name and size should be compressed
+    pub fn applies(&self, filename: &str, size: u32) -> bool {
+        if size < self.min_size {
+            return false;
+        }
+        if self.extensions.is_empty() {
+            return true;
+        }
+        let name = filename.rsplit('\\').next().unwrap_or(filename);
+        match name.rsplit_once('.') {
+            Some((_, ext)) => self.extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)),
+            None => false,
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct WritablePbo<I: Seek + Read> {
     extensions: IndexMap<String, String>,
     files: HashMap<String, (I, Header)>,
+    compression: Option<Compression>,
 }
 
 impl<I: Seek + Read> WritablePbo<I> {
@@ -24,9 +64,19 @@ impl<I: Seek + Read> WritablePbo<I> {
         Self {
             extensions: IndexMap::new(),
             files: HashMap::new(),
+            compression: None,
         }
     }
 
+    /// Compress files when the PBO is written, files are not compressed by default
+    pub fn set_compression(&mut self, compression: Option<Compression>) {
+        self.compression = compression;
+    }
+
+    pub const fn compression(&self) -> Option<&Compression> {
+        self.compression.as_ref()
+    }
+
     /// A list of filenames in the PBO
     pub fn files(&mut self) -> Result<Vec<Header>> {
         let mut filenames = Vec::new();
@@ -137,7 +187,24 @@ impl<I: Seek + Read> WritablePbo<I> {
     }
 
     /// Write the PBO file
+    ///
+    /// The files are streamed to the output, only a file that is compressed is read into memory.
     pub fn write<O: Write>(&mut self, output: &mut O, extensions: bool) -> Result<()> {
+        let mut output = BufWriter::with_capacity(1 << 16, output);
+        let hash = self.write_hashed(&mut output, extensions)?;
+        output.write_all(&[0])?;
+        debug!("pbo generated hash: {:?}", hash);
+        output.write_all(&hash)?;
+        output.flush()
+    }
+
+    /// Generate a checksum of the PBO
+    pub fn checksum(&mut self) -> Result<Vec<u8>> {
+        self.write_hashed(&mut std::io::sink(), true)
+    }
+
+    /// Writes the headers and files, returning the hash of what was written
+    fn write_hashed<O: Write>(&mut self, output: &mut O, extensions: bool) -> Result<Vec<u8>> {
         let mut headers: Cursor<Vec<u8>> = Cursor::new(Vec::new());
 
         let ext_header = Header {
@@ -171,7 +238,7 @@ impl<I: Seek + Read> WritablePbo<I> {
             headers.write_all(b"\0")?;
         }
 
-        let files_sorted = self.files_sorted()?;
+        let files_sorted = self.files_stored()?;
 
         for header in &files_sorted {
             header.write(&mut headers)?;
@@ -184,77 +251,99 @@ impl<I: Seek + Read> WritablePbo<I> {
         trace!("writing null header");
         header.write(&mut headers)?;
 
-        let mut h = Sha1::new();
-
+        let mut output = Hashed {
+            output,
+            hash: Sha1::new(),
+        };
         output.write_all(headers.get_ref())?;
-        h.update(headers.get_ref());
 
         for header in &files_sorted {
             trace!("writing file {}", header.filename());
-            let cursor = self.retrieve_file(header.filename())?.unwrap();
-            output.write_all(cursor.get_ref())?;
-            trace!("hashing");
-            h.update(cursor.get_ref());
+            let (file, source) = self
+                .files
+                .get_mut(&header.filename().replace('/', "\\"))
+                .unwrap();
+            file.seek(SeekFrom::Start(0))?;
+            let written = if header.is_compressed() && !source.is_compressed() {
+                let mut data = Vec::with_capacity(source.size() as usize);
+                file.read_to_end(&mut data)?;
+                let compressed = lzss::compress(&data);
+                output.write_all(&compressed)?;
+                compressed.len() as u64
+            } else {
+                std::io::copy(file, &mut output)?
+            };
+            if written != u64::from(header.size()) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "`{}` changed size from {} to {} bytes while writing",
+                        header.filename(),
+                        header.size(),
+                        written
+                    ),
+                ));
+            }
         }
 
-        output.write_all(&[0])?;
-        let hash = h.finalize().to_vec();
-        debug!("pbo generated hash: {:?}", hash);
-        output.write_all(&hash)?;
-
-        Ok(())
+        Ok(output.hash.finalize().to_vec())
     }
 
-    /// Generate a checksum of the PBO
-    pub fn checksum(&mut self) -> Result<Vec<u8>> {
-        let mut headers: Cursor<Vec<u8>> = Cursor::new(Vec::new());
-
-        let ext_header = Header {
-            filename: String::new(),
-            method: 0x5665_7273,
-            original: 0,
-            reserved: 0,
-            timestamp: Timestamp::from_u32(0),
-            size: 0,
-        };
-        ext_header.write(&mut headers)?;
-
-        if let Some(prefix) = self.extensions.get("prefix") {
-            headers.write_all(b"prefix\0")?;
-            headers.write_cstring(prefix)?;
-        }
-
-        for (key, value) in &self.extensions {
-            if key == "prefix" {
+    /// The headers the files are stored with in alphabetical order
+    ///
+    /// A file that is compressed is compressed here for its size, and again when it is written,
+    /// so that only one file is in memory at a time.
+    fn files_stored(&mut self) -> Result<Vec<Header>> {
+        let mut stored = Vec::new();
+        for header in self.files_sorted()? {
+            let compress = match &self.compression {
+                Some(compression) => {
+                    header.method() == 0 && compression.applies(header.filename(), header.size())
+                }
+                None => false,
+            };
+            if !compress {
+                stored.push(header);
                 continue;
             }
-            headers.write_cstring(key)?;
-            headers.write_cstring(value)?;
-        }
-        headers.write_all(b"\0")?;
-
-        let files_sorted = self.files_sorted()?;
-
-        for header in &files_sorted {
-            header.write(&mut headers)?;
+            let data = self.retrieve_file(header.filename())?.unwrap();
+            let compressed = lzss::compress(data.get_ref()).len();
+            if compressed < data.get_ref().len() {
+                trace!(
+                    "compressed `{}` from {} to {} bytes",
+                    header.filename(),
+                    data.get_ref().len(),
+                    compressed
+                );
+                stored.push(Header {
+                    method: COMPRESSED,
+                    original: data.get_ref().len() as u32,
+                    size: compressed as u32,
+                    ..header
+                });
+            } else {
+                stored.push(header);
+            }
         }
+        Ok(stored)
+    }
+}
 
-        let header = Header {
-            method: 0,
-            ..ext_header
-        };
-        header.write(&mut headers)?;
-
-        let mut h = Sha1::new();
-
-        h.update(headers.get_ref());
+/// Writes to an output and hashes what was written
+struct Hashed<O: Write> {
+    output: O,
+    hash: Sha1,
+}
 
-        for header in &files_sorted {
-            let cursor = self.retrieve_file(header.filename())?.unwrap();
-            h.update(cursor.get_ref());
-        }
+impl<O: Write> Write for Hashed<O> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let written = self.output.write(buf)?;
+        self.hash.update(&buf[..written]);
+        Ok(written)
+    }
 
-        Ok(h.finalize().to_vec())
+    fn flush(&mut self) -> Result<()> {
+        self.output.flush()
     }
 }
 
@@ -264,11 +353,9 @@ impl<B: Seek + Read> TryFrom<ReadablePbo<B>> for WritablePbo<Cursor<Vec<u8>>> {
     fn try_from(mut rp: ReadablePbo<B>) -> Result<Self> {
         let mut pbo = Self::new();
         for header in rp.files() {
-            pbo.add_file_header(
-                header.filename(),
-                rp.retrieve(header.filename()).unwrap(),
-                header.clone(),
-            )?;
+            if let Some((_, data)) = rp.read_file(header.filename())? {
+                pbo.add_file_header(header.filename(), Cursor::new(data), header.clone())?;
+            }
         }
         for (key, value) in rp.extensions() {
             pbo.add_extension(key, value);
@@ -279,9 +366,38 @@ impl<B: Seek + Read> TryFrom<ReadablePbo<B>> for WritablePbo<Cursor<Vec<u8>>> {
 
 #[cfg(test)]
 mod tests {
-    use std::io::Cursor;
+    use std::io::{Cursor, Read, Result, Seek, SeekFrom};
 
     use super::WritablePbo;
+    use crate::{Header, ReadablePbo, Timestamp};
+
+    /// A file that is generated as it is read
+    struct Generated {
+        position: u64,
+        len: u64,
+    }
+
+    impl Read for Generated {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let count = buf.len().min((self.len - self.position) as usize);
+            for byte in &mut buf[..count] {
+                *byte = (self.position % 251) as u8;
+                self.position += 1;
+            }
+            Ok(count)
+        }
+    }
+
+    impl Seek for Generated {
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+            self.position = match pos {
+                SeekFrom::Start(offset) => offset,
+                SeekFrom::End(offset) => (self.len as i64 + offset) as u64,
+                SeekFrom::Current(offset) => (self.position as i64 + offset) as u64,
+            };
+            Ok(self.position)
+        }
+    }
 
     #[test]
     fn empty_pbo() {
@@ -353,4 +469,46 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn streamed() {
+        let len = 8 * 1024 * 1024;
+        let mut pbo = WritablePbo::new();
+        pbo.add_extension("prefix", "foobar");
+        pbo.add_file("data\\terrain.wrp", Generated { position: 0, len })
+            .unwrap();
+        let mut buffer = Vec::new();
+        pbo.write(&mut Cursor::new(&mut buffer), true).unwrap();
+        let checksum = pbo.checksum().unwrap();
+        assert_eq!(&buffer[buffer.len() - 20..], checksum.as_slice());
+
+        let mut read = ReadablePbo::from(Cursor::new(buffer)).unwrap();
+        assert_eq!(read.gen_checksum().unwrap(), checksum);
+        let data = read.retrieve("data\\terrain.wrp").unwrap().into_inner();
+        assert_eq!(data.len() as u64, len);
+        assert!(data
+            .iter()
+            .enumerate()
+            .all(|(i, byte)| *byte == (i % 251) as u8));
+    }
+
+    #[test]
+    fn changed_size() {
+        let mut pbo = WritablePbo::new();
+        pbo.add_file_header(
+            "test.txt",
+            Cursor::new(b"test".to_vec()),
+            Header {
+                filename: String::from("test.txt"),
+                method: 0,
+                original: 5,
+                reserved: 0,
+                timestamp: Timestamp::from_u32(0),
+                size: 5,
+            },
+        )
+        .unwrap();
+        assert!(pbo.write(&mut Vec::new(), true).is_err());
+        assert!(pbo.checksum().is_err());
+    }
 }
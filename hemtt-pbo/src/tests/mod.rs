@@ -59,5 +59,10 @@ pub fn file(pbo: &mut ReadablePbo<File>, file: &str, content: String) {
     let data = pbo.retrieve(file).unwrap();
     let data = String::from_utf8(data.into_inner().to_vec()).unwrap();
     assert_eq!(data, content);
-    assert_eq!(pbo.header(file).unwrap().size() as usize, data.len());
+    let header = pbo.header(file).unwrap();
+    if header.is_compressed() {
+        assert_eq!(header.original() as usize, data.len());
+    } else {
+        assert_eq!(header.size() as usize, data.len());
+    }
 }
@@ -0,0 +1,112 @@
+use std::convert::TryInto;
+use std::io::Cursor;
+
+use hemtt_io::lzss;
+use hemtt_pbo::{Compression, ReadablePbo, WritablePbo};
+
+const SQF: &str = "#include \"script_component.hpp\"\r\n\r\nPREP(handleFired);\r\nPREP(handleHit);\r\nPREP(handleKilled);\r\nPREP(handleRespawn);\r\n";
+
+#[test]
+fn decompress() {
+    // A literal and a reference to it
+    assert_eq!(
+        lzss::decompress(&[0x01, b'a', 0x01, 0x06, 0xCA, 0x03, 0, 0], 10).unwrap(),
+        b"aaaaaaaaaa"
+    );
+    // A reference to before the start of the data
+    assert_eq!(
+        lzss::decompress(&[0x00, 0x05, 0x00, 0x60, 0, 0, 0], 3).unwrap(),
+        b"   "
+    );
+}
+
+#[test]
+fn corrupt() {
+    // Wrong checksum
+    assert!(lzss::decompress(&[0x01, b'a', 0x01, 0x06, 0xCB, 0x03, 0, 0], 10).is_err());
+    // Ends early
+    assert!(lzss::decompress(&[0x01, b'a', 0x01], 10).is_err());
+    // A reference to itself
+    assert!(lzss::decompress(&[0x00, 0x00, 0x00, 0, 0, 0, 0], 3).is_err());
+}
+
+#[test]
+fn round_trip() {
+    let mut noise = Vec::new();
+    let mut seed: u32 = 1;
+    for _ in 0..10_000 {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        noise.push((seed >> 16) as u8);
+    }
+    let repeated = SQF.repeat(200).into_bytes();
+    for data in [
+        Vec::new(),
+        b"a".to_vec(),
+        b"   leading spaces".to_vec(),
+        vec![0; 5000],
+        noise,
+        repeated.clone(),
+    ] {
+        let compressed = lzss::compress(&data);
+        assert_eq!(lzss::decompress(&compressed, data.len()).unwrap(), data);
+    }
+    assert!(lzss::compress(&repeated).len() < repeated.len() / 5);
+}
+
+#[test]
+fn applies() {
+    let compression = Compression::new(vec![String::from(".sqf"), String::from("hpp")], 16);
+    assert!(compression.applies("functions\\fnc_test.SQF", 16));
+    assert!(compression.applies("script_component.hpp", 100));
+    assert!(!compression.applies("functions\\fnc_test.sqf", 15));
+    assert!(!compression.applies("data\\texture.paa", 100));
+    assert!(!compression.applies("sqf", 100));
+    assert!(Compression::new(Vec::new(), 0).applies("texture.paa", 0));
+}
+
+#[test]
+fn pbo() {
+    let mut pbo = WritablePbo::<Cursor<Vec<u8>>>::new();
+    pbo.add_extension("prefix", "z\\abe\\addons\\main");
+    pbo.add_file("XEH_PREP.sqf", Cursor::new(SQF.as_bytes().to_vec()))
+        .unwrap();
+    pbo.add_file("short.sqf", Cursor::new(b"call abe;".to_vec()))
+        .unwrap();
+    pbo.add_file("config.cpp", Cursor::new(SQF.as_bytes().to_vec()))
+        .unwrap();
+    pbo.set_compression(Some(Compression::new(vec![String::from("sqf")], 0)));
+    let mut buffer = Vec::new();
+    pbo.write(&mut buffer, true).unwrap();
+    let checksum = pbo.checksum().unwrap();
+
+    let mut read = ReadablePbo::from(Cursor::new(buffer)).unwrap();
+    assert_eq!(read.checksum(), checksum);
+    assert_eq!(read.gen_checksum().unwrap(), checksum);
+
+    let prep = read.header("XEH_PREP.sqf").unwrap();
+    assert!(prep.is_compressed());
+    assert_eq!(prep.original() as usize, SQF.len());
+    assert!((prep.size() as usize) < SQF.len());
+    assert_eq!(
+        read.retrieve("XEH_PREP.sqf").unwrap().into_inner(),
+        SQF.as_bytes()
+    );
+    assert_eq!(
+        read.retrieve_stored("XEH_PREP.sqf")
+            .unwrap()
+            .into_inner()
+            .len(),
+        prep.size() as usize
+    );
+    // Compressing does not make it smaller, and the extension is not compressed
+    assert!(!read.header("short.sqf").unwrap().is_compressed());
+    assert!(!read.header("config.cpp").unwrap().is_compressed());
+    assert_eq!(
+        read.retrieve("short.sqf").unwrap().into_inner(),
+        b"call abe;"
+    );
+
+    // The compressed data is kept when the PBO is written again
+    let mut writable: WritablePbo<Cursor<Vec<u8>>> = read.try_into().unwrap();
+    assert_eq!(writable.checksum().unwrap(), checksum);
+}
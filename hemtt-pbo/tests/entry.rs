@@ -0,0 +1,76 @@
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+use hemtt_pbo::ReadablePbo;
+
+const PATH: &str = "tests/ace_weather.pbo_cba6f72c";
+const PRESTART: &str = "#include \"script_component.hpp\"\r\n\r\n#include \"XEH_PREP.hpp\"\r\n";
+
+#[test]
+fn entry() {
+    let mut pbo = ReadablePbo::from(File::open(PATH).unwrap()).unwrap();
+    let mut entry = pbo.entry("xeh_prestart.sqf").unwrap();
+    assert_eq!(entry.header().filename(), "XEH_preStart.sqf");
+    assert_eq!(entry.len(), PRESTART.len() as u64);
+
+    let mut content = String::new();
+    entry.read_to_string(&mut content).unwrap();
+    assert_eq!(content, PRESTART);
+    assert_eq!(entry.read(&mut [0; 8]).unwrap(), 0);
+
+    entry.seek(SeekFrom::Start(10)).unwrap();
+    let mut buf = [0; 6];
+    entry.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"script");
+    entry.seek(SeekFrom::End(-6)).unwrap();
+    let mut end = Vec::new();
+    entry.read_to_end(&mut end).unwrap();
+    assert_eq!(end, b"hpp\"\r\n");
+    assert!(entry.seek(SeekFrom::Current(-100)).is_err());
+
+    assert!(pbo.entry("not_real").is_none());
+}
+
+#[test]
+fn random_access() {
+    let mut pbo = ReadablePbo::from(File::open(PATH).unwrap()).unwrap();
+    let files = pbo.files();
+    // Every file is found from any position of the input, in any order and case
+    for header in files.iter().rev() {
+        let data = pbo.retrieve(&header.filename().to_uppercase()).unwrap();
+        assert_eq!(data.get_ref().len(), header.size() as usize);
+        assert_eq!(
+            pbo.header(&header.filename().replace('\\', "/")).unwrap(),
+            *header
+        );
+    }
+}
+
+#[test]
+fn bytes() {
+    let mut data = Vec::new();
+    File::open(PATH).unwrap().read_to_end(&mut data).unwrap();
+    let mut pbo = ReadablePbo::from(Cursor::new(data)).unwrap();
+    assert_eq!(
+        pbo.entry_bytes("XEH_preStart.sqf").unwrap(),
+        PRESTART.as_bytes()
+    );
+    assert!(pbo.entry_bytes("not_real").is_none());
+    assert_eq!(pbo.gen_checksum().unwrap(), pbo.checksum());
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn mmap() {
+    let mut pbo = ReadablePbo::open_mmap(PATH).unwrap();
+    assert_eq!(pbo.files().len(), 41);
+    assert_eq!(
+        pbo.entry_bytes("XEH_preStart.sqf").unwrap(),
+        PRESTART.as_bytes()
+    );
+    assert_eq!(
+        pbo.retrieve("XEH_preStart.sqf").unwrap().into_inner(),
+        PRESTART.as_bytes()
+    );
+    assert_eq!(pbo.gen_checksum().unwrap(), pbo.checksum());
+}
@@ -86,7 +86,7 @@ fn bi_3den() {
     if !Path::new("tests/3den.pbo").exists() {
         return;
     }
-    let mut pbo = pbo(
+    let pbo = pbo(
         File::open("tests/3den.pbo").unwrap(),
         368,
         true,
@@ -75,7 +75,7 @@ impl Command for Verify {
                 new_pbo
                     .add_file_header(
                         f.filename(),
-                        pbo.retrieve(f.filename()).unwrap(),
+                        pbo.retrieve_stored(f.filename()).unwrap(),
                         f.to_owned(),
                     )
                     .unwrap();
@@ -66,7 +66,7 @@ pub fn namehash<I: Seek + Read>(pbo: &mut ReadablePbo<I>) -> DigestBytes {
     let files = pbo.files();
 
     for header in &files {
-        let data = pbo.retrieve(header.filename()).unwrap();
+        let data = pbo.retrieve_stored(header.filename()).unwrap();
         if data.get_ref().is_empty() {
             continue;
         }
@@ -127,7 +127,7 @@ pub fn filehash<I: Seek + Read>(pbo: &mut ReadablePbo<I>, version: BISignVersion
                 }
             }
         }
-        let cursor = pbo.retrieve(header.filename()).unwrap();
+        let cursor = pbo.retrieve_stored(header.filename()).unwrap();
         h.update(cursor.get_ref()).unwrap();
         nothing = false;
     }
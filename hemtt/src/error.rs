@@ -16,12 +16,20 @@ pub struct PreprocessError {
     pub source: Box<HEMTTError>,
 }
 
+#[derive(Debug)]
+pub struct ConfigError {
+    pub path: Option<String>,
+    pub message: String,
+    pub source: Box<HEMTTError>,
+}
+
 #[derive(Debug)]
 pub enum HEMTTError {
     User(String),
     UserHint(String, String),
     Generic(String),
     Preprocess(PreprocessError),
+    Config(ConfigError),
     IO(std::io::Error),
     IOPath(IOPathError),
     SemVer(semver::SemVerError),
@@ -76,6 +84,7 @@ impl std::fmt::Display for HEMTTError {
             Self::UserHint(ref s, ref h) => write!(f, "{}\ntry: {}", s, h),
             Self::Generic(ref s) => write!(f, "{}", s),
             Self::Preprocess(ref e) => write!(f, "Preprocessor: {}", e.message),
+            Self::Config(ref e) => write!(f, "Config: {}", e.message),
             Self::IO(ref e) => write!(f, "IO error: {}", e),
             Self::IOPath(ref e) => write!(f, "IO error: `{:#?}`\n{}", e.path, e.source),
             Self::SemVer(ref e) => write!(f, "SemVer error: `{}`", e),
@@ -124,6 +133,7 @@ impl std::error::Error for HEMTTError {
             Self::UserHint(_, ref _h) => Some(self),
             Self::Generic(_) => Some(self),
             Self::Preprocess(ref e) => Some(&e.source),
+            Self::Config(ref e) => Some(&e.source),
             Self::IO(ref e) => Some(e),
             Self::IOPath(ref e) => Some(&e.source),
             Self::SemVer(ref e) => Some(e),
@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use vfs::PhysicalFS;
 
+use hemtt_arma_config::LinterOptions;
 use semver::Version;
 use serde::{Deserialize, Serialize};
 
@@ -81,6 +82,10 @@ pub struct Project {
     #[serde(default = "default_include")]
     pub include: Vec<PathBuf>,
 
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    #[serde(default = "HashMap::new")]
+    pub mount: HashMap<String, PathBuf>,
+
     #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(default = "Vec::new")]
     pub exclude: Vec<String>,
@@ -93,6 +98,11 @@ pub struct Project {
     #[serde(default = "default_folder_optionals")]
     pub folder_optionals: Option<bool>,
 
+    /// Patches from other mods that addons may require, glob patterns are allowed
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default = "Vec::new")]
+    pub dependencies: Vec<String>,
+
     // Signing
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default = "default_reuse_private_key")]
@@ -131,6 +141,12 @@ pub struct Project {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(default = "Vec::new")]
     pub releasebuild: Vec<String>,
+
+    // Lints
+    /// Options for checking and formatting configs
+    #[serde(skip_serializing_if = "LinterOptions::is_default")]
+    #[serde(default)]
+    pub lint: LinterOptions,
     // #[serde(skip_serializing_if = "HashMap::is_empty")]
     // #[serde(default = "HashMap::new")]
     // pub scripts: HashMap<String, crate::BuildScript>,
@@ -181,6 +197,7 @@ impl Project {
             header_exts: HashMap::new(),
 
             include: default_include(),
+            mount: HashMap::new(),
             exclude: Vec::new(),
             files: if std::path::Path::new("mod.cpp").exists() {
                 vec!["mod.cpp".to_owned()]
@@ -188,6 +205,7 @@ impl Project {
                 Vec::new()
             },
             folder_optionals: default_folder_optionals(),
+            dependencies: Vec::new(),
 
             reuse_private_key: default_reuse_private_key(),
             key_name: String::new(),
@@ -198,6 +216,8 @@ impl Project {
             postbuild: Vec::new(),
             prebuild: Vec::new(),
             releasebuild: Vec::new(),
+
+            lint: LinterOptions::default(),
             // scripts: HashMap::new(),
         }
     }
@@ -365,3 +385,34 @@ impl From<&Project> for hemtt_handlebars::Variables {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use config::{Config, File, FileFormat};
+
+    fn read(toml: &str) -> Result<super::Project, config::ConfigError> {
+        Config::builder()
+            .add_source(File::from_str(toml, FileFormat::Toml))
+            .build()?
+            .try_deserialize()
+    }
+
+    #[test]
+    fn lint() {
+        let project = read("name = \"Test\"\nprefix = \"test\"\nauthor = \"Tester\"\n").unwrap();
+        assert!(project.lint.is_default());
+        let project =
+            read("name = \"Test\"\nprefix = \"test\"\nauthor = \"Tester\"\n[lint]\nindent = 2\n")
+                .unwrap();
+        assert_eq!(project.lint.indent(), 2);
+        assert_eq!(project.lint.array_width(), 100);
+        assert!(read(
+            "name = \"Test\"\nprefix = \"test\"\nauthor = \"Tester\"\n[lint]\nindnet = 2\n",
+        )
+        .is_err());
+        assert!(read(
+            "name = \"Test\"\nprefix = \"test\"\nauthor = \"Tester\"\n[lint]\nindent = \"two\"\n",
+        )
+        .is_err());
+    }
+}